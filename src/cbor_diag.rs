@@ -0,0 +1,255 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Rendering of CBOR data in the [diagnostic notation] of RFC 8949.
+//!
+//! The notation is rendered from the bytes, not a deserialized value, so the major types, tags,
+//! byte strings, and indefinite lengths of the data are all visible. Byte strings are written in
+//! lowercase hexadecimal, like `h'01ff'`, text strings are escaped like JSON, and an indefinite
+//! length is written with an underscore, like `[_ 1, 2]`.
+//!
+//! [diagnostic notation]: https://www.rfc-editor.org/rfc/rfc8949.html#section-8
+
+use byteorder::{BigEndian, ByteOrder};
+use serde_json;
+use std::str;
+
+use super::{Error, Result};
+
+/// The initial byte that ends an item of indefinite length.
+const BREAK: u8 = 0xFF;
+
+/// Renders a CBOR data item in diagnostic notation.
+///
+/// # Errors
+///
+/// A `Generic` error occurs if the data is truncated, there is an invalid initial byte or
+/// indefinite length, a text string is not valid UTF-8, or there are bytes after the data item.
+pub fn to_string(data: &[u8]) -> Result<String> {
+    let mut decoder = Decoder { data, position: 0 };
+    let mut diag = String::new();
+    decoder.item(&mut diag)?;
+    if decoder.position != data.len() {
+        return Err(invalid("Trailing bytes after the CBOR data item"));
+    }
+    Ok(diag)
+}
+
+/// A reader of the data items of CBOR data.
+struct Decoder<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn next(&mut self) -> Result<u8> {
+        let byte = self.peek()?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn peek(&self) -> Result<u8> {
+        self.data
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| invalid("Unexpected end of the CBOR data"))
+    }
+
+    fn take(&mut self, len: u64) -> Result<&'a [u8]> {
+        let data = self.data;
+        let remaining = (data.len() - self.position) as u64;
+        if len > remaining {
+            return Err(invalid("Unexpected end of the CBOR data"));
+        }
+        let end = self.position + len as usize;
+        let bytes = &data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Indicates the next byte ends an item of indefinite length, and skips it if it does.
+    fn is_break(&mut self) -> Result<bool> {
+        if self.peek()? == BREAK {
+            self.position += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Reads the argument of the additional information of an initial byte, or `None` for an
+    /// indefinite length.
+    fn argument(&mut self, info: u8) -> Result<Option<u64>> {
+        Ok(Some(match info {
+            0..=23 => u64::from(info),
+            24 => u64::from(self.next()?),
+            25 => u64::from(BigEndian::read_u16(self.take(2)?)),
+            26 => u64::from(BigEndian::read_u32(self.take(4)?)),
+            27 => BigEndian::read_u64(self.take(8)?),
+            31 => return Ok(None),
+            _ => return Err(invalid(&format!("Invalid CBOR additional information {}", info))),
+        }))
+    }
+
+    fn item(&mut self, diag: &mut String) -> Result<()> {
+        let initial = self.next()?;
+        let major = initial >> 5;
+        let info = initial & 0x1F;
+        if major == 7 {
+            return self.simple(diag, info);
+        }
+        let argument = self.argument(info)?;
+        match (major, argument) {
+            (0, Some(n)) => diag.push_str(&n.to_string()),
+            (1, Some(n)) => diag.push_str(&(-1 - i128::from(n)).to_string()),
+            (2, Some(len)) => push_bytes(diag, self.take(len)?),
+            (3, Some(len)) => push_text(diag, self.take(len)?)?,
+            (2, None) | (3, None) => {
+                diag.push_str("(_ ");
+                let mut first = true;
+                while !self.is_break()? {
+                    let chunk = self.next()?;
+                    if chunk >> 5 != major || chunk & 0x1F == 31 {
+                        return Err(invalid("Invalid chunk of an indefinite length CBOR string"));
+                    }
+                    if !first {
+                        diag.push_str(", ");
+                    }
+                    first = false;
+                    let len = self.argument(chunk & 0x1F)?.unwrap_or(0);
+                    if major == 2 {
+                        push_bytes(diag, self.take(len)?);
+                    } else {
+                        push_text(diag, self.take(len)?)?;
+                    }
+                }
+                diag.push(')');
+            }
+            (4, len) => {
+                diag.push('[');
+                if len.is_none() {
+                    diag.push_str("_ ");
+                }
+                self.items(diag, len, false)?;
+                diag.push(']');
+            }
+            (5, len) => {
+                diag.push('{');
+                if len.is_none() {
+                    diag.push_str("_ ");
+                }
+                self.items(diag, len, true)?;
+                diag.push('}');
+            }
+            (6, Some(tag)) => {
+                diag.push_str(&tag.to_string());
+                diag.push('(');
+                self.item(diag)?;
+                diag.push(')');
+            }
+            _ => return Err(invalid(&format!("Invalid CBOR initial byte 0x{:02X}", initial))),
+        }
+        Ok(())
+    }
+
+    /// Renders the items of an array, or the pairs of a map, separated by commas.
+    fn items(&mut self, diag: &mut String, len: Option<u64>, pairs: bool) -> Result<()> {
+        let mut count = 0;
+        loop {
+            let done = match len {
+                Some(len) => count == len,
+                None => self.is_break()?,
+            };
+            if done {
+                return Ok(());
+            }
+            if count > 0 {
+                diag.push_str(", ");
+            }
+            self.item(diag)?;
+            if pairs {
+                diag.push_str(": ");
+                self.item(diag)?;
+            }
+            count += 1;
+        }
+    }
+
+    fn simple(&mut self, diag: &mut String, info: u8) -> Result<()> {
+        match info {
+            20 => diag.push_str("false"),
+            21 => diag.push_str("true"),
+            22 => diag.push_str("null"),
+            23 => diag.push_str("undefined"),
+            0..=19 => diag.push_str(&format!("simple({})", info)),
+            24 => diag.push_str(&format!("simple({})", self.next()?)),
+            25 => push_float(diag, half_to_f64(BigEndian::read_u16(self.take(2)?))),
+            26 => push_float(diag, f64::from(BigEndian::read_f32(self.take(4)?))),
+            27 => push_float(diag, BigEndian::read_f64(self.take(8)?)),
+            _ => return Err(invalid(&format!("Invalid CBOR simple value {}", info))),
+        }
+        Ok(())
+    }
+}
+
+fn push_bytes(diag: &mut String, bytes: &[u8]) {
+    diag.push_str("h'");
+    for byte in bytes {
+        diag.push_str(&format!("{:02x}", byte));
+    }
+    diag.push('\'');
+}
+
+fn push_text(diag: &mut String, bytes: &[u8]) -> Result<()> {
+    let text = str::from_utf8(bytes)
+        .map_err(|e| invalid(&format!("Invalid UTF-8 in a CBOR text string: {}", e)))?;
+    diag.push_str(&serde_json::to_string(text)?);
+    Ok(())
+}
+
+/// Renders a float, which always has a decimal point or an exponent, so it is not confused with
+/// an integer.
+fn push_float(diag: &mut String, n: f64) {
+    if n.is_nan() {
+        diag.push_str("NaN");
+    } else if n.is_infinite() {
+        diag.push_str(if n > 0.0 { "Infinity" } else { "-Infinity" });
+    } else {
+        diag.push_str(&format!("{:?}", n));
+    }
+}
+
+/// Converts the bits of a half-precision float to a float, like Appendix D of RFC 8949.
+fn half_to_f64(bits: u16) -> f64 {
+    let exponent = (bits >> 10) & 0x1F;
+    let mantissa = f64::from(bits & 0x3FF);
+    let n = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => std::f64::INFINITY,
+        31 => std::f64::NAN,
+        _ => (mantissa + 1024.0) * 2f64.powi(i32::from(exponent) - 25),
+    };
+    if bits & 0x8000 != 0 {
+        -n
+    } else {
+        n
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Generic(String::from(message))
+}
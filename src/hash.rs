@@ -0,0 +1,52 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Digests, or hashes, of serialized data for checksums.
+//!
+//! The digest algorithms are optional dependencies behind the `digest` feature. If the feature is
+//! not enabled, then computing a digest fails with an error.
+
+#[cfg(not(feature = "digest"))]
+use super::Error;
+use super::{Digest, Result};
+
+/// Computes the digest of the data and formats it as lowercase hexadecimal.
+///
+/// The CRC-32 is formatted in Big Endian (Network Order), like the `crc32` command.
+#[cfg(feature = "digest")]
+pub fn to_hex(digest: Digest, data: &[u8]) -> Result<String> {
+    let bytes = match digest {
+        Digest::Crc32 => crc32fast::hash(data).to_be_bytes().to_vec(),
+        Digest::Md5 => <md5::Md5 as md5::Digest>::digest(data).to_vec(),
+        Digest::Sha1 => <sha1::Sha1 as sha1::Digest>::digest(data).to_vec(),
+        Digest::Sha256 => <sha2::Sha256 as sha2::Digest>::digest(data).to_vec(),
+    };
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Computes the digest of the data and formats it as lowercase hexadecimal.
+///
+/// # Errors
+///
+/// A `Generic` error always occurs because the `digest` feature is not enabled.
+#[cfg(not(feature = "digest"))]
+pub fn to_hex(digest: Digest, _data: &[u8]) -> Result<String> {
+    Err(Error::Generic(format!(
+        "The {} checksum requires the 'digest' feature",
+        digest
+    )))
+}
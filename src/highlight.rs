@@ -0,0 +1,176 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Highlighting of the structure of serialized binary data with colors.
+//!
+//! Each byte of MessagePack and CBOR data is classified as a marker, i.e. a format marker or
+//! initial byte, a length, i.e. the bytes of a string length or a collection count, or a payload.
+//! Markers and lengths are painted with ANSI colors and the payload is not painted. Bytes of all
+//! other formats are payload.
+
+use ansi_term::Colour;
+
+use super::ToFormat;
+
+const MARKER_COLOR: Colour = Colour::Fixed(14); // bright cyan
+const LENGTH_COLOR: Colour = Colour::Fixed(11); // bright yellow
+const EXPECTED_COLOR: Colour = Colour::Fixed(9); // bright red
+const ACTUAL_COLOR: Colour = Colour::Fixed(10); // bright green
+
+/// The kind of a byte within serialized binary data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// A format marker, initial byte, or framing byte.
+    Marker,
+    /// A byte of a length or count.
+    Length,
+    /// A byte of a value, or any other byte.
+    Payload,
+    /// A byte of the reference data that differs from the output data in a diff.
+    Expected,
+    /// A byte of the output data that differs from the reference data in a diff.
+    Actual,
+}
+
+/// Classifies each byte of the serialized binary data based on the format.
+///
+/// Truncated or invalid data does not fail. The remaining bytes are payload.
+pub fn kinds(data: &[u8], to: ToFormat) -> Vec<Kind> {
+    let mut kinds = vec![Kind::Payload; data.len()];
+    match to {
+        ToFormat::Cbor => cbor(data, &mut kinds),
+        ToFormat::Msgpack => msgpack(data, &mut kinds),
+        _ => {}
+    }
+    kinds
+}
+
+/// Paints a displayed byte with the color for its kind.
+pub fn paint(kind: Kind, byte: &str) -> String {
+    match kind {
+        Kind::Marker => MARKER_COLOR.paint(byte).to_string(),
+        Kind::Length => LENGTH_COLOR.paint(byte).to_string(),
+        Kind::Payload => byte.to_owned(),
+        Kind::Expected => EXPECTED_COLOR.paint(byte).to_string(),
+        Kind::Actual => ACTUAL_COLOR.paint(byte).to_string(),
+    }
+}
+
+/// Classifies the bytes of MessagePack data.
+fn msgpack(data: &[u8], kinds: &mut [Kind]) {
+    let mut i = 0;
+    while i < data.len() {
+        let marker = data[i];
+        kinds[i] = Kind::Marker;
+        i += 1;
+        // The number of length bytes, if the length is a count of elements instead of bytes, the
+        // number of payload bytes without a length, and if an extension type byte follows.
+        let (length_size, count, fixed, ext) = match marker {
+            0xa0..=0xbf => (0, false, (marker & 0x1f) as usize, false),
+            0xc4 | 0xd9 => (1, false, 0, false),
+            0xc5 | 0xda => (2, false, 0, false),
+            0xc6 | 0xdb => (4, false, 0, false),
+            0xc7 => (1, false, 0, true),
+            0xc8 => (2, false, 0, true),
+            0xc9 => (4, false, 0, true),
+            0xca => (0, false, 4, false),
+            0xcb => (0, false, 8, false),
+            0xcc | 0xd0 => (0, false, 1, false),
+            0xcd | 0xd1 => (0, false, 2, false),
+            0xce | 0xd2 => (0, false, 4, false),
+            0xcf | 0xd3 => (0, false, 8, false),
+            0xd4 => (0, false, 1, true),
+            0xd5 => (0, false, 2, true),
+            0xd6 => (0, false, 4, true),
+            0xd7 => (0, false, 8, true),
+            0xd8 => (0, false, 16, true),
+            0xdc | 0xde => (2, true, 0, false),
+            0xdd | 0xdf => (4, true, 0, false),
+            _ => (0, false, 0, false),
+        };
+        let length = big_endian(data, i, length_size);
+        mark(kinds, i, length_size, Kind::Length);
+        i += length_size;
+        if ext {
+            mark(kinds, i, 1, Kind::Marker);
+            i += 1;
+        }
+        i = i.saturating_add(if count { fixed } else { fixed.saturating_add(length) });
+    }
+}
+
+/// Classifies the bytes of CBOR data.
+fn cbor(data: &[u8], kinds: &mut [Kind]) {
+    let mut i = 0;
+    while i < data.len() {
+        let major = data[i] >> 5;
+        let info = data[i] & 0x1f;
+        kinds[i] = Kind::Marker;
+        i += 1;
+        let size = match info {
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => 0,
+        };
+        let argument = if size == 0 {
+            info as usize
+        } else {
+            big_endian(data, i, size)
+        };
+        match major {
+            // Byte and text strings, where the argument is the length of the payload, unless it
+            // is an indefinite length string of chunks.
+            2 | 3 => {
+                mark(kinds, i, size, Kind::Length);
+                i += size;
+                if info != 31 {
+                    i = i.saturating_add(argument);
+                }
+            }
+            // Arrays and maps, where the argument is the number of elements.
+            4 | 5 => {
+                mark(kinds, i, size, Kind::Length);
+                i += size;
+            }
+            // Tags, where the argument is the tag number.
+            6 => {
+                mark(kinds, i, size, Kind::Marker);
+                i += size;
+            }
+            // Integers, floats, and simple values, where the argument is the value.
+            _ => i += size,
+        }
+    }
+}
+
+/// Sets the kind of a range of bytes, ignoring bytes past the end of the data.
+fn mark(kinds: &mut [Kind], start: usize, len: usize, kind: Kind) {
+    for k in kinds.iter_mut().skip(start).take(len) {
+        *k = kind;
+    }
+}
+
+/// Reads an unsigned Big Endian integer, which is zero (0) if the data is truncated.
+fn big_endian(data: &[u8], start: usize, len: usize) -> usize {
+    data.get(start..start.saturating_add(len)).map_or(0, |bytes| {
+        bytes
+            .iter()
+            .fold(0usize, |acc, b| acc.saturating_mul(256).saturating_add(*b as usize))
+    })
+}
@@ -157,10 +157,52 @@
 //! | 3    | Failure, Input/Output (IO)         |
 //! | 4    | Failure, error parsing integer     |
 //! | 5    | Failure, error with UTF-8 encoding |
+//! | 6    | Failure, output differs            |
+//!
+//! The codes are the variants of the `ExitCode` enum, which is returned by the `Error::code`
+//! method.
+//!
+//! ### Round Trips
+//!
+//! Transcoding a value from JSON to a format and back to JSON gives the same value for the
+//! following formats, which is checked by the round-trip tests for a corpus of values. A format
+//! that is lossless for a subset of values fails to serialize, or changes, any other value.
+//!
+//! | Format      | Lossless                                                           |
+//! |-------------|--------------------------------------------------------------------|
+//! | CBOR        | Every value                                                        |
+//! | FlexBuffers | Every value                                                        |
+//! | Hjson       | Every value                                                        |
+//! | JSON        | Every value                                                        |
+//! | Msgpack     | Every value                                                        |
+//! | RESP        | Every value                                                        |
+//! | SMILE       | Every value                                                        |
+//! | YAML        | Every value                                                        |
+//! | Pickle      | Every value without integers larger than `i64::MAX`                |
+//! | TOML        | A table without nulls and with integers that fit in an `i64`       |
+//! | TSV         | An array of objects with string values                             |
+//! | URL         | An object with string values                                       |
+//!
+//! Bincode is not self-describing, so it cannot be read back as an arbitrary value, and Markdown
+//! can only be written.
 
+extern crate ansi_term;
+extern crate atty;
+extern crate base64;
 extern crate bincode;
 extern crate byteorder;
+#[cfg(feature = "digest")]
+extern crate crc32fast;
+extern crate dotenvy;
+extern crate encoding_rs;
 extern crate envy;
+extern crate flate2;
+extern crate flexbuffers;
+extern crate glob;
+extern crate lz4_flex;
+#[cfg(feature = "digest")]
+extern crate md5;
+extern crate notify;
 extern crate rmp_serde;
 extern crate serde;
 extern crate serde_cbor;
@@ -169,8 +211,17 @@ extern crate serde_json;
 extern crate serde_pickle;
 extern crate serde_urlencoded;
 extern crate serde_yaml;
+#[cfg(feature = "digest")]
+extern crate sha1;
+#[cfg(feature = "digest")]
+extern crate sha2;
 extern crate toml;
+#[cfg(feature = "http")]
+extern crate ureq;
+extern crate walkdir;
+extern crate zstd;
 
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use std::any::Any;
 use std::error::Error as StdError;
 use std::fmt;
@@ -180,11 +231,34 @@ use std::result;
 use std::str::{self, FromStr};
 
 pub use self::panser::deserialize;
+pub use self::panser::diff_values;
+pub use self::panser::format_byte;
+pub use self::panser::parse_hex_dump;
+pub use self::panser::parse_radix_bytes;
 pub use self::panser::serialize;
+pub use self::panser::serialize_value;
 pub use self::panser::transcode;
+pub use self::panser::transcode_value;
+pub use self::panser::FrameReader;
+pub use self::panser::FrameWriter;
 pub use self::panser::Panser;
+pub use self::pipeline::Pipeline;
+pub use self::pipeline::Step;
 
+pub mod pipeline;
+pub mod transform;
+
+mod cbor_diag;
+mod hash;
+mod highlight;
+mod markdown;
+mod net;
 mod panser;
+mod resp;
+mod smile;
+mod table;
+mod tsv;
+mod value;
 
 /// A specialized `Result` type for panser operations.
 pub type Result<T> = result::Result<T, Error>;
@@ -196,6 +270,48 @@ pub enum Framing {
     Sized,
     /// Separate, or delimit, each message with a byte, or char, as a flag.
     Delimited(u8),
+    /// Wrap each message with the ASCII Start-of-Text (STX, `0x02`) and End-of-Text (ETX, `0x03`)
+    /// control characters, optionally followed by a checksum.
+    StxEtx(Option<Checksum>),
+}
+
+/// The checksum appended after the End-of-Text (ETX) byte for STX/ETX framing.
+///
+/// The checksum is calculated over the message and the ETX byte, but not the Start-of-Text (STX)
+/// byte.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Checksum {
+    /// A one byte Longitudinal Redundancy Check (LRC), which is the XOR of all bytes.
+    Lrc,
+    /// A two byte Cyclic Redundancy Check (CRC-16/ARC) in Big Endian (Network Order).
+    Crc16,
+}
+
+impl Checksum {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec!["Lrc", "lrc", "LRC", "Crc16", "crc16", "CRC16"]
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match &*s.to_string().to_lowercase() {
+            "lrc" => Ok(Checksum::Lrc),
+            "crc16" => Ok(Checksum::Crc16),
+            _ => Err("No match"),
+        }
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Checksum::Lrc => write!(f, "LRC"),
+            Checksum::Crc16 => write!(f, "CRC16"),
+        }
+    }
 }
 
 /// The different output (serialization) formats.
@@ -205,11 +321,16 @@ pub enum Framing {
 pub enum ToFormat {
     Bincode,
     Cbor,
+    FlexBuffers,
     Hjson,
     Json,
+    Markdown,
     Msgpack,
     Pickle,
+    Resp,
+    Smile,
     Toml,
+    Tsv,
     Url,
     Yaml,
 }
@@ -217,9 +338,11 @@ pub enum ToFormat {
 impl ToFormat {
     pub fn possible_values() -> Vec<&'static str> {
         vec![
-            "Bincode", "bincode", "BINCODE", "Cbor", "cbor", "CBOR", "Hjson", "hjson", "HJSON",
-            "Json", "json", "JSON", "Msgpack", "msgpack", "MSGPACK", "Pickle", "pickle", "PICKLE",
-            "Toml", "toml", "TOML", "Url", "url", "URL", "Yaml", "yaml", "YAML",
+            "Bincode", "bincode", "BINCODE", "Cbor", "cbor", "CBOR", "FlexBuffers", "flexbuffers",
+            "FLEXBUFFERS", "Hjson", "hjson", "HJSON", "Json", "json", "JSON", "Markdown",
+            "markdown", "MARKDOWN", "Md", "md", "MD", "Msgpack", "msgpack", "MSGPACK", "Pickle",
+            "pickle", "PICKLE", "Resp", "resp", "RESP", "Smile", "smile", "SMILE", "Toml", "toml",
+            "TOML", "Tsv", "tsv", "TSV", "Url", "url", "URL", "Yaml", "yaml", "YAML",
         ]
     }
 }
@@ -231,11 +354,17 @@ impl FromStr for ToFormat {
         match &*s.to_string().to_lowercase() {
             "bincode" => Ok(ToFormat::Bincode),
             "cbor" => Ok(ToFormat::Cbor),
+            "flexbuffers" => Ok(ToFormat::FlexBuffers),
             "hjson" => Ok(ToFormat::Hjson),
             "json" => Ok(ToFormat::Json),
+            "markdown" => Ok(ToFormat::Markdown),
+            "md" => Ok(ToFormat::Markdown),
             "msgpack" => Ok(ToFormat::Msgpack),
             "pickle" => Ok(ToFormat::Pickle),
+            "resp" => Ok(ToFormat::Resp),
+            "smile" => Ok(ToFormat::Smile),
             "toml" => Ok(ToFormat::Toml),
+            "tsv" => Ok(ToFormat::Tsv),
             "url" => Ok(ToFormat::Url),
             "yaml" => Ok(ToFormat::Yaml),
             _ => Err("No Match"),
@@ -248,11 +377,16 @@ impl fmt::Display for ToFormat {
         match *self {
             ToFormat::Bincode => write!(f, "Bincode"),
             ToFormat::Cbor => write!(f, "CBOR"),
+            ToFormat::FlexBuffers => write!(f, "FlexBuffers"),
             ToFormat::Hjson => write!(f, "Hjson"),
             ToFormat::Json => write!(f, "JSON"),
+            ToFormat::Markdown => write!(f, "Markdown"),
             ToFormat::Msgpack => write!(f, "Msgpack"),
             ToFormat::Pickle => write!(f, "Pickle"),
+            ToFormat::Resp => write!(f, "RESP"),
+            ToFormat::Smile => write!(f, "SMILE"),
             ToFormat::Toml => write!(f, "TOML"),
+            ToFormat::Tsv => write!(f, "TSV"),
             ToFormat::Url => write!(f, "URL"),
             ToFormat::Yaml => write!(f, "YAML"),
         }
@@ -266,12 +400,19 @@ impl fmt::Display for ToFormat {
 pub enum FromFormat {
     Bincode,
     Cbor,
+    /// A dotenv file of `KEY=VALUE` lines, which is read into an object of strings without
+    /// changing the environment of the process.
+    Dotenv,
     Envy,
+    FlexBuffers,
     Hjson,
     Json,
     Msgpack,
     Pickle,
+    Resp,
+    Smile,
     Toml,
+    Tsv,
     Url,
     Yaml,
 }
@@ -279,12 +420,161 @@ pub enum FromFormat {
 impl FromFormat {
     pub fn possible_values() -> Vec<&'static str> {
         vec![
-            "Bincode", "bincode", "BINCODE", "Cbor", "cbor", "CBOR", "Envy", "envy", "ENVY",
-            "Hjson", "hjson", "HJSON", "Json", "json", "JSON", "Msgpack", "msgpack", "MSGPACK",
-            "Pickle", "pickle", "PICKLE", "Toml", "toml", "TOML", "Url", "url", "URL", "Yaml",
+            "Bincode", "bincode", "BINCODE", "Cbor", "cbor", "CBOR", "Dotenv", "dotenv", "DOTENV",
+            "Env", "env", "ENV", "Envy", "envy", "ENVY", "FlexBuffers", "flexbuffers",
+            "FLEXBUFFERS", "Hjson", "hjson", "HJSON", "Json", "json", "JSON", "Msgpack", "msgpack",
+            "MSGPACK", "Pickle", "pickle", "PICKLE", "Resp", "resp", "RESP", "Smile", "smile",
+            "SMILE", "Toml", "toml", "TOML", "Tsv", "tsv", "TSV", "Url", "url", "URL", "Yaml",
             "yaml", "YAML",
         ]
     }
+
+    /// Gets the format for a file extension.
+    ///
+    /// The common file extensions that are not a format name are checked first, and then the
+    /// extension is matched like a format name. The extension is case insensitive and does not
+    /// include the dot. The extensions that are not format names are:
+    ///
+    /// | Extension     | Format      |
+    /// |---------------|-------------|
+    /// | `bin`         | Bincode     |
+    /// | `flexbuf`     | FlexBuffers |
+    /// | `htm`, `html` | URL         |
+    /// | `mpk`, `msg`  | Msgpack     |
+    /// | `pkl`         | Pickle      |
+    /// | `sml`         | SMILE       |
+    /// | `yml`         | YAML        |
+    ///
+    /// `None` is returned for any other extension.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use panser::FromFormat;
+    ///
+    /// assert_eq!(
+    ///     FromFormat::detect_from_extension("yml").map(|f| f.to_string()),
+    ///     Some(String::from("YAML"))
+    /// );
+    /// assert!(FromFormat::detect_from_extension("txt").is_none());
+    /// ```
+    pub fn detect_from_extension(ext: &str) -> Option<FromFormat> {
+        match &*ext.to_lowercase() {
+            "bin" => Some(FromFormat::Bincode),
+            "flexbuf" => Some(FromFormat::FlexBuffers),
+            "htm" | "html" => Some(FromFormat::Url),
+            "mpk" | "msg" => Some(FromFormat::Msgpack),
+            "pkl" => Some(FromFormat::Pickle),
+            "sml" => Some(FromFormat::Smile),
+            "yml" => Some(FromFormat::Yaml),
+            e => FromFormat::from_str(e).ok(),
+        }
+    }
+
+    /// Gets the format for the media type of an HTTP `Content-Type` header.
+    ///
+    /// The parameters of the media type, like `; charset=utf-8`, and the case are ignored. The
+    /// `application/*+json` and `application/*+yaml` structured syntax suffixes are also
+    /// recognized, like `application/problem+json`. The media types are:
+    ///
+    /// | Media Type                                                             | Format  |
+    /// |------------------------------------------------------------------------|---------|
+    /// | `application/cbor`                                                     | CBOR    |
+    /// | `application/hjson`                                                    | Hjson   |
+    /// | `application/json`, `text/json`                                        | JSON    |
+    /// | `application/msgpack`, `application/x-msgpack`, `application/vnd.msgpack` | Msgpack |
+    /// | `application/toml`, `text/toml`                                        | TOML    |
+    /// | `text/tab-separated-values`                                            | TSV     |
+    /// | `application/x-www-form-urlencoded`                                    | URL     |
+    /// | `application/yaml`, `application/x-yaml`, `text/yaml`, `text/x-yaml`   | YAML    |
+    ///
+    /// `None` is returned for any other media type, like `text/plain`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use panser::FromFormat;
+    ///
+    /// assert_eq!(
+    ///     FromFormat::from_content_type("application/json; charset=utf-8").map(|f| f.to_string()),
+    ///     Some(String::from("JSON"))
+    /// );
+    /// assert!(FromFormat::from_content_type("text/plain").is_none());
+    /// ```
+    pub fn from_content_type(content_type: &str) -> Option<FromFormat> {
+        let media = content_type.split(';').next().unwrap_or("").trim().to_lowercase();
+        match &*media {
+            "application/cbor" => Some(FromFormat::Cbor),
+            "application/hjson" => Some(FromFormat::Hjson),
+            "application/json" | "text/json" => Some(FromFormat::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(FromFormat::Msgpack)
+            }
+            "application/toml" | "text/toml" => Some(FromFormat::Toml),
+            "text/tab-separated-values" => Some(FromFormat::Tsv),
+            "application/x-www-form-urlencoded" => Some(FromFormat::Url),
+            "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+                Some(FromFormat::Yaml)
+            }
+            m if m.starts_with("application/") && m.ends_with("+json") => Some(FromFormat::Json),
+            m if m.starts_with("application/") && m.ends_with("+yaml") => Some(FromFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Gets the format for a one byte format tag.
+    ///
+    /// The format tag is the first byte of a message when the format header is used. The tags
+    /// are:
+    ///
+    /// | Tag | Format      |
+    /// |-----|-------------|
+    /// | 0   | Bincode     |
+    /// | 1   | CBOR        |
+    /// | 2   | Envy        |
+    /// | 3   | Hjson       |
+    /// | 4   | JSON        |
+    /// | 5   | Msgpack     |
+    /// | 6   | Pickle      |
+    /// | 7   | TOML        |
+    /// | 8   | TSV         |
+    /// | 9   | URL         |
+    /// | 10  | YAML        |
+    /// | 11  | Dotenv      |
+    /// | 12  | SMILE       |
+    /// | 13  | RESP        |
+    /// | 14  | FlexBuffers |
+    ///
+    /// `None` is returned for any other tag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use panser::FromFormat;
+    ///
+    /// assert_eq!(FromFormat::from_tag(4).map(|f| f.to_string()), Some(String::from("JSON")));
+    /// assert!(FromFormat::from_tag(15).is_none());
+    /// ```
+    pub fn from_tag(tag: u8) -> Option<FromFormat> {
+        match tag {
+            0 => Some(FromFormat::Bincode),
+            1 => Some(FromFormat::Cbor),
+            2 => Some(FromFormat::Envy),
+            3 => Some(FromFormat::Hjson),
+            4 => Some(FromFormat::Json),
+            5 => Some(FromFormat::Msgpack),
+            6 => Some(FromFormat::Pickle),
+            7 => Some(FromFormat::Toml),
+            8 => Some(FromFormat::Tsv),
+            9 => Some(FromFormat::Url),
+            10 => Some(FromFormat::Yaml),
+            11 => Some(FromFormat::Dotenv),
+            12 => Some(FromFormat::Smile),
+            13 => Some(FromFormat::Resp),
+            14 => Some(FromFormat::FlexBuffers),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for FromFormat {
@@ -292,12 +582,17 @@ impl fmt::Display for FromFormat {
         match *self {
             FromFormat::Bincode => write!(f, "Bincode"),
             FromFormat::Cbor => write!(f, "CBOR"),
+            FromFormat::Dotenv => write!(f, "Dotenv"),
             FromFormat::Envy => write!(f, "Envy"),
+            FromFormat::FlexBuffers => write!(f, "FlexBuffers"),
             FromFormat::Hjson => write!(f, "Hjson"),
             FromFormat::Json => write!(f, "JSON"),
             FromFormat::Msgpack => write!(f, "Msgpack"),
             FromFormat::Pickle => write!(f, "Pickle"),
+            FromFormat::Resp => write!(f, "RESP"),
+            FromFormat::Smile => write!(f, "SMILE"),
             FromFormat::Toml => write!(f, "TOML"),
+            FromFormat::Tsv => write!(f, "TSV"),
             FromFormat::Url => write!(f, "URL"),
             FromFormat::Yaml => write!(f, "YAML"),
         }
@@ -311,12 +606,18 @@ impl FromStr for FromFormat {
         match &*s.to_string().to_lowercase() {
             "bincode" => Ok(FromFormat::Bincode),
             "cbor" => Ok(FromFormat::Cbor),
+            "dotenv" => Ok(FromFormat::Dotenv),
+            "env" => Ok(FromFormat::Dotenv),
             "envy" => Ok(FromFormat::Envy),
+            "flexbuffers" => Ok(FromFormat::FlexBuffers),
             "hjson" => Ok(FromFormat::Hjson),
             "json" => Ok(FromFormat::Json),
             "msgpack" => Ok(FromFormat::Msgpack),
             "pickle" => Ok(FromFormat::Pickle),
+            "resp" => Ok(FromFormat::Resp),
+            "smile" => Ok(FromFormat::Smile),
             "toml" => Ok(FromFormat::Toml),
+            "tsv" => Ok(FromFormat::Tsv),
             "url" => Ok(FromFormat::Url),
             "yaml" => Ok(FromFormat::Yaml),
             _ => Err("No Match"),
@@ -374,6 +675,32 @@ impl Radix {
             "OCTAL",
         ]
     }
+
+    /// Parses the `-r,--radix` output, a whitespace- or comma-separated list of numeric strings
+    /// in the radix, back to bytes.
+    ///
+    /// This is the same as the `parse_radix_bytes` function.
+    ///
+    /// # Errors
+    ///
+    /// A `ParseInt` error with the numeric string will occur if a numeric string cannot be
+    /// converted to a u8 (byte) value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// extern crate panser;
+    ///
+    /// use panser::Radix;
+    ///
+    /// fn main() {
+    ///     let bytes = Radix::from_str_bytes("81 A4 62 6F 6F 6C C3", Radix::Hexadecimal);
+    ///     assert_eq!(bytes.unwrap(), vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+    /// }
+    /// ```
+    pub fn from_str_bytes(s: &str, radix: Radix) -> Result<Vec<u8>> {
+        parse_radix_bytes(s, radix)
+    }
 }
 
 impl FromStr for Radix {
@@ -413,6 +740,435 @@ impl fmt::Display for Radix {
     }
 }
 
+/// The options for formatting a byte as a numeric string with a radix.
+///
+/// See the `format_byte` function.
+#[derive(Clone, Copy, Debug)]
+pub struct RadixStyle {
+    /// Use lowercase letters for hexadecimal digits. Only hexadecimal has letters.
+    pub lower: bool,
+    /// Zero pad the digits to the maximum number of digits for a byte, i.e. eight for binary,
+    /// three for decimal and octal, and two for hexadecimal.
+    pub pad: bool,
+    /// Prepend the radix prefix, i.e. `0b`, `0o`, or `0x`, to each byte. Decimal has no prefix.
+    pub prefix: bool,
+    /// The radix of the numeric string.
+    pub radix: Radix,
+}
+
+impl RadixStyle {
+    /// Creates a style for the radix with uppercase letters, no padding, and no prefix.
+    pub fn new(radix: Radix) -> RadixStyle {
+        RadixStyle {
+            lower: false,
+            pad: false,
+            prefix: false,
+            radix,
+        }
+    }
+}
+
+/// The policy for replacing null values before serialization.
+///
+/// Some formats, like TOML, do not have a null type and fail to serialize a value containing
+/// a null. A policy replaces, or removes, all of the nulls in a value, so it can be serialized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NullPolicy {
+    /// Replace a null with an empty array, i.e. `[]`.
+    EmptyArray,
+    /// Replace a null with an empty object, i.e. `{}`.
+    EmptyObject,
+    /// Replace a null with an empty string, i.e. `""`.
+    EmptyString,
+    /// Remove a null field from an object and a null element from an array.
+    Skip,
+    /// Replace a null with zero, i.e. `0`.
+    Zero,
+}
+
+impl NullPolicy {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec![
+            "empty-array", "Empty-Array", "EMPTY-ARRAY", "empty-object", "Empty-Object",
+            "EMPTY-OBJECT", "empty-string", "Empty-String", "EMPTY-STRING", "skip", "Skip", "SKIP",
+            "zero", "Zero", "ZERO",
+        ]
+    }
+
+    /// The default policy for an output format.
+    ///
+    /// Only formats without a null type have a default policy. TOML replaces a null with an
+    /// empty string. All other formats have no default policy, i.e. `None`, since the null is
+    /// serialized as the format's nil, or null, value.
+    pub fn default_for(to: ToFormat) -> Option<NullPolicy> {
+        match to {
+            ToFormat::Toml => Some(NullPolicy::EmptyString),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for NullPolicy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match &*s.to_string().to_lowercase() {
+            "empty-array" => Ok(NullPolicy::EmptyArray),
+            "empty-object" => Ok(NullPolicy::EmptyObject),
+            "empty-string" => Ok(NullPolicy::EmptyString),
+            "skip" => Ok(NullPolicy::Skip),
+            "zero" => Ok(NullPolicy::Zero),
+            _ => Err("No match"),
+        }
+    }
+}
+
+impl fmt::Display for NullPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NullPolicy::EmptyArray => write!(f, "empty-array"),
+            NullPolicy::EmptyObject => write!(f, "empty-object"),
+            NullPolicy::EmptyString => write!(f, "empty-string"),
+            NullPolicy::Skip => write!(f, "skip"),
+            NullPolicy::Zero => write!(f, "zero"),
+        }
+    }
+}
+
+/// The style for displaying serialized binary data as source code, or text.
+///
+/// A style is an alternative to displaying the serialized binary data with a `Radix`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    /// Display data as base64 text, i.e. `gaRib29sww==`.
+    Base64,
+    /// Display data as a comma-separated list of C-style hexadecimal literals, i.e. `0x81, 0xa4`.
+    CArray,
+    /// Display data as an escaped string, where printable ASCII characters are as-is and all
+    /// other bytes are hexadecimal escapes, i.e. `\x81\xa4bool\xc3`.
+    Escaped,
+    /// Display data as a hex dump similar to the `xxd` command, i.e. an offset, the bytes as
+    /// hexadecimal, and the bytes as ASCII characters on each line.
+    Hexdump,
+    /// Display data as a Python bytes literal of an escaped string, i.e. `b"\x81\xa4bool\xc3"`.
+    PythonBytes,
+    /// Display data as a Rust `vec!` macro of hexadecimal byte literals, i.e. `vec![0x81, 0xa4]`.
+    Rust,
+    /// Display the digest of each message as hexadecimal instead of the data, i.e.
+    /// `1 4c2f4bc0`, where the first number is the message number.
+    Checksum(Digest),
+}
+
+impl Style {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec![
+            "base64", "Base64", "BASE64", "b64", "B64", "c-array", "C-array", "C-ARRAY", "c", "C",
+            "escaped", "Escaped", "ESCAPED", "hexdump", "Hexdump", "HEXDUMP", "xxd", "XXD",
+            "python-bytes", "Python-Bytes", "PYTHON-BYTES", "py", "PY", "rust", "Rust", "RUST", "rs",
+            "RS", "checksum", "Checksum", "CHECKSUM", "checksum:crc32", "checksum:md5",
+            "checksum:sha1", "checksum:sha256",
+        ]
+    }
+}
+
+impl FromStr for Style {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match &*s.to_string().to_lowercase() {
+            "b64" => Ok(Style::Base64),
+            "base64" => Ok(Style::Base64),
+            "c" => Ok(Style::CArray),
+            "c-array" => Ok(Style::CArray),
+            "escaped" => Ok(Style::Escaped),
+            "hexdump" => Ok(Style::Hexdump),
+            "py" => Ok(Style::PythonBytes),
+            "python-bytes" => Ok(Style::PythonBytes),
+            "rs" => Ok(Style::Rust),
+            "rust" => Ok(Style::Rust),
+            "xxd" => Ok(Style::Hexdump),
+            "checksum" => Ok(Style::Checksum(Digest::Sha256)),
+            s if s.starts_with("checksum:") => {
+                Digest::from_str(&s["checksum:".len()..]).map(Style::Checksum)
+            }
+            _ => Err("No match"),
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Style::Base64 => write!(f, "base64"),
+            Style::CArray => write!(f, "c-array"),
+            Style::Escaped => write!(f, "escaped"),
+            Style::Hexdump => write!(f, "hexdump"),
+            Style::PythonBytes => write!(f, "python-bytes"),
+            Style::Rust => write!(f, "rust"),
+            Style::Checksum(d) => write!(f, "checksum:{}", d),
+        }
+    }
+}
+
+/// The digest, or hash, algorithm for the checksum of each serialized output message.
+///
+/// Computing a digest requires the `digest` feature, which is enabled by default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Digest {
+    /// The four byte Cyclic Redundancy Check (CRC-32/ISO-HDLC), like the `crc32` command.
+    Crc32,
+    /// The sixteen byte MD5 digest.
+    Md5,
+    /// The twenty byte SHA-1 digest.
+    Sha1,
+    /// The thirty-two byte SHA-256 digest.
+    Sha256,
+}
+
+impl Digest {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec![
+            "crc32", "Crc32", "CRC32", "md5", "Md5", "MD5", "sha1", "Sha1", "SHA1", "sha256",
+            "Sha256", "SHA256",
+        ]
+    }
+}
+
+impl FromStr for Digest {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match &*s.to_string().to_lowercase() {
+            "crc32" => Ok(Digest::Crc32),
+            "md5" => Ok(Digest::Md5),
+            "sha1" => Ok(Digest::Sha1),
+            "sha256" => Ok(Digest::Sha256),
+            _ => Err("No match"),
+        }
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Digest::Crc32 => write!(f, "crc32"),
+            Digest::Md5 => write!(f, "md5"),
+            Digest::Sha1 => write!(f, "sha1"),
+            Digest::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+/// When to color the bytes of the serialized output data displayed with a `Radix`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    /// Always color the bytes, even if the output is a file or a pipe.
+    Always,
+    /// Color the bytes only if the output is stdout, stdout is a terminal (TTY), and the
+    /// `NO_COLOR` environment variable is not set, or empty.
+    Auto,
+    /// Never color the bytes.
+    Never,
+}
+
+impl Color {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec![
+            "always", "Always", "ALWAYS", "auto", "Auto", "AUTO", "never", "Never", "NEVER",
+        ]
+    }
+}
+
+impl FromStr for Color {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match &*s.to_string().to_lowercase() {
+            "always" => Ok(Color::Always),
+            "auto" => Ok(Color::Auto),
+            "never" => Ok(Color::Never),
+            _ => Err("No match"),
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Color::Always => write!(f, "always"),
+            Color::Auto => write!(f, "auto"),
+            Color::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// The character encoding of the serialized output data for the text formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Charset {
+    /// UTF-8, which is the encoding of all text formats by default.
+    Utf8,
+    /// UTF-16 with the bytes of each code unit in Big Endian (Network Order).
+    Utf16Be,
+    /// UTF-16 with the bytes of each code unit in Little Endian, which is common on Windows.
+    Utf16Le,
+}
+
+impl Charset {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec![
+            "utf8", "UTF8", "utf-8", "UTF-8", "utf16be", "UTF16BE", "utf-16be", "UTF-16BE",
+            "utf16le", "UTF16LE", "utf-16le", "UTF-16LE",
+        ]
+    }
+
+    /// The byte order mark (BOM) written at the start of the text for the encoding.
+    pub fn bom(&self) -> &'static [u8] {
+        match *self {
+            Charset::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Charset::Utf16Be => &[0xFE, 0xFF],
+            Charset::Utf16Le => &[0xFF, 0xFE],
+        }
+    }
+}
+
+impl FromStr for Charset {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match &*s.to_string().to_lowercase() {
+            "utf8" | "utf-8" => Ok(Charset::Utf8),
+            "utf16be" | "utf-16be" => Ok(Charset::Utf16Be),
+            "utf16le" | "utf-16le" => Ok(Charset::Utf16Le),
+            _ => Err("No match"),
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Charset::Utf8 => write!(f, "utf8"),
+            Charset::Utf16Be => write!(f, "utf16be"),
+            Charset::Utf16Le => write!(f, "utf16le"),
+        }
+    }
+}
+
+/// The character encoding of the input for the text formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// ISO-8859-1, where each byte is the Unicode code point of the same value.
+    Latin1,
+    /// UTF-16 with the bytes of each code unit in Big Endian (Network Order).
+    Utf16Be,
+    /// UTF-16 with the bytes of each code unit in Little Endian, which is common on Windows.
+    Utf16Le,
+    /// UTF-8, which is the encoding of all text formats by default.
+    Utf8,
+    /// Windows-1252, which is like Latin-1 but with printable characters, like the euro sign, in
+    /// place of the C1 control codes.
+    Windows1252,
+}
+
+impl Encoding {
+    pub fn possible_values() -> Vec<&'static str> {
+        vec![
+            "latin1", "LATIN1", "utf8", "UTF8", "utf-8", "UTF-8", "utf16be", "UTF16BE", "utf-16-be",
+            "UTF-16-BE", "utf16le", "UTF16LE", "utf-16-le", "UTF-16-LE", "windows-1252",
+            "WINDOWS-1252",
+        ]
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match &*s.to_string().to_lowercase() {
+            "latin1" => Ok(Encoding::Latin1),
+            "utf8" | "utf-8" => Ok(Encoding::Utf8),
+            "utf16be" | "utf-16-be" => Ok(Encoding::Utf16Be),
+            "utf16le" | "utf-16-le" => Ok(Encoding::Utf16Le),
+            "windows-1252" => Ok(Encoding::Windows1252),
+            _ => Err("No match"),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Encoding::Latin1 => write!(f, "latin1"),
+            Encoding::Utf16Be => write!(f, "utf-16-be"),
+            Encoding::Utf16Le => write!(f, "utf-16-le"),
+            Encoding::Utf8 => write!(f, "utf-8"),
+            Encoding::Windows1252 => write!(f, "windows-1252"),
+        }
+    }
+}
+
+/// A difference between two universal, arbitrary values.
+///
+/// The first field is the JSON Pointer (RFC 6901) of the value that differs, where the empty
+/// string is the entire value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difference {
+    /// A key of an object, or an item of an array, that only exists in the second value.
+    Added(String, serde_json::Value),
+    /// A key of an object, or an item of an array, that only exists in the first value.
+    Removed(String, serde_json::Value),
+    /// A value that is different in the first and second values. The first value is before the
+    /// second value.
+    Changed(String, serde_json::Value, serde_json::Value),
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Difference::Added(ref path, ref value) => write!(f, "+ {}: {}", path, value),
+            Difference::Removed(ref path, ref value) => write!(f, "- {}: {}", path, value),
+            Difference::Changed(ref path, ref before, ref after) => {
+                write!(f, "~ {}: {} -> {}", path, before, after)
+            }
+        }
+    }
+}
+
+/// The exit, or error, code for each category of error.
+///
+/// The code is returned by the `Error::code` method and it is the exit code of the command line
+/// application. Use `i32::from` to get the integer value of a code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(i32)]
+pub enum ExitCode {
+    /// Success, no error.
+    Success = 0,
+    /// Failure, error transcoding, i.e. decoding or encoding a format.
+    Transcode = 1,
+    /// Failure, generic error.
+    Generic = 2,
+    /// Failure, Input/Output (IO).
+    Io = 3,
+    /// Failure, error parsing an integer.
+    ParseInt = 4,
+    /// Failure, error with UTF-8 encoding.
+    Utf8 = 5,
+    /// Failure, the output differs from the reference data.
+    Diff = 6,
+}
+
+impl fmt::Display for ExitCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
+
+impl From<ExitCode> for i32 {
+    fn from(code: ExitCode) -> i32 {
+        code as i32
+    }
+}
+
 /// The error type for panser-releated operations and associated traits.
 ///
 /// Errors mostly originate from the dependencies, but custom instances of Error can be crated with
@@ -423,9 +1179,19 @@ pub enum Error {
     Bincode(bincode::Error),
     /// Decoding/encoding of the CBOR format failed.
     Cbor(serde_cbor::Error),
+    /// The output differs from the reference data by a number of bytes.
+    Diff(usize),
+    /// The values of two inputs are different. The number of differences is included.
+    Differences(usize),
+    /// Decoding of the dotenv format failed.
+    Dotenv(dotenvy::Error),
     Envy(envy::Error),
     /// End-of-File (EOF) reached.
     Eof,
+    /// Decoding of the FlexBuffers format failed.
+    FlexBuffersDecode(flexbuffers::DeserializationError),
+    /// Encoding of the FlexBuffers format failed.
+    FlexBuffersEncode(flexbuffers::SerializationError),
     /// A generic or custom error occurred. The message should contain the detailed information.
     Generic(String),
     //Hjson(serde_hjson::Error),
@@ -437,10 +1203,15 @@ pub enum Error {
     MsgpackDecode(rmp_serde::decode::Error),
     /// Encoding of the MessagePack format failed.
     MsgpackEncode(rmp_serde::encode::Error),
-    /// Converting a string to an integer failed.
-    ParseInt(num::ParseIntError),
+    /// Converting a string to an integer failed. The numeric string is included if it is known,
+    /// such as for a list of bytes.
+    ParseInt(num::ParseIntError, Option<String>),
     /// Decoding/encoding of the Pickle format failed.
     Pickle(serde_pickle::Error),
+    /// Decoding of the RESP format failed. The message describes the invalid input.
+    Resp(String),
+    /// Decoding of the SMILE format failed. The message describes the invalid input.
+    Smile(String),
     /// Decoding of the TOML format failed.
     TomlDecode(toml::de::Error),
     /// Encoding of the TOML format failed.
@@ -461,26 +1232,66 @@ impl Error {
     /// This is useful as a return, or exit, code for a command line application, where a non-zero
     /// integer indicates a failure in the application. It can also be used for quickly and easily
     /// teseting equality between two errors.
-    pub fn code(&self) -> i32 {
+    pub fn code(&self) -> ExitCode {
+        match *self {
+            Error::Bincode(..) => ExitCode::Transcode,
+            Error::Cbor(..) => ExitCode::Transcode,
+            Error::Diff(..) => ExitCode::Diff,
+            Error::Differences(..) => ExitCode::Generic,
+            Error::Dotenv(..) => ExitCode::Transcode,
+            Error::Envy(..) => ExitCode::Transcode,
+            Error::Eof => ExitCode::Success, // Not actually an error
+            Error::FlexBuffersDecode(..) => ExitCode::Transcode,
+            Error::FlexBuffersEncode(..) => ExitCode::Transcode,
+            Error::Generic(..) => ExitCode::Generic,
+            //Error::Hjson(..) => ExitCode::Transcode,
+            Error::Io(..) => ExitCode::Io,
+            Error::Json(..) => ExitCode::Transcode,
+            Error::MsgpackDecode(..) => ExitCode::Transcode,
+            Error::MsgpackEncode(..) => ExitCode::Transcode,
+            Error::ParseInt(..) => ExitCode::ParseInt,
+            Error::Pickle(..) => ExitCode::Transcode,
+            Error::Resp(..) => ExitCode::Transcode,
+            Error::Smile(..) => ExitCode::Transcode,
+            Error::TomlDecode(..) => ExitCode::Transcode,
+            Error::TomlEncode(..) => ExitCode::Transcode,
+            Error::Utf8(..) => ExitCode::Utf8,
+            Error::UrlDecode(..) => ExitCode::Transcode,
+            Error::UrlEncode(..) => ExitCode::Transcode,
+            Error::Yaml(..) => ExitCode::Transcode,
+        }
+    }
+
+    /// Gets a short name for the kind of error, like `JSON` or `IO`.
+    ///
+    /// This is the same for all errors of a variant, so it is useful for grouping errors in logs.
+    pub fn kind(&self) -> &'static str {
         match *self {
-            Error::Bincode(..) => 1,
-            Error::Cbor(..) => 1,
-            Error::Envy(..) => 1,
-            Error::Eof => 0, // Not actually an error
-            Error::Generic(..) => 2,
-            //Error::Hjson(..) => 1,
-            Error::Io(..) => 3,
-            Error::Json(..) => 1,
-            Error::MsgpackDecode(..) => 1,
-            Error::MsgpackEncode(..) => 1,
-            Error::ParseInt(..) => 4,
-            Error::Pickle(..) => 1,
-            Error::TomlDecode(..) => 1,
-            Error::TomlEncode(..) => 1,
-            Error::Utf8(..) => 5,
-            Error::UrlDecode(..) => 1,
-            Error::UrlEncode(..) => 1,
-            Error::Yaml(..) => 1,
+            Error::Bincode(..) => "Bincode",
+            Error::Cbor(..) => "CBOR",
+            Error::Diff(..) => "Diff",
+            Error::Differences(..) => "Differences",
+            Error::Dotenv(..) => "Dotenv",
+            Error::Envy(..) => "Envy error",
+            Error::Eof => "EOF",
+            Error::FlexBuffersDecode(..) => "FlexBuffers decoding",
+            Error::FlexBuffersEncode(..) => "FlexBuffers encoding",
+            Error::Generic(..) => "Generic",
+            //Error::Hjson(..) => "Hjson error",
+            Error::Io(..) => "IO",
+            Error::Json(..) => "JSON",
+            Error::MsgpackDecode(..) => "MessagePack decoding",
+            Error::MsgpackEncode(..) => "MessagePack encoding",
+            Error::ParseInt(..) => "Parse integer",
+            Error::Pickle(..) => "Pickle",
+            Error::Resp(..) => "RESP",
+            Error::Smile(..) => "SMILE",
+            Error::TomlDecode(..) => "TOML decoding",
+            Error::TomlEncode(..) => "TOML encoding",
+            Error::UrlDecode(..) => "URL decoding",
+            Error::UrlEncode(..) => "URL encoding",
+            Error::Utf8(..) => "UTF-8",
+            Error::Yaml(..) => "YAML",
         }
     }
 }
@@ -490,16 +1301,28 @@ impl fmt::Display for Error {
         match *self {
             Error::Bincode(ref err) => write!(f, "{}", err),
             Error::Cbor(ref err) => write!(f, "{}", err),
+            Error::Diff(count) => {
+                write!(f, "The output differs from the reference by {} byte(s)", count)
+            }
+            Error::Differences(count) => write!(f, "The inputs differ by {} value(s)", count),
+            Error::Dotenv(ref err) => write!(f, "{}", err),
             Error::Envy(ref message) => write!(f, "{}", message),
             Error::Eof => write!(f, "End of file reached"),
+            Error::FlexBuffersDecode(ref err) => write!(f, "{}", err),
+            Error::FlexBuffersEncode(ref err) => write!(f, "{}", err),
             Error::Generic(ref message) => write!(f, "{}", message),
             //Error::Hjson(ref message) => write!(f, "{}", message),
             Error::Io(ref err) => write!(f, "{}", err),
             Error::Json(ref err) => write!(f, "{}", err),
             Error::MsgpackDecode(ref err) => write!(f, "{}", err),
             Error::MsgpackEncode(ref err) => write!(f, "{}", err),
-            Error::ParseInt(ref err) => write!(f, "{}", err),
+            Error::ParseInt(ref err, Some(ref token)) => {
+                write!(f, "Invalid number '{}': {}", token, err)
+            }
+            Error::ParseInt(ref err, None) => write!(f, "{}", err),
             Error::Pickle(ref err) => write!(f, "{}", err),
+            Error::Resp(ref message) => write!(f, "{}", message),
+            Error::Smile(ref message) => write!(f, "{}", message),
             Error::TomlDecode(ref err) => write!(f, "{}", err),
             Error::TomlEncode(ref err) => write!(f, "{}", err),
             Error::UrlDecode(ref err) => write!(f, "{}", err),
@@ -512,39 +1335,23 @@ impl fmt::Display for Error {
 
 impl StdError for Error {
     fn description(&self) -> &str {
-        match *self {
-            Error::Bincode(..) => "Bincode",
-            Error::Cbor(..) => "CBOR",
-            Error::Envy(..) => "Envy error",
-            Error::Eof => "EOF",
-            Error::Generic(..) => "Generic",
-            //Error::Hjson(..) => "Hjson error",
-            Error::Io(..) => "IO",
-            Error::Json(..) => "JSON",
-            Error::MsgpackDecode(..) => "MessagePack decoding",
-            Error::MsgpackEncode(..) => "MessagePack encoding",
-            Error::ParseInt(..) => "Parse integer",
-            Error::Pickle(..) => "Pickle",
-            Error::TomlDecode(..) => "TOML decoding",
-            Error::TomlEncode(..) => "TOML encoding",
-            Error::UrlDecode(..) => "URL decoding",
-            Error::UrlEncode(..) => "URL encoding",
-            Error::Utf8(..) => "UTF-8",
-            Error::Yaml(..) => "YAML",
-        }
+        self.kind()
     }
 
-    fn cause(&self) -> Option<&dyn StdError> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
             Error::Bincode(ref err) => Some(err),
             Error::Cbor(ref err) => Some(err),
+            Error::Dotenv(ref err) => Some(err),
             Error::Envy(ref err) => Some(err),
+            Error::FlexBuffersDecode(ref err) => Some(err),
+            Error::FlexBuffersEncode(ref err) => Some(err),
             Error::Io(ref err) => Some(err),
             //Error::Hjson(ref err) => Some(err),
             Error::Json(ref err) => Some(err),
             Error::MsgpackDecode(ref err) => Some(err),
             Error::MsgpackEncode(ref err) => Some(err),
-            Error::ParseInt(ref err) => Some(err),
+            Error::ParseInt(ref err, _) => Some(err),
             Error::Pickle(ref err) => Some(err),
             Error::TomlDecode(ref err) => Some(err),
             Error::TomlEncode(ref err) => Some(err),
@@ -569,6 +1376,12 @@ impl From<serde_cbor::Error> for Error {
     }
 }
 
+impl From<dotenvy::Error> for Error {
+    fn from(err: dotenvy::Error) -> Error {
+        Error::Dotenv(err)
+    }
+}
+
 impl From<envy::Error> for Error {
     fn from(err: envy::Error) -> Error {
         Error::Envy(err)
@@ -602,6 +1415,18 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<flexbuffers::DeserializationError> for Error {
+    fn from(err: flexbuffers::DeserializationError) -> Error {
+        Error::FlexBuffersDecode(err)
+    }
+}
+
+impl From<flexbuffers::SerializationError> for Error {
+    fn from(err: flexbuffers::SerializationError) -> Error {
+        Error::FlexBuffersEncode(err)
+    }
+}
+
 impl From<rmp_serde::encode::Error> for Error {
     fn from(err: rmp_serde::encode::Error) -> Error {
         Error::MsgpackEncode(err)
@@ -616,7 +1441,7 @@ impl From<rmp_serde::decode::Error> for Error {
 
 impl From<num::ParseIntError> for Error {
     fn from(err: num::ParseIntError) -> Error {
-        Error::ParseInt(err)
+        Error::ParseInt(err, None)
     }
 }
 
@@ -661,3 +1486,40 @@ impl From<serde_yaml::Error> for Error {
         Error::Yaml(err)
     }
 }
+
+/// A wrapper of an error that can be serialized for machine-readable error reporting.
+///
+/// The `Error` type wraps the errors of the dependencies, which cannot be serialized, so this
+/// serializes the `code`, the `kind`, the `message` from the `Display` implementation, and the
+/// `source_message` from the source error, if any, as a struct, or object. The `source_message`
+/// is `null` if the error does not have a source.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate panser;
+/// extern crate serde_json;
+///
+/// use panser::{Error, SerializableError};
+///
+/// fn main() {
+///     let error = Error::Generic(String::from("Something failed"));
+///     assert_eq!(
+///         serde_json::to_string(&SerializableError(&error)).unwrap(),
+///         r#"{"code":2,"kind":"Generic","message":"Something failed","source_message":null}"#
+///     );
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SerializableError<'a>(pub &'a Error);
+
+impl<'a> Serialize for SerializableError<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 4)?;
+        state.serialize_field("code", &i32::from(self.0.code()))?;
+        state.serialize_field("kind", self.0.kind())?;
+        state.serialize_field("message", &self.0.to_string())?;
+        state.serialize_field("source_message", &self.0.source().map(|e| e.to_string()))?;
+        state.end()
+    }
+}
@@ -21,9 +21,15 @@ extern crate atty;
 extern crate panser;
 
 use ansi_term::Colour;
-use clap::{App, Arg};
-use panser::{FromFormat, Panser, Radix, ToFormat};
+use clap::{App, Arg, ArgMatches, ErrorKind};
+use panser::{
+    Charset, Checksum, Color, Digest, Encoding, ExitCode, FromFormat, NullPolicy, Panser, Radix,
+    Style, ToFormat,
+};
+use std::env;
+use std::fmt::Display;
 use std::io::Write;
+use std::str::FromStr;
 
 const ERROR_COLOR: Colour = Colour::Fixed(9); // bright red
 
@@ -36,11 +42,86 @@ fn main() {
     // avoid build errors on non-windows platforms, a cfg guard should be put in place.
     #[cfg(windows)] ansi_term::enable_ansi_support().unwrap();
 
-    let matches = App::new(crate_name!())
+    let app = App::new(crate_name!())
         .version(crate_version!())
         .about(crate_description!()) 
+        .arg(Arg::with_name("allow-nan")
+             .help("Keeps non-finite floats, i.e. NaN and infinity, from the input and writes them as the non-standard 'NaN', 'Infinity', and '-Infinity' literals for JSON and Hjson output, which matches Python's 'json.dumps' with 'allow_nan=True'. All other output formats have the non-finite floats written as floats. By default, non-finite floats are replaced with null. This flag cannot be used with the '--nan-as-null' flag.")
+             .long("allow-nan")
+             .conflicts_with("nan-as-null"))
+        .arg(Arg::with_name("annotate")
+             .help("Writes a comment-style header line before each output message with the frame number, starting at one, the size of the input frame data, and the offset of the start of the frame within its input file, such as '# frame 17, 203 bytes, offset 0x1A40'. This is useful for keeping track of which output belongs to which frame of a large stream. This flag is ignored unless the '-r,--radix' or '--style' option is used.")
+             .long("annotate"))
+        .arg(Arg::with_name("append")
+             .help("Appends the output to the end of the file specified with the '-o,--output' option instead of truncating it. The file is created if it does not exist. This flag is ignored unless the '-o,--output' option is used. A warning is written to stderr if a text output format is appended without output framing, since the file may not parse as a single document.")
+             .long("append"))
+        .arg(Arg::with_name("ascii-only")
+             .help("Escapes every non-ASCII character in the JSON and Hjson output as a '\\uXXXX' escape sequence, so 'café' is written as 'caf\\u00e9'. A character outside the Basic Multilingual Plane is written as a surrogate pair. This is useful for systems that cannot handle Unicode. This flag is ignored with a warning for all other output formats.")
+             .long("ascii-only"))
+        .arg(Arg::with_name("atomic")
+             .help("Writes the output to a temporary file with the '.tmp' extension added, like 'data.json.tmp', which is synced to the disk and renamed to the '-o,--output' file only after all of the messages are written successfully, so a crash never leaves a partial output file. If the run fails, then the temporary file is removed and the output file is not changed. This flag requires the '-o,--output' option and cannot be used with the '--append' flag.")
+             .long("atomic")
+             .requires("output")
+             .conflicts_with_all(&["append", "in-place", "output-dir"]))
+        .arg(Arg::with_name("base64-to-bytes")
+             .help("Serializes the strings with the 'base64:' prefix from the '--bytes-as-base64' flag back to byte strings, so '\"base64:AQID\"' is written as a byte string of '01 02 03' for CBOR and Msgpack output. Formats without byte strings, like JSON, write the bytes as an array of numbers. A string with the prefix that is not valid base64 is written as a string. This flag cannot be used with the '--allow-nan' or '--msgpack-restore-ext' flags.")
+             .long("base64-to-bytes")
+             .conflicts_with_all(&["allow-nan", "msgpack-restore-ext"]))
+        .arg(Arg::with_name("base64-url")
+             .help("Uses the URL-safe alphabet, with '-' and '_' instead of '+' and '/', for the output of the 'base64' style. This flag is ignored unless the '--style' option is 'base64'.")
+             .long("base64-url"))
+        .arg(Arg::with_name("bytes-as-base64")
+             .help("Deserializes the byte strings of CBOR and Msgpack input, which JSON cannot represent, as base64 strings with the 'base64:' prefix to distinguish them from regular strings, so a byte string of '01 02 03' is '\"base64:AQID\"'. By default, a byte string fails to deserialize. See the '--base64-to-bytes' flag for the reverse direction.")
+             .long("bytes-as-base64"))
+        .arg(Arg::with_name("c-declaration")
+             .help("Wraps the output of the 'c-array' style in a 'static const uint8_t data[] = { ... };' declaration followed by a 'static const size_t data_len' declaration with the number of bytes, similar to the 'xxd -i' command. This flag is ignored unless the '--style' option is 'c-array'.")
+             .long("c-declaration"))
+        .arg(Arg::with_name("cbor-diag")
+             .help("Writes each message as CBOR diagnostic notation, like '{\"bool\": true}' or '[1, h'ff', 1.5]', instead of the serialized output data. The notation is rendered from the CBOR bytes of each message, so the output format is always CBOR, and each message is written on a separate line without the framing, prefix, or suffix. This is a debugging aid for seeing the major types, tags, and indefinite lengths of CBOR data. This flag cannot be used with the '-r,--radix', '--style', or '-t,--to' options.")
+             .long("cbor-diag")
+             .conflicts_with_all(&["radix", "style", "to"]))
+        .arg(Arg::with_name("check")
+             .help("Only checks that the inputs deserialize without writing any output. The success, or failure, of each input is written to stderr, and an input that fails does not stop the remaining inputs from being checked. The exit code is non-zero if any input fails. This is useful for validating files, such as 'panser --check -f yaml *.yaml'.")
+             .long("check"))
+        .arg(Arg::with_name("checksum")
+             .help("Writes the digest of each serialized message to stderr in addition to the output. A line with the index of the message, starting at one, and the digest as lowercase hexadecimal is written for each message, such as '1 9f86d0...'. The value is case insensitive. [values: crc32, md5, sha1, sha256]")
+             .long("checksum")
+             .hide_possible_values(true)
+             .possible_values(&Digest::possible_values())
+             .takes_value(true))
+        .arg(Arg::with_name("coerce-numbers")
+             .help("Converts strings that look like numbers to numbers before serialization. Each string is parsed as an integer and then as a float. A string that cannot be parsed remains a string. This is useful for input formats where all values are strings, like URL-encoded data.")
+             .long("coerce-numbers"))
+        .arg(Arg::with_name("coerce-scalars")
+             .help("Converts strings that look like booleans or numbers to booleans or numbers before serialization. Only the exact 'true' and 'false' strings are booleans, and all other strings are converted like the '--coerce-numbers' flag. A string that is not unambiguously a boolean or a number remains a string. This is useful for input formats where all values are strings, like URL-encoded data and dotenv files.")
+             .long("coerce-scalars"))
+        .arg(Arg::with_name("color")
+            .help("Colors the bytes of the '-r,--radix' option output by their role for the MessagePack and CBOR output formats, so format markers, lengths, and payloads are distinct. The bytes of all other output formats are not colored. The 'auto' value colors the bytes only if the output is stdout, stdout is a terminal, and the 'NO_COLOR' environment variable is not set. The 'always' value colors the bytes even if the output is a file or a pipe. The value is case insensitive. [values: always, auto, never] [default: auto]")
+            .long("color")
+            .hide_possible_values(true)
+            .possible_values(&Color::possible_values())
+            .takes_value(true))
+        .arg(Arg::with_name("connect")
+             .help("A TCP server to write the output to instead of writing to stdout, such as '127.0.0.1:1234' or 'localhost:1234'. The host name is resolved and TCP_NODELAY is set, so each message is sent immediately. This is an alternative to piping the output to the 'nc' command.")
+             .long("connect")
+             .conflicts_with_all(&["output", "output-dir"])
+             .takes_value(true))
+        .arg(Arg::with_name("connect-timeout")
+             .help("The number of seconds to wait for a connection to the '--connect' server. By default, the timeout of the operating system is used. The number must be at least one.")
+             .long("connect-timeout")
+             .requires("connect")
+             .validator(|v| match v.parse::<u64>() {
+                 Ok(0) => Err(String::from("The number of seconds must be at least one")),
+                 Ok(_) => Ok(()),
+                 Err(e) => Err(e.to_string()),
+             })
+             .takes_value(true))
+        .arg(Arg::with_name("continue-on-error")
+             .help("Skips a message that fails to deserialize and continues with the next message instead of stopping. The error is written to stderr and the total number of skipped messages is written to stderr at the end. This is only useful with framed input. The default is to stop at the first error, see the '--fail-fast' flag.")
+             .long("continue-on-error")
+             .overrides_with("fail-fast"))
         .arg(Arg::with_name("delimited")
-             .help("Inidcates a complete message is delimited by the specified byte value and the byte should be appended to the output of each message. This is equivalent to using the '--delimited-input' and '--delimited-output' options with the same value. The delimiter byte can be specified as a (b) binary, (d) decimal, (h) hexadecimal, or (o) octal string value by using the character as a radix suffix. For example, '0Ah' would be the ASCII newline character specified as a hexadecimal string value. If no radix suffix is specified, then hexadecimal notation is assumed. This option cannot be used with the '--sized', '--sized-input', or '--sized-output' flags.")
+             .help("Inidcates a complete message is delimited by the specified byte value and the byte should be appended to the output of each message. This is equivalent to using the '--delimited-input' and '--delimited-output' options with the same value. The delimiter byte can be specified as a (b) binary, (d) decimal, (h) hexadecimal, or (o) octal string value by using the character as a radix suffix. For example, '0Ah' would be the ASCII newline character specified as a hexadecimal string value. If no radix suffix is specified, then hexadecimal notation is assumed. A single ASCII character, like ',', or one of the '\\n', '\\t', '\\r', and '\\0' escape sequences can also be used, but a single hexadecimal digit, like '0', is a hexadecimal number. This option cannot be used with the '--sized', '--sized-input', or '--sized-output' flags. If no framing option is used, then the delimiter is read from the PANSER_DELIMITER environment variable, if it is set.")
              .long("delimited")
              .short("d")
              .conflicts_with("delimited-input")
@@ -50,42 +131,304 @@ fn main() {
              .conflicts_with("sized-output")
              .takes_value(true))
         .arg(Arg::with_name("delimited-input")
-             .help("Indicates a complete message is delimited by the specified byte value. The delimiter byte can be specified as a (b) binary, (d) decimal, (h) hexadecimal, or (o) octal string value by using the character as a radix suffix. For example, '0Ah' would be the ASCII newline character specified as a hexadecimal string value. If no radix suffix is used, then hexadecimal notation is assumed. This option cannot be used with the '--sized', '--sized-input', or '--delimited' options.")
+             .help("Indicates a complete message is delimited by the specified byte value. The delimiter byte can be specified as a (b) binary, (d) decimal, (h) hexadecimal, or (o) octal string value by using the character as a radix suffix. For example, '0Ah' would be the ASCII newline character specified as a hexadecimal string value. If no radix suffix is used, then hexadecimal notation is assumed. A single ASCII character, like ',', or one of the '\\n', '\\t', '\\r', and '\\0' escape sequences can also be used, but a single hexadecimal digit, like '0', is a hexadecimal number. This option cannot be used with the '--sized', '--sized-input', or '--delimited' options.")
              .long("delimited-input")
              .conflicts_with("delimited")
              .conflicts_with("sized")
              .conflicts_with("sized-input")
              .takes_value(true))
         .arg(Arg::with_name("delimited-output")
-             .help("Appends the delimiter byte to the message. The delimiter byte can be specified as a (b) binary, (d) decimal, (h) hexadecimal, or (o) octal string value by using the character as a radix suffix. For example, '0Ah' would be the ASCII newline character specified as a hexadecimal string value. If no radix suffix is used, then hexadecimal notation is assumed. This option cannot be used with the '--sized', '--sized-output', or '--delimited' options.")
+             .help("Appends the delimiter byte to the message. The delimiter byte can be specified as a (b) binary, (d) decimal, (h) hexadecimal, or (o) octal string value by using the character as a radix suffix. For example, '0Ah' would be the ASCII newline character specified as a hexadecimal string value. If no radix suffix is used, then hexadecimal notation is assumed. A single ASCII character, like ',', or one of the '\\n', '\\t', '\\r', and '\\0' escape sequences can also be used, but a single hexadecimal digit, like '0', is a hexadecimal number. This option cannot be used with the '--sized', '--sized-output', or '--delimited' options.")
              .long("delimited-output")
              .conflicts_with("delimited")
              .conflicts_with("sized")
              .conflicts_with("sized-output")
              .takes_value(true))
+        .arg(Arg::with_name("diff")
+             .help("Compares the values of two input files instead of transcoding them. Each file is deserialized with the '-f,--from' format or the format from its file extension, so a YAML file can be compared with the TOML file it was converted to. A line is written for each difference with the JSON Pointer of the value: '+' for a key or item that was added in the second file, '-' for a key or item that was removed, and '~' for a value that changed. Nothing is written if the values are the same. The exit code is 2 if they differ. Exactly two FILES are required.")
+             .long("diff")
+             .requires("FILES")
+             .conflicts_with_all(&["check", "diff-against", "output-dir", "watch"]))
+        .arg(Arg::with_name("diff-against")
+             .help("Compares the output with a reference file instead of writing it. The output, including any framing and the '-r,--radix' or '--style' display, is compared byte by byte with the reference file. A line with the offset, the expected byte from the reference file, and the actual byte from the output as hexadecimal is written for each byte that differs, where a missing byte is '--' if one is shorter than the other, followed by a summary. The expected bytes are red and the actual bytes are green with the '--color' option. If the output and the reference file are the same, then 'identical (N bytes)' is written. The exit code is 6 if they differ.")
+             .long("diff-against")
+             .takes_value(true))
+        .arg(Arg::with_name("escape-html")
+             .help("Escapes the '<', '>', and '&' characters in the JSON and Hjson output as the '\\u003C', '\\u003E', and '\\u0026' escape sequences, so the JSON can be embedded in an HTML page without a string closing a '<script>' element, which matches Python's 'json.encoder.ESCAPE_HTML'. This flag is ignored with a warning for all other output formats.")
+             .long("escape-html"))
+        .arg(Arg::with_name("eval")
+            .help("A string to read as the input instead of stdin or files, such as 'panser -e '{\"a\":1}' -t yaml'. The option can be used more than once, and each string is read like a separate input file, so each string is a message unless framing is used, such as '-e '{\"a\":1}' -e '{\"a\":2}' -t json --delimited-output 0Ah'. The '-f,--from' format, or JSON, is used for all of the strings. This option cannot be used with input files or the '--listen', '--udp-listen', or '--unix-listen' options.")
+            .long("eval")
+            .short("e")
+            .multiple(true)
+            .number_of_values(1)
+            .takes_value(true)
+            .conflicts_with_all(&["FILES", "listen", "udp-listen", "unix-listen"]))
+        .arg(Arg::with_name("every")
+             .help("Writes only every Nth message, starting with the first message, so '--every 3' writes the first, fourth, seventh, and so on, messages. The other messages are still read and deserialized, but they are not written. This is useful for downsampling a high-frequency stream. If the '--stats' flag is used, then the number of messages received is included in the summary. The number must be at least one. [default: 1]")
+             .long("every")
+             .validator(|v| match v.parse::<usize>() {
+                 Ok(0) => Err(String::from("The number of messages must be at least one")),
+                 Ok(_) => Ok(()),
+                 Err(e) => Err(e.to_string()),
+             })
+             .takes_value(true))
+        .arg(Arg::with_name("fail-fast")
+             .help("Stops at the first message that fails to deserialize and exits with an error. This is the default. It overrides a previous '--continue-on-error' flag.")
+             .long("fail-fast")
+             .overrides_with("continue-on-error"))
         .arg(Arg::with_name("FILES")
-            .help("The files to read as input instead of reading from stdin. Unless the '-f,--from' option is used, the file extension for each file will be used to determine the input data format. If a file extension does not exist, the data format is assumed to be JSON. If the '-f,--from' option is used, then the same input data format is used for deserialization regardless of the file extensions.")
+            .help("The files to read as input instead of reading from stdin. Unless the '-f,--from' option is used, the file extension for each file will be used to determine the input data format. If a file extension does not exist, the data format is assumed to be JSON. If the '-f,--from' option is used, then the same input data format is used for deserialization regardless of the file extensions. A file of '-' reads stdin, such as 'panser header.json - footer.json', which uses the '-f,--from' format or JSON, since there is no file extension. The '-' file can only be used once. A file with a glob pattern, like 'logs/**/*.msgpack', is expanded to the matching files in sorted order, even if the shell does not expand it, such as on Windows. A pattern that does not match any files is an error. A file can also be an 'http://' or 'https://' URL, which is fetched with redirects followed, like 'panser -t yaml https://example.com/data.json'. Unless the '-f,--from' option is used, the input data format of a URL is determined from the 'Content-Type' header of the response, such as 'application/json', and then the file extension of the URL path. A response without a success (2xx) status is an error.")
             .index(1)
             .multiple(true))
+        .arg(Arg::with_name("follow")
+             .help("Waits for more data at the end of the last input file, like 'tail -f', so the messages appended to the file by another process are transcoded as they arrive until the process is stopped. A partial frame at the end of the file is completed once the rest of the frame is appended. The file is checked for more data every 100 milliseconds. Input framing is required, such as the '--sized-input' flag. The gzip and Zstandard magic bytes are not detected for the followed file.")
+             .long("follow")
+             .requires("FILES")
+             .conflicts_with_all(&["check", "diff", "output-dir", "watch"]))
+        .arg(Arg::with_name("force-float")
+            .help("Converts integers to floats instead of converting floats without a fractional part to integers for the '--normalize-numbers' flag, so '1' becomes '1.0'. Integers larger than 2^53 may lose precision. This flag requires the '--normalize-numbers' flag.")
+            .long("force-float")
+            .requires("normalize-numbers"))
         .arg(Arg::with_name("from")
-            .help("The input format. The value is case insensitive. [values: Bincode, CBOR, Envy, Hjson, JSON, Msgpack, Pickle, TOML, URL, YAML] [default: JSON]")
+            .help("The input format. The Dotenv format is a file of 'KEY=VALUE' lines, which is read into an object of strings, and is also selected by the '.env' extension or file name. The value is case insensitive. If this option is not used and the format cannot be determined from the file extension, then the format is read from the PANSER_FROM environment variable, if it is set. [values: Bincode, CBOR, Dotenv, Envy, FlexBuffers, Hjson, JSON, Msgpack, Pickle, RESP, SMILE, TOML, TSV, URL, YAML] [default: JSON]")
             .long("from")
             .short("f")
             .hide_possible_values(true)
             .possible_values(&FromFormat::possible_values())
             .takes_value(true))
+        .arg(Arg::with_name("from-base16-file")
+            .help("Changes the input to be a hex dump, where the bytes are pairs of hexadecimal digits, like the output of the 'xxd' and 'hexdump -C' commands or a C array of '0x81, 0xA4' bytes. The bytes are converted to binary data before deserialization with the format specified with the '-f,--from' option. Unlike the '--radix-input' option, the formatting is forgiving: the offsets and text columns of a dump, the '0x' prefixes, the text around the braces of a C array, and all other characters that are not hexadecimal digits are ignored. This is useful for recovering data pasted from documentation. If framing is used, then each message is a hex dump.")
+            .long("from-base16-file")
+            .conflicts_with("radix-input"))
+        .arg(Arg::with_name("from-stdin-format-header")
+            .help("Reads the input format of each message from a one byte format tag at the start of the message. The first byte of each frame, or the entire input if framing is not used, is the format tag and the remaining bytes are deserialized with the format for the tag. This allows a single stream to have messages in different formats. The tags are: 0 = Bincode, 1 = CBOR, 2 = Envy, 3 = Hjson, 4 = JSON, 5 = Msgpack, 6 = Pickle, 7 = TOML, 8 = TSV, 9 = URL, 10 = YAML, 11 = Dotenv, 12 = SMILE, 13 = RESP, and 14 = FlexBuffers. A message with an unknown tag fails to deserialize. The '-f,--from' option is ignored if this flag is used.")
+            .long("from-stdin-format-header"))
+        .arg(Arg::with_name("group")
+            .help("The number of bytes per group when the '-r,--radix' option is used or the '--style' option is 'hexdump'. For the '-r,--radix' option, the bytes within a group are concatenated and zero padded, such as '81A4 626F', and the final group of a message may be shorter. By default, the bytes are not grouped for the '-r,--radix' option and are in groups of two for the 'hexdump' style, like the 'xxd' command.")
+            .long("group")
+            .validator(|v| match v.parse::<usize>() {
+                Ok(0) => Err(String::from("The number of bytes per group must be at least one")),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            })
+            .takes_value(true))
+        .arg(Arg::with_name("gzip-input")
+            .help("Decompresses every input file and stdin with gzip before it is read. By default, an input file is only decompressed if it has the '.gz' extension or starts with the gzip magic bytes, 1F 8B, which is detected separately for each file, and stdin is never decompressed. Concatenated gzip members are decompressed as one input, like the 'zcat' command.")
+            .long("gzip-input"))
+        .arg(Arg::with_name("gzip-output")
+            .help("Compresses the output with gzip. The optional LEVEL is from 0, no compression, to 9, the best compression, and must be set with an equals sign, such as '--gzip-output=9'. The default LEVEL is 6. The output is also compressed if the '-o,--output' file has the '.gz' extension, and the format is determined from the remaining extension, so 'data.json.gz' is JSON. For the '--output-dir' option, the '.gz' extension is added to each output file. Colors are not used with the 'auto' value of the '--color' option.")
+            .long("gzip-output")
+            .value_name("LEVEL")
+            .min_values(0)
+            .max_values(1)
+            .require_equals(true)
+            .validator(|v| match v.parse::<u32>() {
+                Ok(0..=9) => Ok(()),
+                Ok(_) => Err(String::from("The gzip level must be from 0 to 9")),
+                Err(e) => Err(e.to_string()),
+            }))
+        .arg(Arg::with_name("in-place")
+            .help("Rewrites each input file with its output instead of writing to stdout, such as 'panser -t toml --in-place *.json'. Each file is transcoded to a temporary file in the same directory, which replaces the input file only after the transcoding succeeds, and the permissions of the input file are kept. If a file fails, then it is left untouched, the error is written to stderr, and the remaining files are still rewritten, but the exit code is non-zero. See the '--rename-ext' flag to change the file extensions.")
+            .long("in-place")
+            .requires("FILES")
+            .conflicts_with_all(&[
+                "append", "check", "diff", "follow", "output", "output-dir", "watch",
+            ]))
+        .arg(Arg::with_name("input-encoding")
+            .help("The character encoding of the input for the text formats, like JSON and YAML. The data of each message, without the framing, is transcoded from the encoding to UTF-8 before it is deserialized. The input for a binary format, like Msgpack, is never changed. The exit code is 5 if a message is not valid for the encoding. The value is case insensitive. [values: utf-8, latin1, windows-1252, utf-16-le, utf-16-be] [default: utf-8]")
+            .long("input-encoding")
+            .hide_possible_values(true)
+            .possible_values(&Encoding::possible_values())
+            .takes_value(true))
+        .arg(Arg::with_name("input-null-terminated")
+            .help("Indicates a complete message is terminated by a null (00h) byte, like the output of the 'find -print0' command. This is equivalent to using the '--delimited-input 0d' option. This flag cannot be used with the '--delimited', '--delimited-input', '--sized', '--sized-input', '--stxetx', or '--stxetx-input' options.")
+            .long("input-null-terminated")
+            .short("0")
+            .conflicts_with("delimited")
+            .conflicts_with("delimited-input")
+            .conflicts_with("sized")
+            .conflicts_with("sized-input")
+            .conflicts_with("stxetx")
+            .conflicts_with("stxetx-input"))
+        .arg(Arg::with_name("jobs")
+            .help("The number of worker threads for transcoding messages in parallel. The output is always written in the same order as the input. This is useful for large framed streams with output formats that are slow to serialize. The default is one, which transcodes the messages one at a time.")
+            .long("jobs")
+            .short("j")
+            .validator(|v| match v.parse::<usize>() {
+                Ok(0) => Err(String::from("The number of jobs must be at least one")),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            })
+            .takes_value(true))
+        .arg(Arg::with_name("keep-listening")
+            .help("Accepts the next connection after each connection to the '--listen' address, or the '--unix-listen' socket file, is closed until the process is stopped. By default, only one connection is accepted.")
+            .long("keep-listening")
+            .requires("listeners"))
+        .arg(Arg::with_name("line-numbers")
+            .help("Writes the number of the input message, starting at one, followed by a colon and a space before each output message, such as '1: '. This is useful for matching a message in the output with the message number of an error. The input messages are numbered, so the numbers skip the messages that are not written with the '--every' option or skipped with the '--continue-on-error' flag. The number is written as ASCII text, which corrupts binary output formats, so it only makes sense with a text output format or the '-r,--radix' option. A warning is written to stderr if it is used with a binary output format.")
+            .long("line-numbers"))
+        .arg(Arg::with_name("list-formats")
+            .help("Writes a table of the formats to stdout with columns for reading (deserializing) and writing (serializing) and exits without transcoding. A format that can only read some values, like Bincode, is listed as 'limited'. All other options and arguments are ignored.")
+            .long("list-formats"))
+        .arg(Arg::with_name("listen")
+            .help("A TCP address to listen on and read the input from a client instead of reading from stdin, such as '0.0.0.0:1234'. A connection is read like a file with the input framing, and the client closing the connection ends the input. Unless the '-f,--from' option is used, the input format is JSON. This is an alternative to piping the output of the 'nc -l' command.")
+            .long("listen")
+            .group("listeners")
+            .conflicts_with_all(&["FILES", "output-dir", "recursive", "watch"])
+            .takes_value(true))
+        .arg(Arg::with_name("lower")
+            .help("Uses lowercase letters for the hexadecimal digits of the '-r,--radix' option output. This flag is ignored unless the '-r,--radix' option is hex.")
+            .long("lower"))
+        .arg(Arg::with_name("lz4-input")
+            .help("Decompresses every input file and stdin with LZ4 before it is read. By default, an input file is only decompressed if it has the '.lz4' extension or starts with the LZ4 frame magic bytes, 04 22 4D 18, which is detected separately for each file, and stdin is never decompressed. The framing is read from the decompressed data. This flag cannot be used with the '--gzip-input' or '--zstd-input' flags.")
+            .long("lz4-input")
+            .conflicts_with_all(&["gzip-input", "zstd-input"]))
+        .arg(Arg::with_name("lz4-output")
+            .help("Compresses the output with the LZ4 frame format, which is much faster than gzip and Zstandard but does not compress as well. The compression wraps the already framed output, so the framing and any display options are applied first. The output is also compressed if the '-o,--output' file has the '.lz4' extension, and the format is determined from the remaining extension, so 'data.json.lz4' is JSON. For the '--output-dir' option, the '.lz4' extension is added to each output file. This flag cannot be used with the '--gzip-output' or '--zstd-output' options.")
+            .long("lz4-output")
+            .conflicts_with_all(&["gzip-output", "zstd-output"]))
+        .arg(Arg::with_name("msgpack-ext-as-hex")
+            .help("Deserializes MessagePack extension types, like timestamps, as a '{\"__ext_type__\": -1, \"__ext_data__\": \"<hex>\"}' object, where the data is a lowercase hexadecimal string. See the '--msgpack-restore-ext' flag for the reverse direction. This flag cannot be used with the '--preserve-ext' flag.")
+            .long("msgpack-ext-as-hex")
+            .conflicts_with("preserve-ext"))
+        .arg(Arg::with_name("msgpack-restore-ext")
+            .help("Serializes '{\"__ext_type__\": -1, \"__ext_data__\": \"<hex>\"}' objects, and the tagged representation from the '--preserve-ext' flag, back to MessagePack extension types if the output format is Msgpack. All other output formats have the objects written as-is.")
+            .long("msgpack-restore-ext"))
+        .arg(Arg::with_name("nan-as-null")
+            .help("Replaces non-finite floats, i.e. NaN and infinity, from the input with null. This is the default behavior. This flag cannot be used with the '--allow-nan' flag.")
+            .long("nan-as-null")
+            .conflicts_with("allow-nan"))
+        .arg(Arg::with_name("newline")
+            .help("Writes a newline after the final message of the output. This is independent of framing, so a newline is written once after all of the messages. This is useful for keeping textual output, like JSON, from running into the shell prompt.")
+            .long("newline")
+            .short("n"))
+        .arg(Arg::with_name("no-padding")
+            .help("Removes the '=' padding from the output of the 'base64' style. This flag is ignored unless the '--style' option is 'base64'.")
+            .long("no-padding"))
+        .arg(Arg::with_name("no-trailing-newline")
+            .help("Removes the final newline from each message of text output formats. Some serializers, like the YAML and TOML serializers, end the output with a newline, which can get in the way of concatenating messages or comparing the output byte for byte. This only applies to the Hjson, JSON, TOML, and YAML output formats and is ignored for all other output formats.")
+            .long("no-trailing-newline"))
+        .arg(Arg::with_name("normalize-numbers")
+            .help("Converts floats without a fractional part to integers before serialization, so '1.0' becomes '1'. Floats too large for a 64-bit integer remain floats. This reduces changes of number types when transcoding between formats with different number models, like YAML and TOML. Use the '--force-float' flag to convert integers to floats instead.")
+            .long("normalize-numbers"))
+        .arg(Arg::with_name("null-policy")
+            .help("Replaces null values before serialization. The 'empty-array', 'empty-object', 'empty-string', and 'zero' policies replace a null with '[]', '{}', '\"\"', and '0', respectively. The 'skip' policy removes null fields from objects and null elements from arrays. This is useful for output formats without a null type, like TOML. The value is case insensitive. This option takes precedence over the '--null-to-default' flag. [values: empty-array, empty-object, empty-string, skip, zero]")
+            .long("null-policy")
+            .hide_possible_values(true)
+            .possible_values(&NullPolicy::possible_values())
+            .takes_value(true))
+        .arg(Arg::with_name("null-to-default")
+            .help("Replaces null values with a default for the output format before serialization. The default for TOML, which does not have a null type, is an empty string. All other output formats keep null values as their nil, or null, value. This flag is ignored if the '--null-policy' option is used.")
+            .long("null-to-default"))
         .arg(Arg::with_name("output")
-            .help("A file to write the output instead of writing to stdout. If a file extension exists, then it is used to determined the format of the output serialized data. If a file extension does not exist, then the `-t,--to` option should be used or the MessagePack format is assumed.")
+            .help("A file to write the output instead of writing to stdout. If a file extension exists, then it is used to determined the format of the output serialized data. If a file extension does not exist, then the `-t,--to` option should be used or the MessagePack format is assumed. A file with the '.gz', '.lz4', or '.zst' extension is compressed with gzip, LZ4, or Zstandard, respectively, and the format is determined from the remaining extension.")
             .long("output")
             .short("o")
             .takes_value(true))
+        .arg(Arg::with_name("output-bom")
+            .help("Prepends the byte order mark (BOM) of the '--output-charset' option, or UTF-8 if the option is not used, to each message of the output. This flag cannot be used with a binary output format, like Msgpack.")
+            .long("output-bom"))
+        .arg(Arg::with_name("output-charset")
+            .help("The character encoding of the output for the text formats, like JSON and YAML. The serialized UTF-8 text of each message is transcoded to the charset, but the framing, prefix, and suffix are written as-is. This option cannot be used with a binary output format, like Msgpack. The value is case insensitive. [values: utf8, utf16le, utf16be] [default: utf8]")
+            .long("output-charset")
+            .hide_possible_values(true)
+            .possible_values(&Charset::possible_values())
+            .takes_value(true))
+        .arg(Arg::with_name("output-dir")
+            .help("A directory to write the output of each input file to a separate file instead of writing all of the output to stdout. Each file has the same name as its input file with the file extension of the '-t,--to' option, or the MessagePack format if the '-t,--to' option is not used. The files found with the '-R,--recursive' flag are written to the same relative path within the directory, so the directory structure is kept. If more than one input file has the same output file, such as 'a/data.json' and 'b/data.json', then a numeric suffix is added to the later files, like 'data-1.msgpack', instead of overwriting the earlier output. The directory and any subdirectories are created if they do not exist. At least one input file is required.")
+            .long("output-dir")
+            .conflicts_with("output")
+            .takes_value(true))
+        .arg(Arg::with_name("output-newline")
+            .help("Writes a newline (0Ah) as a binary byte after each message of the output, including any suffix and delimiter, so binary messages are on separate lines for tools like 'hexdump'. Unlike the '--delimited-output 0Ah' option, the newline is not part of the framing, so the output cannot be read back with a newline delimiter if a message contains a newline byte.")
+            .long("output-newline"))
+        .arg(Arg::with_name("output-null-terminated")
+            .help("Appends a null (00h) byte to each message of the output, like the input of the 'xargs -0' command. This is equivalent to using the '--delimited-output 0d' option. This flag cannot be used with the '--delimited', '--delimited-output', '--sized', '--sized-output', '--stxetx', or '--stxetx-output' options.")
+            .long("output-null-terminated")
+            .conflicts_with("delimited")
+            .conflicts_with("delimited-output")
+            .conflicts_with("sized")
+            .conflicts_with("sized-output")
+            .conflicts_with("stxetx")
+            .conflicts_with("stxetx-output"))
+        .arg(Arg::with_name("output-prefix")
+            .help("Prepends a sequence of bytes to each message of the output. The bytes are a comma- or space-separated list, where each byte uses the same notation as the delimiter byte for the '--delimited-output' option, such as '02h' or '0Dh,0Ah'. The prefix is always written as binary data, even if the '-r,--radix' or '--style' options are used, and it is written before any framing.")
+            .long("output-prefix")
+            .takes_value(true))
+        .arg(Arg::with_name("output-suffix")
+            .help("Appends a sequence of bytes to each message of the output. The bytes use the same notation as the '--output-prefix' option. The suffix is always written as binary data, even if the '-r,--radix' or '--style' options are used, and it is written before the delimiter byte if the '--delimited' or '--delimited-output' options are used.")
+            .long("output-suffix")
+            .takes_value(true))
+        .arg(Arg::with_name("pad")
+            .help("Zero pads each byte of the '-r,--radix' option output to the maximum number of digits for a byte, i.e. eight for bin, three for dec and oct, and two for hex, so the bytes line up in columns. By default, the bytes are written without leading zeros. This flag is ignored unless the '-r,--radix' or '--tee-radix' options are used.")
+            .long("pad"))
+        .arg(Arg::with_name("pointer")
+            .help("Extracts the value at a JSON Pointer, like '/servers/0/port', from each message and transcodes only that value. The pointer uses the syntax of RFC 6901, where '~0' is a '~' and '~1' is a '/' in a key, and an empty pointer is the entire message. It is an error if the pointer does not resolve to a value.")
+            .long("pointer")
+            .alias("from-json-pointer")
+            .value_name("POINTER")
+            .takes_value(true))
+        .arg(Arg::with_name("prefix")
+            .help("Prepends the radix prefix to each byte of the '-r,--radix' option output. The prefix is '0b' for bin, '0o' for oct, and '0x' for hex. The dec radix does not have a prefix. This flag is ignored unless the '-r,--radix' option is used.")
+            .long("prefix"))
+        .arg(Arg::with_name("preserve-ext")
+            .help("Keeps MessagePack extension types, like timestamps, from the input. An extension type is deserialized as a '{\"$msgpack_ext\": {\"type\": -1, \"data\": \"<base64>\"}}' tagged representation, where the data is base64 encoded, and the tagged representation is serialized back to an extension type if the output format is Msgpack. By default, an extension type is deserialized as an array of the type and the data.")
+            .long("preserve-ext"))
+        .arg(Arg::with_name("pretty-indent")
+            .help("Writes pretty JSON and Hjson output, where each value is on its own line and each level of nesting is indented with the number of spaces. By default, JSON output is compact and Hjson output is indented with two spaces. This option is ignored for all other output formats, except YAML output, which is always indented with two spaces and is an error. It cannot be used with the '--pretty-tab' flag. The '--indent' option is an alias.")
+            .long("pretty-indent")
+            .visible_alias("indent")
+            .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+            .takes_value(true))
+        .arg(Arg::with_name("pretty-tab")
+            .help("Writes pretty JSON and Hjson output, where each value is on its own line and each level of nesting is indented with a tab. This flag is ignored for all other output formats, except YAML output, which does not allow tabs and is an error. It cannot be used with the '--pretty-indent' option. The '--indent-tabs' flag is an alias.")
+            .long("pretty-tab")
+            .visible_alias("indent-tabs")
+            .conflicts_with("pretty-indent"))
+        .arg(Arg::with_name("preview")
+            .help("Writes a second line after the '-r,--radix' option output of each message with the bytes as lossy UTF-8 text, where invalid sequences and control characters are replaced with a '.', similar to the 'strings' command. This flag is ignored unless the '-r,--radix' option is used.")
+            .long("preview"))
+        .arg(Arg::with_name("progress")
+            .help("Writes a progress line with the number of input messages and bytes to stderr while transcoding, like 'Messages: 12345, Bytes in: 1.2 MB', which is rewritten in place every 100 milliseconds. A final newline is written when the transcoding ends. This is useful for seeing that a large input is still being transcoded. Nothing is written if stderr is not a terminal.")
+            .long("progress"))
+        .arg(Arg::with_name("quiet")
+            .help("Suppresses the warnings, skipped messages, and other diagnostics written to stderr that are not errors, so the output can be piped cleanly. An error that causes a non-zero exit code is still written. This flag cannot be used with the '--progress', '--stats', '--stats-json', or '-v,--verbose' options.")
+            .long("quiet")
+            .short("q")
+            .conflicts_with_all(&["progress", "stats", "stats-json", "verbose"]))
         .arg(Arg::with_name("radix")
-             .help("Changes the output to be a space-separated list of bytes, where each byte is represented as a numeric string based on the radix value. The serialized input data is transcoded to the format specified with the '-t,--to' option, but it is written to the output as a string. This is useful for debugging serialization formats and creating an interactive console. Note, if delimited-based framing is employed, the delimiter byte is not included in the space-separated list of bytes. The radix value can be the first letter of the possible values ('b', 'd', 'h', or 'o') and the value is case insensitive. [values: bin, dec, hex, oct] [default: hex]")
+             .help("Changes the output to be a space-separated list of bytes, where each byte is represented as a numeric string based on the radix value. The serialized input data is transcoded to the format specified with the '-t,--to' option, but it is written to the output as a string. This is useful for debugging serialization formats and creating an interactive console. Note, if delimited-based framing is employed, the delimiter byte is not included in the space-separated list of bytes. The radix value can be the first letter of the possible values ('b', 'd', 'h', or 'o') and the value is case insensitive. If this option is not used, then the radix is read from the PANSER_RADIX environment variable, if it is set, unless the '--cbor-diag', '--style', or '--tee-radix' option is used. [values: bin, dec, hex, oct] [default: hex]")
              .long("radix")
              .short("r")
              .hide_possible_values(true)
              .possible_values(&Radix::possible_values())
              .takes_value(true))
+        .arg(Arg::with_name("radix-input")
+            .help("Changes the input to be a list of bytes, where each byte is represented as a numeric string based on the radix value. This is the reverse of the '-r,--radix' option, so the output of the '-r,--radix' option can be used as input. The bytes are separated by whitespace or commas and each byte can have a radix prefix, such as '0x' for hex. The bytes are converted to binary data before deserialization with the format specified with the '-f,--from' option. If framing is used, then each message is a list of bytes, such as each line of the input with the '--delimited-input 0Ah' option. The radix value can be the first letter of the possible values ('b', 'd', 'h', or 'o') and the value is case insensitive. [values: bin, dec, hex, oct]")
+            .long("radix-input")
+            .hide_possible_values(true)
+            .possible_values(&Radix::possible_values())
+            .takes_value(true))
+        .arg(Arg::with_name("reconnect")
+             .help("Reconnects to the '--connect' server if the connection fails. Connecting is retried up to five times with a backoff that starts at 250 milliseconds and doubles for each attempt, and a message that fails to send is sent again after reconnecting. Each message is sent whole, so the framing is not broken by a reconnect.")
+             .long("reconnect")
+             .requires("connect"))
+        .arg(Arg::with_name("recursive")
+            .help("Walks any directory in the input files recursively and reads every file with a file extension for one of the input formats, such as '.yaml'. The files are read in order by name and all other files are ignored. The directories matching a glob pattern in the input files are also walked. Unless the '-f,--from' option is used, the file extension for each file is used to determine the input data format.")
+            .long("recursive")
+            .short("R"))
+        .arg(Arg::with_name("rename-ext")
+            .help("Renames the file extension of each file rewritten with the '--in-place' flag to the file extension of the '-t,--to' format, so 'config.json' is replaced with 'config.toml' for the TOML format. This flag requires the '--in-place' flag.")
+            .long("rename-ext")
+            .requires("in-place"))
+        .arg(Arg::with_name("rust-slice")
+             .help("Writes the output of the 'rust' style as a '&[...]' slice instead of a 'vec![...]' macro. This flag is ignored unless the '--style' option is 'rust'.")
+             .long("rust-slice"))
+        .arg(Arg::with_name("silent")
+            .help("Suppresses everything written to stderr, including the errors. The exit code is still non-zero if an error occurs. This flag implies the '-q,--quiet' flag and cannot be used with the '--progress', '--stats', '--stats-json', or '-v,--verbose' options.")
+            .long("silent")
+            .conflicts_with_all(&["progress", "stats", "stats-json", "verbose"]))
         .arg(Arg::with_name("sized")
             .help("Indicates the first four bytes of the input is an unsigned 32-bit integer in Big Endian (Network Order) that is the total size of the serialized data, and the data size should be prepended to the output. This flag cannot be used with the '--delimited', '--delimited-input', '--delimited-output', '--sized-input', or '--sized-output' options.")
             .long("sized")
@@ -107,29 +450,354 @@ fn main() {
             .conflicts_with("delimited")
             .conflicts_with("delimited-output")
             .conflicts_with("sized"))
+        .arg(Arg::with_name("stats")
+            .help("Writes the size of each serialized output message and a summary to stderr. The summary is the number of messages, the total input and output bytes, the minimum, mean, and maximum output message size, and the elapsed time. Sizes do not include any framing, such as the size, the delimiter, the STX/ETX bytes, or the checksum. Use '-o /dev/null' to only measure the sizes.")
+            .long("stats"))
+        .arg(Arg::with_name("stats-json")
+            .help("Writes a summary of the run to stderr as a single line of JSON after all of the messages. The object has the 'messages', 'received', 'skipped', 'errors_collected', 'input_bytes', 'output_bytes', 'min_bytes', 'mean_bytes', 'max_bytes', 'elapsed_seconds', 'input_formats', and 'output_format' fields, where the 'input_formats' are the number of messages deserialized for each input format. Unlike the '--stats' flag, the size of each message is not written.")
+            .long("stats-json"))
+        .arg(Arg::with_name("stdin")
+            .help("Reads stdin even if it is a terminal, so the input can be typed. By default, the usage is written to stderr instead of waiting for the input if stdin is a terminal, no input files are given, and no other input, like the '-e,--eval' or '--listen' options, or input framing, like the '--delimited-input' option, is used.")
+            .long("stdin")
+            .conflicts_with_all(&["FILES", "eval", "listen", "udp-listen", "unix-listen"]))
+        .arg(Arg::with_name("stop-after")
+            .help("Stops after writing a number of messages. The output is flushed and the process exits without reading the rest of the input, even if the input is a pipe that is still open. This is useful for taking the first few messages from a continuous stream. The number must be at least one.")
+            .long("stop-after")
+            .validator(|v| match v.parse::<usize>() {
+                Ok(0) => Err(String::from("The number of messages must be at least one")),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            })
+            .takes_value(true))
+        .arg(Arg::with_name("stringify-keys")
+            .help("Converts the boolean and number keys of maps to strings, such as the integer keys of a MessagePack map, so the map can be transcoded to formats with only string keys, like JSON. By default, a map with a key that is not a string fails to deserialize. This is lossy, since the keys are written as strings for every output format, including MessagePack. The '--coerce-scalars' flag only converts values, so it does not restore the keys. This flag is used for the CBOR, Msgpack, and YAML input formats.")
+            .long("stringify-keys"))
+        .arg(Arg::with_name("stxetx")
+            .help("Indicates each message of the input is wrapped with the ASCII Start-of-Text (STX, 02h) and End-of-Text (ETX, 03h) bytes, and the output should be wrapped with the same bytes. This is equivalent to using the '--stxetx-input' and '--stxetx-output' flags. Note, the ETX byte is not escaped within a message. This flag cannot be used with the '--delimited', '--delimited-input', '--delimited-output', '--sized', '--sized-input', '--sized-output', '--stxetx-input', or '--stxetx-output' options.")
+            .long("stxetx")
+            .conflicts_with("delimited")
+            .conflicts_with("delimited-input")
+            .conflicts_with("delimited-output")
+            .conflicts_with("sized")
+            .conflicts_with("sized-input")
+            .conflicts_with("sized-output")
+            .conflicts_with("stxetx-input")
+            .conflicts_with("stxetx-output"))
+        .arg(Arg::with_name("stxetx-checksum")
+            .help("The checksum that follows the ETX byte when STX/ETX framing is used. The checksum is calculated over the message and the ETX byte. The 'lrc' value is a single byte XOR of the bytes and the 'crc16' value is a two byte CRC-16/ARC in Big Endian (Network Order). The value is case insensitive. This option can only be used with the '--stxetx', '--stxetx-input', or '--stxetx-output' flags. [values: LRC, CRC16]")
+            .long("stxetx-checksum")
+            .hide_possible_values(true)
+            .possible_values(&Checksum::possible_values())
+            .takes_value(true))
+        .arg(Arg::with_name("stxetx-input")
+            .help("Indicates each message of the input is wrapped with the ASCII Start-of-Text (STX, 02h) and End-of-Text (ETX, 03h) bytes. This flag cannot be used with the '--delimited', '--delimited-input', '--sized', '--sized-input', or '--stxetx' options.")
+            .long("stxetx-input")
+            .conflicts_with("delimited")
+            .conflicts_with("delimited-input")
+            .conflicts_with("sized")
+            .conflicts_with("sized-input")
+            .conflicts_with("stxetx"))
+        .arg(Arg::with_name("stxetx-output")
+            .help("Wraps the serialized data with the ASCII Start-of-Text (STX, 02h) and End-of-Text (ETX, 03h) bytes. This flag cannot be used with the '--delimited', '--delimited-output', '--sized', '--sized-output', or '--stxetx' options.")
+            .long("stxetx-output")
+            .conflicts_with("delimited")
+            .conflicts_with("delimited-output")
+            .conflicts_with("sized")
+            .conflicts_with("sized-output")
+            .conflicts_with("stxetx"))
+        .arg(Arg::with_name("style")
+            .help("Changes the output to be source code, or text, in a style. The 'base64' style is base64 text, such as 'gaRib29sww=='. The 'checksum' style is the digest of each message as lowercase hexadecimal instead of the data, with one line per message prefixed with the index of the message, starting at one, and without any framing, where the digest algorithm is appended after a colon, such as 'checksum:md5', and defaults to 'sha256'. The 'c-array' style is a comma-separated list of C-style hexadecimal literals, such as '0x81, 0xa4'. The 'escaped' style is an escaped string, where printable ASCII characters are as-is, the double quote and backslash are escaped with a backslash, and all other bytes are hexadecimal escapes, such as '\\x81\\xa4bool\\xc3'. The 'hexdump' style is similar to the output of the 'xxd' command, with an offset, the bytes as hexadecimal, and the bytes as ASCII characters on each line, where the offset starts at zero for each message. The 'python-bytes' style is a Python bytes literal of the 'escaped' style, such as 'b\"\\x81\\xa4bool\\xc3\"'. The 'rust' style is a Rust 'vec!' macro of hexadecimal byte literals, such as 'vec![0x81, 0xa4]', with one macro per line for each message. Similar to the '-r,--radix' option, the serialized data is transcoded to the format specified with the '-t,--to' option, but it is written to the output as a string. The value is case insensitive. This option cannot be used with the '-r,--radix' option. [values: base64, checksum, checksum:crc32, checksum:md5, checksum:sha1, checksum:sha256, c-array, escaped, hexdump, python-bytes, rust]")
+            .long("style")
+            .hide_possible_values(true)
+            .possible_values(&Style::possible_values())
+            .conflicts_with("radix")
+            .takes_value(true))
+        .arg(Arg::with_name("tee-radix")
+            .help("Writes the output as usual and also writes each framed message to stderr as a space-separated list of bytes, where each byte is represented as a numeric string based on the radix value, like the '-r,--radix' option. Each message is preceded by a header line with the frame number, size, and input offset, like the '--annotate' flag. This is useful for seeing the binary frames sent to another program, such as 'nc'. The text of each message is written all at once, so it is not interleaved with error messages. This option cannot be used with the '-r,--radix' or '--style' options. [values: bin, dec, hex, oct]")
+            .long("tee-radix")
+            .hide_possible_values(true)
+            .possible_values(&Radix::possible_values())
+            .conflicts_with("radix")
+            .conflicts_with("style")
+            .takes_value(true))
         .arg(Arg::with_name("to")
-            .help("The output format. The value is case insensitive. [values: Bincode, CBOR, Hjson, JSON, Msgpack, Pickle, TOML, URL, YAML] [default: Msgpack]")
+            .help("The output format. The value is case insensitive. The option can be used more than once to write the same messages to more than one format, such as '-t json -t yaml -o out', which writes 'out.json' and 'out.yaml'. The '-o,--output' option is then the stem of each output file and is required, unless the '--output-dir' option is used, since the formats cannot all be written to stdout. If this option is not used and the format cannot be determined from the '-o,--output' file extension, then the format is read from the PANSER_TO environment variable, if it is set, unless the '--cbor-diag' flag is used. [values: Bincode, CBOR, FlexBuffers, Hjson, JSON, Markdown, Msgpack, Pickle, RESP, SMILE, TOML, TSV, URL, YAML] [default: Msgpack]")
             .long("to")
             .short("t")
             .hide_possible_values(true)
             .possible_values(&ToFormat::possible_values())
+            .multiple(true)
+            .number_of_values(1)
+            .takes_value(true))
+        .arg(Arg::with_name("udp-listen")
+            .help("A UDP address to listen on and read the input from datagrams instead of reading from stdin, such as '0.0.0.0:1234'. Each datagram is a message, so no framing is needed, and datagrams are read until the process is stopped, such as with Ctrl+C, or the '--stop-after' option is reached. A datagram larger than the '--udp-max-size' option is skipped with a warning, since it may have been truncated.")
+            .long("udp-listen")
+            .conflicts_with_all(&[
+                "FILES",
+                "delimited",
+                "delimited-input",
+                "input-null-terminated",
+                "listen",
+                "output-dir",
+                "recursive",
+                "sized",
+                "sized-input",
+                "stxetx",
+                "stxetx-input",
+                "watch",
+            ])
+            .takes_value(true))
+        .arg(Arg::with_name("udp-max-size")
+            .help("The maximum size, in bytes, of a datagram for the '--udp-listen' and '--udp-send' options. A larger datagram is skipped when reading, and a larger message is not sent when writing. A warning is written to stderr for either. The number must be at least one. [default: 65507]")
+            .long("udp-max-size")
+            .validator(|v| match v.parse::<usize>() {
+                Ok(0) => Err(String::from("The size must be at least one")),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            })
+            .takes_value(true))
+        .arg(Arg::with_name("udp-send")
+            .help("A UDP address to send each message of the output to as a datagram instead of writing to stdout, such as '127.0.0.1:1234'. The host name is resolved. Any framing, prefix, and suffix are included in the datagram of each message. A message larger than the '--udp-max-size' option is not sent and a warning is written to stderr.")
+            .long("udp-send")
+            .conflicts_with_all(&["connect", "output", "output-dir"])
+            .takes_value(true))
+        .arg(Arg::with_name("verbose")
+            .help("Writes a log of each message to stderr: the input format, the size and first 64 bytes of the raw input as hexadecimal, the deserialized value as compact JSON, and the size of the serialized output. Each line starts with the input frame number in brackets, like '[3]'. Use the flag twice, '-vv', to log all of the input and output bytes. The log is only written if stderr is a terminal, so a piped or redirected stderr is not polluted.")
+            .long("verbose")
+            .short("v")
+            .multiple(true))
+        .arg(Arg::with_name("watch")
+            .help("Transcodes the input files again each time an input file changes until the process is stopped, such as with Ctrl+C. Rapid changes within 200 milliseconds are combined into a single change. The output is overwritten each time unless the '--append' flag is used. An error while transcoding is written to stderr and does not stop the watching. At least one input file is required.")
+            .long("watch"))
+        .arg(Arg::with_name("width")
+            .help("The number of bytes per line when the '-r,--radix' or '--style' options are used. By default, all of the bytes for a message are written on a single line, except for the '--c-declaration' flag, which uses twelve bytes per line, and the 'hexdump' style, which uses sixteen bytes per line.")
+            .long("width")
+            .validator(|v| v.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
             .takes_value(true))
-        .get_matches();
+        .arg(Arg::with_name("zstd-input")
+            .help("Decompresses every input file and stdin with Zstandard before it is read. By default, an input file is only decompressed if it has the '.zst' extension or starts with the Zstandard magic bytes, 28 B5 2F FD, which is detected separately for each file, and stdin is never decompressed. The framing is read from the decompressed data. This flag cannot be used with the '--gzip-input' flag.")
+            .long("zstd-input")
+            .conflicts_with("gzip-input"))
+        .arg(Arg::with_name("zstd-output")
+            .help("Compresses the output with Zstandard. The optional LEVEL is from 1, the fastest, to 22, the best compression, and must be set with an equals sign, such as '--zstd-output=19'. The default LEVEL is 3. The output is also compressed if the '-o,--output' file has the '.zst' extension, and the format is determined from the remaining extension, so 'data.json.zst' is JSON. For the '--output-dir' option, the '.zst' extension is added to each output file. This option cannot be used with the '--gzip-output' option.")
+            .long("zstd-output")
+            .value_name("LEVEL")
+            .min_values(0)
+            .max_values(1)
+            .require_equals(true)
+            .conflicts_with("gzip-output")
+            .validator(|v| match v.parse::<i32>() {
+                Ok(1..=22) => Ok(()),
+                Ok(_) => Err(String::from("The Zstandard level must be from 1 to 22")),
+                Err(e) => Err(e.to_string()),
+            }));
+    // Unix domain sockets only exist on Unix-like platforms, so the options are only added for
+    // Unix builds, like the Windows guard for ANSI support.
+    #[cfg(unix)]
+    let app = app
+        .arg(Arg::with_name("unix-connect")
+            .help("A Unix domain socket file to write the output to instead of writing to stdout, such as '/run/app.sock'. This is the same as the '--connect' option, but for a local socket file instead of a TCP server.")
+            .long("unix-connect")
+            .conflicts_with_all(&["connect", "output", "output-dir", "udp-send"])
+            .takes_value(true))
+        .arg(Arg::with_name("unix-listen")
+            .help("A Unix domain socket file to listen on and read the input from a client instead of reading from stdin, such as '/tmp/panser.sock'. This is the same as the '--listen' option, but for a local socket file instead of a TCP address. A stale socket file, where connecting is refused, is removed first, but the socket file of a running server, or any other file, is never removed. The socket file is removed when the input ends.")
+            .long("unix-listen")
+            .group("listeners")
+            .conflicts_with_all(&["FILES", "output-dir", "recursive", "udp-listen", "watch"])
+            .takes_value(true))
+        .arg(Arg::with_name("unix-mode")
+            .help("The permissions of the socket file created for the '--unix-listen' option as an octal number, such as '600' for only the owner to read and write. By default, the permissions are from the umask of the process.")
+            .long("unix-mode")
+            .requires("unix-listen")
+            .validator(|v| match u32::from_str_radix(&v, 8) {
+                Ok(m) if m > 0o777 => Err(String::from("The mode must be at most 777")),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            })
+            .takes_value(true));
+    let matches = app.get_matches();
+    let silent = matches.is_present("silent");
+    // An environment variable is only read if no option that sets or conflicts with it is used,
+    // so the command line always takes precedence. The formats are only the defaults, so the
+    // format from a file extension still takes precedence over them, too.
+    let framing = [
+        "delimited", "delimited-input", "delimited-output", "input-null-terminated",
+        "output-null-terminated", "recursive", "sized", "sized-input", "sized-output", "stxetx",
+        "stxetx-input", "stxetx-output", "udp-listen",
+    ];
+    let delimiter = env_value::<String>(&matches, &framing, "PANSER_DELIMITER");
+    let default_from = env_value::<FromFormat>(&matches, &["from"], "PANSER_FROM");
+    let default_to = env_value::<ToFormat>(&matches, &["cbor-diag", "to"], "PANSER_TO");
+    let radix = env_value::<Radix>(
+        &matches,
+        &["cbor-diag", "radix", "style", "tee-radix"],
+        "PANSER_RADIX",
+    );
+    // A bare `panser` in a terminal waits for the input on stdin until Ctrl+D, which looks like it
+    // hangs, so the usage is written instead unless the input can come from somewhere else or is
+    // framed for an interactive console.
+    let inputs = [
+        "FILES", "delimited", "delimited-input", "eval", "input-null-terminated", "list-formats",
+        "listen", "sized", "sized-input", "stdin", "stxetx", "stxetx-input", "udp-listen",
+        "unix-listen",
+    ];
+    let envy = match value_t!(matches, "from", FromFormat).ok().or(default_from) {
+        Some(FromFormat::Envy) => true,
+        _ => false,
+    };
+    let framed = delimiter.is_some() || inputs.iter().any(|a| matches.is_present(a));
+    if !framed && !envy && atty::is(atty::Stream::Stdin) {
+        if !silent {
+            eprintln!(
+                "{}\n\nThe input is read from stdin, which is a terminal. Pipe the input, such as \
+                 'panser -t yaml < data.json', give input files, or use the '--stdin' flag to type \
+                 the input.\n\nFor more information try --help",
+                matches.usage()
+            );
+        }
+        std::process::exit(ExitCode::Generic.into());
+    }
     let result = Panser::new()
-        .delimited_output(matches.value_of("delimited-output").or(matches.value_of("delimited")))
-        .delimited_input(matches.value_of("delimited-input").or(matches.value_of("delimited")))
+        .allow_nan(matches.is_present("allow-nan"))
+        .also_to(
+            values_t!(matches, "to", ToFormat).map_or_else(|_| Vec::new(), |mut t| t.split_off(1)),
+        )
+        .annotate(matches.is_present("annotate"))
+        .append(matches.is_present("append"))
+        .ascii_only(matches.is_present("ascii-only"))
+        .atomic(matches.is_present("atomic"))
+        .base64_to_bytes(matches.is_present("base64-to-bytes"))
+        .base64_url(matches.is_present("base64-url"))
+        .bytes_as_base64(matches.is_present("bytes-as-base64"))
+        .c_declaration(matches.is_present("c-declaration"))
+        .cbor_diag(matches.is_present("cbor-diag"))
+        .check(matches.is_present("check"))
+        .checksum(value_t!(matches, "checksum", Digest).ok())
+        .checksum_writer(Some(Box::new(std::io::stderr())))
+        .coerce_numbers(matches.is_present("coerce-numbers"))
+        .coerce_scalars(matches.is_present("coerce-scalars"))
+        .color(Some(value_t!(matches, "color", Color).unwrap_or(Color::Auto)))
+        .connect(matches.value_of("connect"))
+        .connect_timeout(value_t!(matches, "connect-timeout", u64).ok())
+        .continue_on_error(matches.is_present("continue-on-error"))
+        .default_from(default_from)
+        .default_to(default_to)
+        .delimited_output(
+            matches
+                .value_of("delimited-output")
+                .or(matches.value_of("delimited"))
+                .or(delimiter.as_deref())
+                .or(if matches.is_present("output-null-terminated") {
+                    Some("0d")
+                } else {
+                    None
+                }),
+        )
+        .diff(matches.is_present("diff"))
+        .diff_against(matches.value_of("diff-against"))
+        .escape_html(matches.is_present("escape-html"))
+        .eval(matches.values_of("eval").map(|v| v.collect::<Vec<&str>>()))
+        .every(value_t!(matches, "every", usize).unwrap_or(1))
+        .delimited_input(
+            matches
+                .value_of("delimited-input")
+                .or(matches.value_of("delimited"))
+                .or(delimiter.as_deref())
+                .or(if matches.is_present("input-null-terminated") {
+                    Some("0d")
+                } else {
+                    None
+                }),
+        )
+        .follow(matches.is_present("follow"))
+        .force_float(matches.is_present("force-float"))
         .from(value_t!(matches, "from", FromFormat).ok())
+        .from_base16_file(matches.is_present("from-base16-file"))
+        .from_format_header(matches.is_present("from-stdin-format-header"))
+        .group(value_t!(matches, "group", usize).ok())
+        .gzip_input(matches.is_present("gzip-input"))
+        .gzip_output(if matches.is_present("gzip-output") {
+            value_t!(matches, "gzip-output", u32).ok().or(Some(6))
+        } else {
+            None
+        })
+        .in_place(matches.is_present("in-place"))
+        .input_encoding(value_t!(matches, "input-encoding", Encoding).ok())
         .inputs(matches.values_of("FILES").map(|v| v.collect::<Vec<&str>>()))
+        .jobs(value_t!(matches, "jobs", usize).ok())
+        .keep_listening(matches.is_present("keep-listening"))
+        .line_numbers(matches.is_present("line-numbers"))
+        .list_formats(matches.is_present("list-formats"))
+        .listen(matches.value_of("listen"))
+        .lower(matches.is_present("lower"))
+        .lz4_input(matches.is_present("lz4-input"))
+        .lz4_output(matches.is_present("lz4-output"))
+        .msgpack_ext_as_hex(matches.is_present("msgpack-ext-as-hex"))
+        .msgpack_restore_ext(matches.is_present("msgpack-restore-ext"))
+        .nan_as_null(matches.is_present("nan-as-null"))
+        .newline(matches.is_present("newline"))
+        .no_padding(matches.is_present("no-padding"))
+        .normalize_numbers(matches.is_present("normalize-numbers"))
+        .null_policy(value_t!(matches, "null-policy", NullPolicy).ok())
+        .null_to_default(matches.is_present("null-to-default"))
         .output(matches.value_of("output"))
-        .radix(value_t!(matches, "radix", Radix).ok())
+        .output_bom(matches.is_present("output-bom"))
+        .output_charset(value_t!(matches, "output-charset", Charset).ok())
+        .output_dir(matches.value_of("output-dir"))
+        .output_newline(matches.is_present("output-newline"))
+        .output_prefix(matches.value_of("output-prefix"))
+        .output_suffix(matches.value_of("output-suffix"))
+        .pad(matches.is_present("pad"))
+        .pointer(matches.value_of("pointer"))
+        .prefix(matches.is_present("prefix"))
+        .preserve_ext(matches.is_present("preserve-ext"))
+        .pretty_indent(value_t!(matches, "pretty-indent", usize).ok())
+        .pretty_tab(matches.is_present("pretty-tab"))
+        .preview(matches.is_present("preview"))
+        .progress(matches.is_present("progress"))
+        .quiet(matches.is_present("quiet"))
+        .radix(value_t!(matches, "radix", Radix).ok().or(radix))
+        .radix_input(value_t!(matches, "radix-input", Radix).ok())
+        .reconnect(matches.is_present("reconnect"))
+        .recursive(matches.is_present("recursive"))
+        .rename_ext(matches.is_present("rename-ext"))
+        .rust_slice(matches.is_present("rust-slice"))
+        .silent(silent)
         .sized_input(matches.is_present("sized-input") || matches.is_present("sized"))
         .sized_output(matches.is_present("sized-output") || matches.is_present("sized"))
+        .stats(matches.is_present("stats"))
+        .stats_json(matches.is_present("stats-json"))
+        .stop_after(value_t!(matches, "stop-after", usize).ok())
+        .stringify_keys(matches.is_present("stringify-keys"))
+        .stxetx_checksum(value_t!(matches, "stxetx-checksum", Checksum).ok())
+        .stxetx_input(matches.is_present("stxetx-input") || matches.is_present("stxetx"))
+        .stxetx_output(matches.is_present("stxetx-output") || matches.is_present("stxetx"))
+        .style(value_t!(matches, "style", Style).ok())
+        .tee_radix(value_t!(matches, "tee-radix", Radix).ok())
         .to(value_t!(matches, "to", ToFormat).ok())
+        .trailing_newline(!matches.is_present("no-trailing-newline"))
+        .udp_listen(matches.value_of("udp-listen"))
+        .udp_max_size(value_t!(matches, "udp-max-size", usize).ok())
+        .udp_send(matches.value_of("udp-send"))
+        .unix_connect(matches.value_of("unix-connect"))
+        .unix_listen(matches.value_of("unix-listen"))
+        .unix_mode(matches.value_of("unix-mode").and_then(|m| u32::from_str_radix(m, 8).ok()))
+        .verbosity(matches.occurrences_of("verbose").min(u64::from(u8::max_value())) as u8)
+        .watch(matches.is_present("watch"))
+        .width(value_t!(matches, "width", usize).ok())
+        .zstd_input(matches.is_present("zstd-input"))
+        .zstd_output(if matches.is_present("zstd-output") {
+            value_t!(matches, "zstd-output", i32).ok().or(Some(3))
+        } else {
+            None
+        })
         .run();
     match result {
         Ok(_) => {
-            std::process::exit(0);
+            std::process::exit(ExitCode::Success.into());
         },
+        Err(e) if silent => std::process::exit(e.code().into()),
         Err(e) => {
             let mut tag = format!("Error[{}] ({})", e.code(), e);
             if atty::is(atty::Stream::Stderr) {
@@ -137,8 +805,30 @@ fn main() {
             }
             writeln!(&mut std::io::stderr(), "{}: {}", tag, e)
                 .expect("Writing to stderr");
-            std::process::exit(e.code());
+            std::process::exit(e.code().into());
         }
     }
 }
 
+/// Gets the value of an environment variable if none of the `overrides` options are used.
+///
+/// An empty environment variable is the same as an unset variable. The process exits with a usage
+/// error if the value of the environment variable is invalid.
+fn env_value<T>(matches: &ArgMatches, overrides: &[&str], var: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if overrides.iter().any(|a| matches.is_present(a)) {
+        return None;
+    }
+    let value = env::var(var).ok().filter(|v| !v.is_empty())?;
+    match value.parse() {
+        Ok(v) => Some(v),
+        Err(e) => clap::Error::with_description(
+            &format!("Invalid value '{}' for the {} environment variable: {}", value, var, e),
+            ErrorKind::InvalidValue,
+        )
+        .exit(),
+    }
+}
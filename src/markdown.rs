@@ -0,0 +1,106 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serialization of a universal, arbitrary value to a GitHub Flavored Markdown (GFM) table.
+
+use serde_json::Value;
+
+use super::table;
+use super::{Error, Result};
+
+/// Serializes a value to a GitHub Flavored Markdown (GFM) table.
+///
+/// An array of objects is a table, where the columns are the union of all of the keys of the
+/// objects and each row is the values of an object. A missing key is an empty cell. An empty
+/// array has no columns, so nothing is written. A single object is a two-column table of keys and
+/// values.
+///
+/// # Errors
+///
+/// A `Generic` error occurs if the value is not an object or an array of objects, or a cell
+/// contains an object or an array with a nested array or object that cannot be flattened to
+/// a cell.
+pub fn to_vec(value: &Value) -> Result<Vec<u8>> {
+    let mut table = String::new();
+    match *value {
+        Value::Array(ref rows) if rows.is_empty() => {}
+        Value::Array(ref rows) => {
+            let objects = table::objects(rows, "Markdown")?;
+            let columns = table::columns(&objects);
+            table.push_str(&row(columns.iter().map(|c| escape(c))));
+            table.push_str(&row(columns.iter().map(|_| String::from("---"))));
+            for object in objects {
+                let cells = columns
+                    .iter()
+                    .map(|c| object.get(*c).map_or(Ok(String::new()), cell))
+                    .collect::<Result<Vec<String>>>()?;
+                table.push_str(&row(cells.into_iter()));
+            }
+        }
+        Value::Object(ref object) => {
+            table.push_str(&row(vec![String::from("Key"), String::from("Value")].into_iter()));
+            table.push_str(&row(vec![String::from("---"), String::from("---")].into_iter()));
+            for (key, value) in object {
+                table.push_str(&row(vec![escape(key), cell(value)?].into_iter()));
+            }
+        }
+        _ => {
+            return Err(Error::Generic(String::from(
+                "Markdown output requires an object or an array of objects",
+            )))
+        }
+    }
+    Ok(table.into_bytes())
+}
+
+/// Formats the cells as a row of the table, including the trailing newline.
+fn row<I: Iterator<Item = String>>(cells: I) -> String {
+    format!("| {} |\n", cells.collect::<Vec<String>>().join(" | "))
+}
+
+/// Formats a value as the text of a cell.
+///
+/// An array of values that are not arrays or objects is a comma-separated list.
+fn cell(value: &Value) -> Result<String> {
+    match *value {
+        Value::Array(ref values) => values
+            .iter()
+            .map(|v| match *v {
+                Value::Array(_) | Value::Object(_) => Err(nested_error()),
+                _ => cell(v),
+            })
+            .collect::<Result<Vec<String>>>()
+            .map(|v| v.join(", ")),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Null => Ok(String::new()),
+        Value::Number(ref n) => Ok(n.to_string()),
+        Value::Object(_) => Err(nested_error()),
+        Value::String(ref s) => Ok(escape(s)),
+    }
+}
+
+/// Escapes the pipe character and replaces newlines with line breaks, so the text stays within
+/// a cell.
+fn escape(s: &str) -> String {
+    s.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>")
+}
+
+fn nested_error() -> Error {
+    Error::Generic(String::from(
+        "Nested objects and arrays cannot be flattened to a Markdown table",
+    ))
+}
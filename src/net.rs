@@ -0,0 +1,547 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Network sources and destinations for the serialized data.
+
+#[cfg(unix)]
+use std::fs::{self, Permissions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+#[cfg(feature = "http")]
+use ureq;
+
+use super::{Error, FromFormat, Result};
+
+/// The largest payload of a UDP datagram over IPv4.
+pub const MAX_DATAGRAM_SIZE: usize = 65_507;
+/// The number of times to reconnect before giving up.
+const RECONNECT_ATTEMPTS: u32 = 5;
+/// The delay before the first reconnect, which doubles for each attempt.
+const RECONNECT_BACKOFF: Duration = Duration::from_millis(250);
+/// The number of redirects to follow for an HTTP(S) input.
+#[cfg(feature = "http")]
+const HTTP_MAX_REDIRECTS: u32 = 10;
+
+/// Checks if an input is an HTTP or HTTPS URL instead of a path to a file.
+pub fn is_url(input: &str) -> bool {
+    let input = input.to_lowercase();
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+/// Gets the path of a URL without the query and the fragment, so the file extension can be used
+/// to determine the input format, like `/data.yaml` for `https://example.com/data.yaml?v=2`.
+pub fn url_path(url: &str) -> &str {
+    url.split(|c| c == '?' || c == '#').next().unwrap_or(url)
+}
+
+/// Fetches the body of an HTTP(S) URL to read as an input, along with the input format from the
+/// `Content-Type` header of the response, if it is recognized.
+///
+/// Redirects are followed up to ten times. The body is decompressed if the server used gzip for
+/// the `Content-Encoding`.
+///
+/// # Errors
+///
+/// A `Generic` error will occur if the request fails or the response has a status that is not
+/// a success (2xx), where the message includes the status.
+#[cfg(feature = "http")]
+pub fn http_get(url: &str) -> Result<(Box<dyn Read + Send>, Option<FromFormat>)> {
+    let agent = ureq::AgentBuilder::new().redirects(HTTP_MAX_REDIRECTS).build();
+    match agent.get(url).call() {
+        Ok(response) => {
+            let from = response.header("Content-Type").and_then(FromFormat::from_content_type);
+            Ok((Box::new(response.into_reader()), from))
+        }
+        Err(ureq::Error::Status(status, response)) => Err(Error::Generic(format!(
+            "The request for '{}' failed with the HTTP status {} {}",
+            url,
+            status,
+            response.status_text()
+        ))),
+        // The error of a failed request already includes the URL.
+        Err(e) => Err(Error::Generic(format!("The request failed: {}", e))),
+    }
+}
+
+/// Fails to fetch an HTTP(S) URL, since the `http` feature is not enabled.
+#[cfg(not(feature = "http"))]
+pub fn http_get(url: &str) -> Result<(Box<dyn Read + Send>, Option<FromFormat>)> {
+    Err(Error::Generic(format!(
+        "The '{}' input requires the 'http' feature",
+        url
+    )))
+}
+
+/// A TCP client for writing the output to a server.
+///
+/// The written bytes are buffered until a flush, so each message is sent with a single write and
+/// is never split by a reconnect. Nagle's algorithm is disabled (TCP_NODELAY), so small messages
+/// are sent immediately instead of waiting for more data.
+pub struct TcpWriter {
+    address: String,
+    buffer: Vec<u8>,
+    reconnect: bool,
+    stream: TcpStream,
+    timeout: Option<Duration>,
+}
+
+impl TcpWriter {
+    /// Connects to the address, which is a `host:port` string, where the host can be a name or an
+    /// IP address.
+    ///
+    /// If `reconnect` is `true`, then connecting is retried with an exponential backoff, and
+    /// a failed flush reconnects and sends the message again.
+    ///
+    /// # Errors
+    ///
+    /// An I/O error with the address in the message occurs if the address cannot be resolved or
+    /// a connection cannot be made.
+    pub fn connect(
+        address: &str,
+        timeout: Option<Duration>,
+        reconnect: bool,
+    ) -> io::Result<TcpWriter> {
+        let stream = if reconnect {
+            retry(address, timeout)?
+        } else {
+            open(address, timeout)?
+        };
+        Ok(TcpWriter {
+            address: address.to_owned(),
+            buffer: Vec::new(),
+            reconnect,
+            stream,
+            timeout,
+        })
+    }
+}
+
+impl Write for TcpWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        if let Err(err) = self.stream.write_all(&self.buffer) {
+            if !self.reconnect {
+                return Err(with_address(&self.address, err));
+            }
+            self.stream = retry(&self.address, self.timeout)?;
+            self.stream
+                .write_all(&self.buffer)
+                .map_err(|e| with_address(&self.address, e))?;
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl Drop for TcpWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// A TCP server for reading the input from clients.
+///
+/// Each accepted connection is a separate input, so the framing starts over for each connection
+/// and the End-of-File (EOF) of a connection only ends that input. The iterator yields the reader
+/// and the address of the client for each connection.
+pub struct TcpInputs {
+    accepted: bool,
+    address: String,
+    keep_listening: bool,
+    listener: TcpListener,
+}
+
+impl TcpInputs {
+    /// Binds a listener to the address, which is a `host:port` string, such as `0.0.0.0:1234`.
+    ///
+    /// If `keep_listening` is `false`, then only one connection is accepted. If `true`, then
+    /// connections are accepted one after another until the process is stopped.
+    ///
+    /// # Errors
+    ///
+    /// An I/O error with the address in the message occurs if the listener cannot be bound.
+    pub fn bind(address: &str, keep_listening: bool) -> io::Result<TcpInputs> {
+        let listener = TcpListener::bind(address).map_err(|e| with_address(address, e))?;
+        Ok(TcpInputs {
+            accepted: false,
+            address: address.to_owned(),
+            keep_listening,
+            listener,
+        })
+    }
+}
+
+impl Iterator for TcpInputs {
+    type Item = io::Result<(BufReader<TcpStream>, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.accepted && !self.keep_listening {
+            return None;
+        }
+        self.accepted = true;
+        Some(
+            self.listener
+                .accept()
+                .map(|(stream, peer)| (BufReader::new(stream), peer.to_string()))
+                .map_err(|e| with_address(&self.address, e)),
+        )
+    }
+}
+
+/// A UDP client for writing the output to a server, where each message is a datagram.
+///
+/// The written bytes are buffered until a flush, so each message is sent as a single datagram. A
+/// message larger than the maximum size is not sent, because it would be truncated or fail, and
+/// a warning is written to stderr instead.
+pub struct UdpWriter {
+    address: String,
+    buffer: Vec<u8>,
+    max_size: usize,
+    peer: SocketAddr,
+    quiet: bool,
+    socket: UdpSocket,
+}
+
+impl UdpWriter {
+    /// Resolves the address, which is a `host:port` string, and binds a socket to an unspecified
+    /// local address for sending the datagrams. If `quiet` is `true`, then the warning for a
+    /// message that is too large is not written.
+    ///
+    /// # Errors
+    ///
+    /// An I/O error with the address in the message occurs if the address cannot be resolved or
+    /// the socket cannot be bound.
+    pub fn connect(address: &str, max_size: usize, quiet: bool) -> io::Result<UdpWriter> {
+        let peer = address
+            .to_socket_addrs()
+            .map_err(|e| with_address(address, e))?
+            .next()
+            .ok_or_else(|| {
+                with_address(
+                    address,
+                    io::Error::new(io::ErrorKind::NotFound, "No addresses were resolved"),
+                )
+            })?;
+        let local = if peer.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local).map_err(|e| with_address(address, e))?;
+        Ok(UdpWriter {
+            address: address.to_owned(),
+            buffer: Vec::new(),
+            max_size,
+            peer,
+            quiet,
+            socket,
+        })
+    }
+}
+
+impl Write for UdpWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        if self.buffer.len() > self.max_size {
+            if !self.quiet {
+                eprintln!(
+                    "Warning: A {} byte message is larger than the maximum datagram size of {} \
+                     bytes and was not sent",
+                    self.buffer.len(),
+                    self.max_size
+                );
+            }
+        } else {
+            self.socket
+                .send_to(&self.buffer, self.peer)
+                .map_err(|e| with_address(&self.address, e))?;
+        }
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl Drop for UdpWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// A UDP server for reading the input, where each datagram is a message.
+///
+/// The iterator yields the data and the address of the sender for each datagram until the process
+/// is stopped. A datagram is received into a buffer that is one byte larger than the maximum size,
+/// so a datagram that is larger than the maximum size, and may have been truncated, is always
+/// longer than the maximum size instead of looking like a complete message.
+pub struct UdpDatagrams {
+    address: String,
+    max_size: usize,
+    socket: UdpSocket,
+}
+
+impl UdpDatagrams {
+    /// Binds a socket to the address, which is a `host:port` string, such as `0.0.0.0:1234`.
+    ///
+    /// # Errors
+    ///
+    /// An I/O error with the address in the message occurs if the socket cannot be bound.
+    pub fn bind(address: &str, max_size: usize) -> io::Result<UdpDatagrams> {
+        let socket = UdpSocket::bind(address).map_err(|e| with_address(address, e))?;
+        Ok(UdpDatagrams {
+            address: address.to_owned(),
+            max_size,
+            socket,
+        })
+    }
+}
+
+impl Iterator for UdpDatagrams {
+    type Item = io::Result<(Vec<u8>, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = vec![0; self.max_size + 1];
+        Some(
+            self.socket
+                .recv_from(&mut buf)
+                .map(|(len, peer)| {
+                    buf.truncate(len);
+                    (buf, peer.to_string())
+                })
+                .map_err(|e| with_address(&self.address, e)),
+        )
+    }
+}
+
+/// The connections to a listener, where each item is a reader and the address of the client.
+pub type Connections =
+    Box<dyn Iterator<Item = io::Result<(Box<dyn BufRead + Send>, String)>> + Send>;
+
+/// A Unix domain socket server for reading the input from clients.
+///
+/// This is the same as the `TcpInputs` type, but the connections are to a socket file instead of
+/// an address. Since the peer of a Unix domain socket is usually unnamed, the path of the socket
+/// file is the address of every client. The socket file is removed when the server is dropped.
+#[cfg(unix)]
+pub struct UnixInputs {
+    accepted: bool,
+    keep_listening: bool,
+    listener: UnixListener,
+    path: String,
+}
+
+#[cfg(unix)]
+impl UnixInputs {
+    /// Binds a listener to the path of a socket file, such as `/tmp/panser.sock`.
+    ///
+    /// A stale socket file, which is left behind by a server that did not exit cleanly, is
+    /// removed first. A socket file is stale if connecting to it is refused, so the socket file of
+    /// a running server is never removed. If `mode` is `Some`, then the permissions of the
+    /// created socket file are set to the mode, such as `0o600`.
+    ///
+    /// # Errors
+    ///
+    /// An I/O error with the path in the message occurs if the path exists and is not a stale
+    /// socket file, or the listener cannot be bound.
+    pub fn bind(path: &str, keep_listening: bool, mode: Option<u32>) -> io::Result<UnixInputs> {
+        remove_stale(path).map_err(|e| with_address(path, e))?;
+        let listener = UnixListener::bind(path).map_err(|e| with_address(path, e))?;
+        if let Some(m) = mode {
+            fs::set_permissions(path, Permissions::from_mode(m))
+                .map_err(|e| with_address(path, e))?;
+        }
+        Ok(UnixInputs {
+            accepted: false,
+            keep_listening,
+            listener,
+            path: path.to_owned(),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl Iterator for UnixInputs {
+    type Item = io::Result<(BufReader<UnixStream>, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.accepted && !self.keep_listening {
+            return None;
+        }
+        self.accepted = true;
+        Some(
+            self.listener
+                .accept()
+                .map(|(stream, _)| (BufReader::new(stream), self.path.clone()))
+                .map_err(|e| with_address(&self.path, e)),
+        )
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixInputs {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Listens on the path of a Unix domain socket file for the input connections.
+///
+/// See the `UnixInputs` type for the stale socket file and the `mode`.
+///
+/// # Errors
+///
+/// An I/O error with the path in the message occurs if the listener cannot be bound.
+#[cfg(unix)]
+pub fn unix_listen(path: &str, keep_listening: bool, mode: Option<u32>) -> io::Result<Connections> {
+    let connections = UnixInputs::bind(path, keep_listening, mode)?;
+    Ok(Box::new(connections.map(|c| {
+        c.map(|(reader, name)| (Box::new(reader) as Box<dyn BufRead + Send>, name))
+    })))
+}
+
+/// Listens on the path of a Unix domain socket file for the input connections.
+///
+/// # Errors
+///
+/// An I/O error always occurs, because Unix domain sockets are not supported on this platform.
+#[cfg(not(unix))]
+pub fn unix_listen(path: &str, _: bool, _: Option<u32>) -> io::Result<Connections> {
+    Err(unsupported(path))
+}
+
+/// Connects to the path of a Unix domain socket file for writing the output.
+///
+/// # Errors
+///
+/// An I/O error with the path in the message occurs if a connection cannot be made.
+#[cfg(unix)]
+pub fn unix_connect(path: &str) -> io::Result<Box<dyn Write + Send>> {
+    let stream = UnixStream::connect(path).map_err(|e| with_address(path, e))?;
+    Ok(Box::new(stream))
+}
+
+/// Connects to the path of a Unix domain socket file for writing the output.
+///
+/// # Errors
+///
+/// An I/O error always occurs, because Unix domain sockets are not supported on this platform.
+#[cfg(not(unix))]
+pub fn unix_connect(path: &str) -> io::Result<Box<dyn Write + Send>> {
+    Err(unsupported(path))
+}
+
+/// Removes the socket file of the path if no server is listening on it.
+///
+/// Nothing is removed if the path does not exist. An error occurs if the path is not a socket
+/// file or a server is listening on it.
+#[cfg(unix)]
+fn remove_stale(path: &str) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "The path exists and is not a socket file",
+        ));
+    }
+    match UnixStream::connect(Path::new(path)) {
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "Another process is listening on the socket file",
+        )),
+        Err(ref e) if e.kind() == io::ErrorKind::ConnectionRefused => fs::remove_file(path),
+        Err(e) => Err(e),
+    }
+}
+
+/// Creates the error for a Unix domain socket on a platform without them.
+#[cfg(not(unix))]
+fn unsupported(path: &str) -> io::Error {
+    with_address(
+        path,
+        io::Error::new(
+            io::ErrorKind::Other,
+            "Unix domain sockets are not supported on this platform",
+        ),
+    )
+}
+
+/// Resolves the address and connects to the first socket address that accepts the connection.
+fn open(address: &str, timeout: Option<Duration>) -> io::Result<TcpStream> {
+    let addrs = address
+        .to_socket_addrs()
+        .map_err(|e| with_address(address, e))?
+        .collect::<Vec<SocketAddr>>();
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "No addresses were resolved");
+    for addr in addrs {
+        let stream = match timeout {
+            Some(t) => TcpStream::connect_timeout(&addr, t),
+            None => TcpStream::connect(addr),
+        };
+        match stream {
+            Ok(s) => {
+                s.set_nodelay(true)?;
+                return Ok(s);
+            }
+            Err(e) => last_err = e,
+        }
+    }
+    Err(with_address(address, last_err))
+}
+
+/// Connects to the address, retrying with an exponential backoff.
+fn retry(address: &str, timeout: Option<Duration>) -> io::Result<TcpStream> {
+    let mut delay = RECONNECT_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match open(address, timeout) {
+            Ok(s) => return Ok(s),
+            Err(e) if attempt >= RECONNECT_ATTEMPTS => return Err(e),
+            Err(_) => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Adds the address to the message of an I/O error.
+fn with_address(address: &str, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {}", address, err))
+}
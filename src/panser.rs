@@ -15,8 +15,18 @@
 // You should have received a copy of the GNU General Public License
 // along with Panser.  If not, see <http://www.gnu.org/licenses/>.
 
+use base64;
 use bincode;
+use dotenvy;
+use encoding_rs;
 use envy;
+use flexbuffers;
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use glob;
+use lz4_flex;
+use notify::{self, DebouncedEvent, RecursiveMode, Watcher};
 use rmp_serde;
 use serde_cbor;
 use serde_json;
@@ -24,529 +34,5135 @@ use serde_pickle;
 use serde_urlencoded;
 use serde_yaml;
 use toml;
+use walkdir::WalkDir;
+use zstd;
 
-use super::{Error, Framing, FromFormat, Radix, Result, ToFormat};
+use super::cbor_diag;
+use super::highlight::{self, Kind};
+use super::net::{self, TcpInputs, TcpWriter, UdpDatagrams, UdpWriter};
+use super::markdown;
+use super::resp;
+use super::smile;
+use super::transform;
+use super::tsv;
+use super::value::{self, Base64Bytes, Ext, ExtRepresentation, NonFinite, ValueOptions, ValueSeed};
+use super::hash;
+use super::{
+    Charset, Checksum, Color, Difference, Digest, Encoding, Error, Framing, FromFormat, NullPolicy,
+    Radix, RadixStyle, Result, Style, ToFormat,
+};
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Cursor, ErrorKind, Write};
+use serde::de::DeserializeSeed;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Cursor, ErrorKind, Read, Write};
+use std::iter;
+use std::mem;
+use std::env;
 use std::panic;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::{self, FromStr};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
 use std::thread;
+use std::time::{Duration, Instant};
 
-type Sender = mpsc::Sender<serde_json::Value>;
-type Receiver = mpsc::Receiver<serde_json::Value>;
+/// The ASCII Start-of-Text (STX) control character.
+const STX: u8 = 0x02;
+/// The ASCII End-of-Text (ETX) control character.
+const ETX: u8 = 0x03;
+/// The first two bytes of gzip compressed data.
+const GZIP_MAGIC: &[u8; 2] = &[0x1F, 0x8B];
+/// The first four bytes of an LZ4 compressed frame.
+const LZ4_MAGIC: &[u8; 4] = &[0x04, 0x22, 0x4D, 0x18];
+/// The first four bytes of a Zstandard compressed frame.
+const ZSTD_MAGIC: &[u8; 4] = &[0x28, 0xB5, 0x2F, 0xFD];
+/// The default compression level of Zstandard.
+const ZSTD_DEFAULT_LEVEL: i32 = 3;
+/// The number of bytes of each message written to the verbose log for the first level.
+const VERBOSE_BYTES: usize = 64;
+/// The input path that reads stdin instead of a file, like most UNIX tools.
+const STDIN_PATH: &str = "-";
+/// The delay before reading a followed input file again after the End-of-File (EOF).
+const FOLLOW_POLL: Duration = Duration::from_millis(100);
+/// The delay for combining rapid file system events into a single event for the watch mode.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+/// The delay between updates of the progress line.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+type Sender = mpsc::Sender<Message>;
+type Receiver = mpsc::Receiver<Message>;
+/// A writer that can be shared with the consumer loop, such as for the digests of the messages.
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+/// A transform of the raw bytes of a message that can be shared with the producer and consumer
+/// loops.
+type Transform = Arc<Mutex<Box<dyn Fn(Vec<u8>) -> Result<Vec<u8>> + Send>>>;
+/// The errors collected by the producer and consumer loops for the `collect_errors` option.
+type Errors = Arc<Mutex<Vec<Error>>>;
+/// An input reader with its format and name, which is a file, `stdin`, or the address of a client.
+type Input = (Box<dyn BufRead + Send>, FromFormat, String);
+
+/// A deserialized input message sent from the producer loop to the consumer loop.
+#[derive(Clone, Debug)]
+struct Message {
+    frame: FrameInfo,
+    value: serde_json::Value,
+}
+
+/// The metadata of the input frame of a message.
+#[derive(Clone, Copy, Debug, Default)]
+struct FrameInfo {
+    /// The frame number, starting at one (1), across all of the inputs.
+    index: usize,
+    /// The number of bytes of the frame data, excluding any framing.
+    length: usize,
+    /// The offset, in bytes, of the start of the frame, including any framing, within its input.
+    offset: usize,
+}
+
+/// The options for displaying the serialized output data.
+#[derive(Clone, Default)]
+struct Display {
+    annotate: bool,
+    base64_url: bool,
+    c_declaration: bool,
+    cbor_diag: bool,
+    checksum: Option<Digest>,
+    checksum_writer: Option<SharedWriter>,
+    color: bool,
+    group: Option<usize>,
+    no_padding: bool,
+    preview: bool,
+    radix: Option<RadixStyle>,
+    rust_slice: bool,
+    style: Option<Style>,
+    tee_radix: Option<RadixStyle>,
+    width: Option<usize>,
+}
+
+/// The options for the producer loop.
+#[derive(Clone, Default)]
+struct ReadOptions {
+    continue_on_error: bool,
+    encoding: Option<Encoding>,
+    errors: Option<Errors>,
+    format_header: bool,
+    hex_dump: bool,
+    quiet: bool,
+    radix: Option<Radix>,
+    transform: Option<Transform>,
+    value: ValueOptions,
+    verbose: Option<Verbose>,
+}
+
+/// The level and writer of the verbose log of each message.
+#[derive(Clone)]
+struct Verbose {
+    level: u8,
+    writer: SharedWriter,
+}
+
+/// The counts of the producer loop.
+#[derive(Clone, Debug, Default)]
+struct ReadCounts {
+    /// The number of bytes of the input messages, excluding any framing.
+    bytes: usize,
+    /// The number of messages deserialized for each input format.
+    formats: BTreeMap<String, usize>,
+    /// The number of input frames.
+    frames: usize,
+    /// The number of messages that failed to deserialize and were skipped.
+    skipped: usize,
+}
+
+/// The counts of the consumer loop.
+#[derive(Clone, Copy, Debug, Default)]
+struct WriteCounts {
+    /// The number of bytes of the serialized output messages, excluding any framing.
+    bytes: usize,
+    /// The size of the largest serialized output message.
+    max: usize,
+    /// The number of messages written.
+    messages: usize,
+    /// The size of the smallest serialized output message.
+    min: usize,
+    /// The number of messages received from the producer loop, including the messages that are
+    /// not written because of the `every` option.
+    received: usize,
+}
+
+impl WriteCounts {
+    /// Adds a serialized output message of the size, excluding any framing, to the counts.
+    ///
+    /// The size is written to stderr if `stats` is `true`.
+    fn add(&mut self, size: usize, stats: bool) {
+        if self.messages == 0 || size < self.min {
+            self.min = size;
+        }
+        if size > self.max {
+            self.max = size;
+        }
+        self.messages += 1;
+        self.bytes += size;
+        if stats {
+            eprintln!("Message {}: {} bytes", self.messages, size);
+        }
+    }
+}
+
+/// The summary of a run, which is written to stderr for the `stats` and `stats_json` options.
+#[derive(Clone, Debug, Default)]
+struct RunStats {
+    /// The elapsed time in seconds.
+    elapsed: f64,
+    /// The number of messages deserialized for each input format.
+    formats: BTreeMap<String, usize>,
+    /// The number of bytes of the input messages, excluding any framing.
+    input_bytes: usize,
+    /// The size of the largest serialized output message.
+    max: usize,
+    /// The mean size of the serialized output messages.
+    mean: f64,
+    /// The number of messages written.
+    messages: usize,
+    /// The size of the smallest serialized output message.
+    min: usize,
+    /// The number of bytes of the serialized output messages, excluding any framing.
+    output_bytes: usize,
+    /// The output format.
+    output_format: String,
+    /// The number of messages received from the producer loop.
+    received: usize,
+    /// The number of errors collected for the `collect_errors` option.
+    errors_collected: usize,
+    /// The number of messages that failed to deserialize and were skipped.
+    skipped: usize,
+}
+
+impl RunStats {
+    /// Creates the summary from the counts of the producer and consumer loops.
+    fn new(read: ReadCounts, write: WriteCounts, to: ToFormat, elapsed: Duration) -> RunStats {
+        RunStats {
+            elapsed: elapsed.as_secs_f64(),
+            errors_collected: 0,
+            formats: read.formats,
+            input_bytes: read.bytes,
+            max: write.max,
+            mean: if write.messages > 0 {
+                write.bytes as f64 / write.messages as f64
+            } else {
+                0.0
+            },
+            messages: write.messages,
+            min: write.min,
+            output_bytes: write.bytes,
+            output_format: to.to_string(),
+            received: write.received,
+            skipped: read.skipped,
+        }
+    }
+
+    /// Writes the summary as human readable lines to stderr.
+    ///
+    /// The number of messages received is only written if it can differ from the number of
+    /// messages written, i.e. `every` is `true`.
+    fn write_text(&self, every: bool) {
+        eprintln!("Messages: {}", self.messages);
+        if every {
+            eprintln!("Received: {}", self.received);
+        }
+        eprintln!("Input: {} bytes", self.input_bytes);
+        eprintln!("Output: {} bytes", self.output_bytes);
+        eprintln!("Minimum: {} bytes", self.min);
+        eprintln!("Mean: {:.2} bytes", self.mean);
+        eprintln!("Maximum: {} bytes", self.max);
+        eprintln!("Elapsed: {:.3} s", self.elapsed);
+    }
+}
+
+impl Serialize for RunStats {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("RunStats", 12)?;
+        state.serialize_field("messages", &self.messages)?;
+        state.serialize_field("received", &self.received)?;
+        state.serialize_field("skipped", &self.skipped)?;
+        state.serialize_field("errors_collected", &self.errors_collected)?;
+        state.serialize_field("input_bytes", &self.input_bytes)?;
+        state.serialize_field("output_bytes", &self.output_bytes)?;
+        state.serialize_field("min_bytes", &self.min)?;
+        state.serialize_field("mean_bytes", &self.mean)?;
+        state.serialize_field("max_bytes", &self.max)?;
+        state.serialize_field("elapsed_seconds", &self.elapsed)?;
+        state.serialize_field("input_formats", &self.formats)?;
+        state.serialize_field("output_format", &self.output_format)?;
+        state.end()
+    }
+}
+
+/// The options for the consumer loop.
+#[derive(Clone, Default)]
+struct WriteOptions {
+    allow_nan: bool,
+    ascii_only: bool,
+    base64_to_bytes: bool,
+    bom: bool,
+    charset: Option<Charset>,
+    coerce_numbers: bool,
+    coerce_scalars: bool,
+    errors: Option<Errors>,
+    escape_html: bool,
+    every: usize,
+    force_float: bool,
+    indent: Option<Vec<u8>>,
+    jobs: usize,
+    line_numbers: bool,
+    newline: bool,
+    normalize_numbers: bool,
+    null_policy: Option<NullPolicy>,
+    output_newline: bool,
+    pointer: Option<String>,
+    prefix: Vec<u8>,
+    restore_ext: bool,
+    stats: bool,
+    stop_after: Option<usize>,
+    suffix: Vec<u8>,
+    transform: Option<Transform>,
+    trim_newline: bool,
+    verbose: Option<Verbose>,
+}
 
 /// A Builder for transcoding.
+#[derive(Clone)]
 pub struct Panser {
+    allow_nan: bool,
+    also_to: Vec<ToFormat>,
+    annotate: bool,
+    append: bool,
+    ascii_only: bool,
+    atomic: bool,
+    base64_to_bytes: bool,
+    base64_url: bool,
+    bytes_as_base64: bool,
+    c_declaration: bool,
+    cbor_diag: bool,
+    check: bool,
+    checksum: Option<Digest>,
+    checksum_writer: Option<SharedWriter>,
+    coerce_numbers: bool,
+    coerce_scalars: bool,
+    collected_errors: Option<Errors>,
+    color: Option<Color>,
+    connect: Option<String>,
+    connect_timeout: Option<u64>,
+    continue_on_error: bool,
+    default_from: Option<FromFormat>,
+    default_to: Option<ToFormat>,
     delimited_input: Option<String>,
     delimited_output: Option<String>,
+    diff: bool,
+    diff_against: Option<String>,
+    escape_html: bool,
+    eval: Option<Vec<String>>,
+    every: usize,
+    follow: bool,
+    force_float: bool,
     from: Option<FromFormat>,
+    from_base16_file: bool,
+    from_format_header: bool,
+    group: Option<usize>,
+    gzip_input: bool,
+    gzip_output: Option<u32>,
+    in_place: bool,
+    input_encoding: Option<Encoding>,
+    input_transform: Option<Transform>,
     inputs: Option<Vec<String>>,
+    jobs: Option<usize>,
+    keep_listening: bool,
+    line_numbers: bool,
+    list_formats: bool,
+    listen: Option<String>,
+    lower: bool,
+    lz4_input: bool,
+    lz4_output: bool,
+    msgpack_ext_as_hex: bool,
+    msgpack_restore_ext: bool,
+    nan_as_null: bool,
+    newline: bool,
+    no_padding: bool,
+    normalize_numbers: bool,
+    null_policy: Option<NullPolicy>,
+    null_to_default: bool,
     output: Option<String>,
+    output_bom: bool,
+    output_charset: Option<Charset>,
+    output_dir: Option<String>,
+    output_newline: bool,
+    output_prefix: Option<String>,
+    output_suffix: Option<String>,
+    output_transform: Option<Transform>,
+    pad: bool,
+    pointer: Option<String>,
+    prefix: bool,
+    preserve_ext: bool,
+    pretty_indent: Option<usize>,
+    pretty_tab: bool,
+    preview: bool,
+    progress: bool,
+    quiet: bool,
     radix: Option<Radix>,
+    radix_input: Option<Radix>,
+    reconnect: bool,
+    recursive: bool,
+    rename_ext: bool,
+    rust_slice: bool,
+    silent: bool,
     sized_input: bool,
     sized_output: bool,
+    stats: bool,
+    stats_json: bool,
+    stop_after: Option<usize>,
+    stringify_keys: bool,
+    stxetx_checksum: Option<Checksum>,
+    stxetx_input: bool,
+    stxetx_output: bool,
+    style: Option<Style>,
+    tee_radix: Option<Radix>,
     to: Option<ToFormat>,
+    trailing_newline: bool,
+    udp_listen: Option<String>,
+    udp_max_size: Option<usize>,
+    udp_send: Option<String>,
+    unix_connect: Option<String>,
+    unix_listen: Option<String>,
+    unix_mode: Option<u32>,
+    verbose_writer: Option<SharedWriter>,
+    verbosity: u8,
+    watch: bool,
+    width: Option<usize>,
+    zstd_input: bool,
+    zstd_output: Option<i32>,
 }
 
 impl Panser {
     /// Creates a new `Panser` with default options.
     ///
     /// The defaults are stdin for input, stdout for output, JSON for the from format, Msgpack for
-    /// the to format, no delimited input, no delimited output, no sized input, and no sized
-    /// output. The `Panser` struct is implemented following the Builder pattern. Methods can be
-    /// chained to change the defaults.
+    /// the to format, no delimited input, no delimited output, no sized input, no sized output,
+    /// no STX/ETX input, and no STX/ETX output. The `Panser` struct is implemented following the
+    /// Builder pattern. Methods can be chained to change the defaults.
     pub fn new() -> Panser {
         Panser {
+            allow_nan: false,
+            also_to: Vec::new(),
+            annotate: false,
+            append: false,
+            ascii_only: false,
+            atomic: false,
+            base64_to_bytes: false,
+            base64_url: false,
+            bytes_as_base64: false,
+            c_declaration: false,
+            cbor_diag: false,
+            check: false,
+            checksum: None,
+            checksum_writer: None,
+            coerce_numbers: false,
+            coerce_scalars: false,
+            collected_errors: None,
+            color: None,
+            connect: None,
+            connect_timeout: None,
+            continue_on_error: false,
+            default_from: None,
+            default_to: None,
             delimited_input: None,
             delimited_output: None,
+            diff: false,
+            diff_against: None,
+            escape_html: false,
+            eval: None,
+            every: 1,
+            follow: false,
+            force_float: false,
             from: None,
+            from_base16_file: false,
+            from_format_header: false,
+            group: None,
+            gzip_input: false,
+            gzip_output: None,
+            in_place: false,
+            input_encoding: None,
+            input_transform: None,
             inputs: None,
+            jobs: None,
+            keep_listening: false,
+            line_numbers: false,
+            list_formats: false,
+            listen: None,
+            lower: false,
+            lz4_input: false,
+            lz4_output: false,
+            msgpack_ext_as_hex: false,
+            msgpack_restore_ext: false,
+            nan_as_null: false,
+            newline: false,
+            no_padding: false,
+            normalize_numbers: false,
+            null_policy: None,
+            null_to_default: false,
             output: None,
+            output_bom: false,
+            output_charset: None,
+            output_dir: None,
+            output_newline: false,
+            output_prefix: None,
+            output_suffix: None,
+            output_transform: None,
+            pad: false,
+            pointer: None,
+            prefix: false,
+            preserve_ext: false,
+            pretty_indent: None,
+            pretty_tab: false,
+            preview: false,
+            progress: false,
+            quiet: false,
             radix: None,
+            radix_input: None,
+            reconnect: false,
+            recursive: false,
+            rename_ext: false,
+            rust_slice: false,
+            silent: false,
             sized_input: false,
             sized_output: false,
+            stats: false,
+            stats_json: false,
+            stop_after: None,
+            stringify_keys: false,
+            stxetx_checksum: None,
+            stxetx_input: false,
+            stxetx_output: false,
+            style: None,
+            tee_radix: None,
             to: None,
+            trailing_newline: true,
+            udp_listen: None,
+            udp_max_size: None,
+            udp_send: None,
+            unix_connect: None,
+            unix_listen: None,
+            unix_mode: None,
+            verbose_writer: None,
+            verbosity: 0,
+            watch: false,
+            width: None,
+            zstd_input: false,
+            zstd_output: None,
         }
     }
 
-    /// Sets a delimiter byte for the input and changes to framed reading of the data.
+    /// Keeps non-finite floats, i.e. NaN and infinity, and writes them as the non-standard `NaN`,
+    /// `Infinity`, and `-Infinity` literals for JSON output.
     ///
-    /// Data is read from the input source to the next delimiter byte. When the delimiter byte is
-    /// reached, then all bytes up to the delimiter byte are transcoded. This continues until the
-    /// End-of-File (EOF) is reached.
-    pub fn delimited_input(mut self, delimited: Option<&str>) -> Self {
-        self.delimited_input = delimited.map(|d| d.to_owned());
+    /// JSON does not allow non-finite floats, but formats like CBOR, MessagePack, TOML, and YAML
+    /// do. If `false`, which is the default, then non-finite floats are replaced with null. If
+    /// `true`, then non-finite floats are written as literals for JSON and Hjson output, which
+    /// matches Python's `json.dumps` with `allow_nan=True`, and as floats for all other output
+    /// formats. This is ignored if the `nan_as_null` option is `true`.
+    pub fn allow_nan(mut self, allow: bool) -> Self {
+        self.allow_nan = allow;
         self
     }
 
-    /// Sets a delimiter byte for the output.
+    /// Writes the same messages to more output formats alongside the `to` format.
     ///
-    /// The delimiter byte is appended to the output data.
-    pub fn delimited_output(mut self, delimited: Option<&str>) -> Self {
-        self.delimited_output = delimited.map(|d| d.to_owned());
+    /// If empty, which is the default, then only the `to` format is written. Otherwise, the
+    /// `output` file is a stem and each format is written to a separate file with the stem and the
+    /// file extension of the format, so an `out` output with the JSON and YAML formats is written
+    /// to `out.json` and `out.yaml`. The input is only read once and each message is sent to a
+    /// separate consumer loop for each format. The framing and display options are applied to all
+    /// of the formats, but the `stats` and `stop_after` options only count the `to` format. With
+    /// an `output_dir`, each input file is written to a file for each format in the directory.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if there is no `output` file or `output_dir`, since the formats
+    /// cannot all be written to stdout, or the `check` or `diff_against` options are used.
+    pub fn also_to(mut self, formats: Vec<ToFormat>) -> Self {
+        self.also_to = formats;
         self
     }
 
-    /// The format of the input.
+    /// Writes a comment-style header line before each output message with the metadata of its
+    /// input frame.
     ///
-    /// If `None`, which is the default, then the input format is assumed to be JSON.
-    pub fn from(mut self, from: Option<FromFormat>) -> Self {
-        self.from = from;
+    /// The header has the frame number, starting at one (1), the size of the frame data, and the
+    /// offset of the start of the frame within its input, such as
+    /// `# frame 17, 203 bytes, offset 0x1A40`. This is useful for matching the output of a message
+    /// with its frame in a large stream. This is ignored unless the `radix` or `style` option is
+    /// used.
+    pub fn annotate(mut self, annotate: bool) -> Self {
+        self.annotate = annotate;
         self
     }
 
-    /// The input source.
+    /// Appends the output to the end of the `output` file instead of truncating it.
     ///
-    /// If `None`, which is the default, then stdin is used as the source. The value is a path to
-    /// a file.
-    pub fn inputs(mut self, inputs: Option<Vec<&str>>) -> Self {
-        self.inputs = inputs.map(|i| i.iter().map(|f| String::from(*f)).collect::<Vec<String>>());
+    /// The file is created if it does not exist. This is ignored unless an `output` file is used.
+    /// This is useful for accumulating a framed stream across runs. A warning is written to stderr
+    /// if a text output format is appended without output framing, since the file may not parse
+    /// as a single document.
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
         self
     }
 
-    /// The output destination.
+    /// Escapes all non-ASCII characters in JSON and Hjson output.
     ///
-    /// If `None`, which is the default, then stdout is used as the destination. The value is
-    /// a path to a file.
-    pub fn output(mut self, output: Option<&str>) -> Self {
-        self.output = output.map(|o| o.to_owned());
+    /// Every character above U+007F is written as a `\uXXXX` escape sequence, where a character
+    /// outside the Basic Multilingual Plane is written as a surrogate pair, like Python's
+    /// `json.dumps` with `ensure_ascii=True`. The output is the same value, but it is only ASCII
+    /// for systems that cannot handle Unicode. This is ignored with a warning for all other output
+    /// formats.
+    pub fn ascii_only(mut self, ascii: bool) -> Self {
+        self.ascii_only = ascii;
         self
     }
 
-    /// Sets the written output to be a space-separated list of bytes represented as numeric
-    /// strings with a specific radix, or notation.
+    /// Writes the `output` file atomically, so a crash never leaves a partial file.
     ///
-    /// The data is still transcoded to the `to` format, but it is written to the output as
-    /// a string. This is useful for debugging and creating an interactive console where humans are
-    /// reading the serialized output.
-    pub fn radix(mut self, radix: Option<Radix>) -> Self {
-        self.radix = radix;
+    /// If `false`, which is the default, then the output is written directly to the `output`
+    /// file. If `true`, then the output is written to a temporary file with the `.tmp` extension
+    /// added, like `data.json.tmp`, which is synced to the disk and renamed to the `output` file
+    /// only after all of the messages are written successfully. On Unix-like platforms, the
+    /// directory is synced after the rename, too, so the rename is durable. If the run fails, then the
+    /// temporary file is removed and the `output` file is not changed. The output format and
+    /// compression are still determined from the `output` file name.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if there is no `output` file, or the `append` or `also_to`
+    /// options are used.
+    pub fn atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
         self
     }
 
-    /// Create a producer-consumer architecture for reading and writing data.
+    /// Serializes the strings with the `base64:` prefix as byte strings.
     ///
-    /// A separate thread is created and started for reading the input until End-of-File (EOF) is
-    /// reached. If reading stdin, Ctrl+D can be used to force an EOF.
+    /// This is the reverse of the `bytes_as_base64` option, so a `"base64:AQID"` string is
+    /// serialized as a byte string of `01 02 03` for CBOR and MessagePack. Formats without byte
+    /// strings, like JSON, write the bytes as an array of numbers. A string with the prefix that is
+    /// not valid base64 is written as a string. The JSON and Hjson output is still pretty with the
+    /// `pretty_indent` or `pretty_tab` options.
     ///
-    /// If input is `None`, then stdin is used for input. If output is `None`, then stdout is used
-    /// for output. If the input (from) format is `None`, then the format is determined from the file
-    /// extension if a file is provided and it has an extension. The default input format is JSON. If
-    /// the input format is not JSON and a file with an appropriate extension is _not_ used, then the
-    /// `from` parameter should not be `None`. A similar procedure is used for the output (to) format.
-    pub fn run(self) -> Result<()> {
-        let (tx, rx) = mpsc::channel::<serde_json::Value>();
-        // Use `BufRead` instead of `Read` to add additional reading methods, like `read_until`. The
-        // `Send` trait is needed to move the reader to the read thread.
-        let readers: Vec<Box<dyn BufRead + Send>> = {
-            if let Some(i) = self.inputs.as_ref() {
-                // There has to be a way to do this with map and collect.
-                let mut files: Vec<Box<dyn BufRead + Send>> = Vec::new();
-                for f in i {
-                    files.push(Box::new(BufReader::new(File::open(f)?)));
-                }
-                files
-            } else {
-                vec![Box::new(BufReader::new(io::stdin()))]
-            }
-        };
-        let writer: Box<dyn Write> = {
-            if let Some(o) = self.output.as_ref() {
-                Box::new(File::create(o)?)
-            } else {
-                Box::new(io::stdout())
-            }
-        };
-        let froms = {
-            if let Some(files) = self.inputs.as_ref() {
-                files
-                    .iter()
-                    .map(|f| {
-                        self.from.unwrap_or({
-                            if let Some(e) = Path::new(f).extension() {
-                                FromFormat::from_str(e.to_str().unwrap_or("json"))
-                                    .unwrap_or(FromFormat::Json)
-                            } else {
-                                FromFormat::Json
-                            }
-                        })
-                    })
-                    .collect()
-            } else {
-                vec![self.from.unwrap_or(FromFormat::Json)]
-            }
-        };
-        let to = self.to.unwrap_or({
-            if let Some(o) = self.output.as_ref() {
-                if let Some(e) = Path::new(o).extension() {
-                    ToFormat::from_str(e.to_str().unwrap_or("msgpack")).unwrap_or(ToFormat::Msgpack)
-                } else {
-                    ToFormat::Msgpack
-                }
-            } else {
-                ToFormat::Msgpack
-            }
-        });
-        let input_framing = self.delimited_input.as_ref().map_or_else(
-            || {
-                if self.sized_input {
-                    Ok(Some(Framing::Sized))
-                } else {
-                    Ok(None)
-                }
-            },
-            to_framing_delimited,
-        )?;
-        let output_framing = self.delimited_output.as_ref().map_or_else(
-            || {
-                if self.sized_output {
-                    Ok(Some(Framing::Sized))
-                } else {
-                    Ok(None)
-                }
-            },
-            to_framing_delimited,
-        )?;
-        // Set the panic hook to do nothing. This suppresses the
-        //
-        // >thread '<unnamed>' panicked at 'Box<Any>', src/panser.rs:223
-        // >note: Run with `RUST_BACKTRACE=1` for a backtrace."
-        //
-        // message when an error occurs. The read thread panics on an error, but it is not really
-        // a panic. This ensure the application exits in a clean fashion everytime and with the
-        // appropriate error code without having to implement redundant error message printing and
-        // exiting. This probably shold be changed in the future to user a verbose flag to re-enable
-        // the full panic message when debugging.
-        panic::set_hook(Box::new(|_| {}));
-        let handle = thread::spawn(move || {
-            for r in readers.into_iter().zip(froms) {
-                let (reader, from) = r;
-                let result = read(reader, from, input_framing, &tx).or_else(|e| match e {
-                    Error::Eof => Ok(()),
-                    _ => Err(e),
-                });
-                // Do not use `unwrap` for the read function result. Using `unwrap` yields an
-                // `Unknown error: Any` message, which is not very useful. The `unwrap` method for
-                // a Result creates a custom string from the Error value, it does not pass the actual
-                // Error value. The type of error is lost, so when the panic occurs within this
-                // thread and it is "caught" by the `join` method later, the unwrapped panic
-                // message appears as a string and fails to be cast to the Error type. This yields
-                // a default Generic Error that is printed to stderr as "Unknown error: Any". The
-                // workaround to print more useful error message on an error from the read thread
-                // without having to write redundant error handling and keep the error type
-                // information is to use the panic! macro and pass the Error value directly to the
-                // Result of the `join` message. A Generic Error is still created with the `From`
-                // trait, but the Error type can be determined and a more appropriate error message
-                // with more useful information can be created.
-                //
-                // There is probably a better way to do all of this, but I have not found it yet.
-                match result {
-                    Ok(_) => {}
-                    Err(e) => panic!("{}", e),
-                }
-            }
-        });
-        write(writer, to, output_framing, self.radix, rx)?;
-        handle.join()?;
-        Ok(())
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if the `allow_nan` or `msgpack_restore_ext` options are also
+    /// used, since the byte strings cannot be combined with the non-finite floats or the extension
+    /// types.
+    pub fn base64_to_bytes(mut self, to_bytes: bool) -> Self {
+        self.base64_to_bytes = to_bytes;
+        self
     }
 
-    /// Indicates the first four bytes is the total data length and changes to framed reading of
-    /// the data.
+    /// Uses the URL-safe alphabet for the base64 style output.
     ///
-    /// The first four bytes are read as an unsigned 32-bit integer (u32) in Big Endian (Network
-    /// Order). Then N number of bytes are read, where N is the size converted from the first
-    /// four bytes. Once N bytes are read, all bytes up to the size are transcoded. This
-    /// continues until the End-of-File (EOF) is reached.
-    pub fn sized_input(mut self, sized: bool) -> Self {
-        self.sized_input = sized;
+    /// The URL-safe alphabet uses `-` and `_` instead of `+` and `/`. This is ignored unless the
+    /// style is `Style::Base64`.
+    pub fn base64_url(mut self, url: bool) -> Self {
+        self.base64_url = url;
         self
     }
 
-    /// Prepends the length of the data to the output.
+    /// Deserializes byte strings as base64 strings with the `base64:` prefix.
     ///
-    /// The size of the output is prepended as an unsigned 32-bit integer (u32) in Big Endian
-    /// (Network Order).
-    pub fn sized_output(mut self, sized: bool) -> Self {
-        self.sized_output = sized;
+    /// CBOR and MessagePack have byte strings, which JSON cannot represent. If `false`, which is
+    /// the default, then a byte string fails to deserialize. If `true`, then a byte string of
+    /// `01 02 03` is deserialized as the `"base64:AQID"` string, where the prefix distinguishes it
+    /// from a regular string. See the `base64_to_bytes` option for the reverse direction.
+    pub fn bytes_as_base64(mut self, as_base64: bool) -> Self {
+        self.bytes_as_base64 = as_base64;
         self
     }
 
-    /// The format of the output.
+    /// Wraps the C array style output in a declaration.
     ///
-    /// If `None`, which is the default, then the output format is assumed to be MessagePack
-    /// (Msgpack).
-    pub fn to(mut self, to: Option<ToFormat>) -> Self {
-        self.to = to;
+    /// The output is wrapped in a `static const uint8_t data[] = { ... };` declaration and
+    /// followed by a `static const size_t data_len` declaration with the number of bytes, similar
+    /// to the `xxd -i` command. This is ignored unless the style is `Style::CArray`.
+    pub fn c_declaration(mut self, declaration: bool) -> Self {
+        self.c_declaration = declaration;
         self
     }
-}
-
-/// Deserialize to a universal, arbitrary value.
-///
-/// The `serde_json::Value` type is used as a container for an arbitrary deserialized value. All
-/// formats are deserialized to a `serde_json::Value` type.
-pub fn deserialize(input: &[u8], from: FromFormat) -> Result<serde_json::Value> {
-    Ok({
-        match from {
-            FromFormat::Bincode => bincode::deserialize::<serde_json::Value>(input)?,
-            FromFormat::Cbor => serde_cbor::from_slice::<serde_json::Value>(input)?,
-            FromFormat::Envy => envy::from_env::<serde_json::Value>()?,
-            // TODO: Change to use Hjson serde library. Until the Hjson crate is updated to work
-            // with serde v0.9 or newer, the serde_json create is used. The Hjson crate currently
-            // uses serde v0.8 and causes compiler errors.
-            FromFormat::Hjson => serde_json::from_slice::<serde_json::Value>(input)?,
-            FromFormat::Json => serde_json::from_slice::<serde_json::Value>(input)?,
-            FromFormat::Msgpack => rmp_serde::from_slice::<serde_json::Value>(input)?,
-            FromFormat::Pickle => serde_pickle::from_slice::<serde_json::Value>(
-                input,
-                serde_pickle::DeOptions::default(),
-            )?,
-            FromFormat::Toml => toml::from_slice::<serde_json::Value>(input)?,
-            FromFormat::Url => serde_urlencoded::from_bytes::<serde_json::Value>(input)?,
-            FromFormat::Yaml => serde_yaml::from_slice::<serde_json::Value>(input)?,
-        }
-    })
-}
 
-/// Serialize from a universal, arbitrary value.
-///
-/// The `serde_json::Value` type is used as a container for an arbitrary value that can be
-/// serialized to any format.
-pub fn serialize(value: serde_json::Value, to: ToFormat) -> Result<Vec<u8>> {
-    Ok({
-        match to {
-            ToFormat::Bincode => bincode::serialize(&value)?,
-            ToFormat::Cbor => serde_cbor::to_vec(&value)?,
-            // TODO: Change to use Hjson serde library. Until the Hjson crate is updated to work
-            // with serde v0.9 or newer, the serde_json create is used. The Hjson crate currently
-            // uses serde v0.8 and causes compiler errors.
-            ToFormat::Hjson => serde_json::to_vec_pretty(&value)?,
-            ToFormat::Json => serde_json::to_vec(&value)?,
-            ToFormat::Msgpack => rmp_serde::to_vec(&value)?,
-            ToFormat::Pickle => serde_pickle::to_vec(&value, serde_pickle::SerOptions::default())?,
-            ToFormat::Toml => toml::to_vec(&value)?,
-            ToFormat::Url => serde_urlencoded::to_string(&value)?.into_bytes(),
-            ToFormat::Yaml => serde_yaml::to_string(&value)?.into_bytes(),
-        }
-    })
-}
+    /// Writes each message as CBOR diagnostic notation instead of the serialized output data.
+    ///
+    /// The diagnostic notation of RFC 8949 is rendered from the CBOR bytes of each message, so the
+    /// output format is always CBOR and the `to` format is ignored. Each message is written on a
+    /// separate line without the framing, prefix, or suffix, such as `{"bool": true}` or
+    /// `[1, h'ff', 1.5]`. This is a debugging aid, like the radix and styles, for seeing the
+    /// major types and encoded sizes of the CBOR data.
+    pub fn cbor_diag(mut self, diag: bool) -> Self {
+        self.cbor_diag = diag;
+        self
+    }
 
-/// Convert the input in one format to the output of another format.
-///
-/// This does allocate memory, as not all serde-based libraries support allocation-free
-/// transcoding. However, if used in a producer-consumer architecture with framing,
-/// the memory usage should be minimized.
-///
-/// # Example
-///
-/// ```rust
-/// extern crate panser;
-///
-/// use panser::{FromFormat, ToFormat};
-///
-/// fn main() {
-///     let input = "{\"bool\":true}";
-///     let output = panser::transcode(
-///         input.as_bytes(),
-///         FromFormat::Json,
-///         ToFormat::Msgpack
-///     ).unwrap();
-///     assert_eq!(output, vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
-/// }
-/// ```
-pub fn transcode(input: &[u8], from: FromFormat, to: ToFormat) -> Result<Vec<u8>> {
-    serialize(deserialize(input, from)?, to)
-}
+    /// Only checks that the inputs deserialize without writing any output.
+    ///
+    /// If `true`, then every message of each input is deserialized, but nothing is serialized or
+    /// written, not even to the `output` file. The success, or failure, of each input is written
+    /// to stderr, and an input that fails does not stop the remaining inputs from being checked.
+    /// An error is returned after all of the inputs are checked if any input fails.
+    pub fn check(mut self, check: bool) -> Self {
+        self.check = check;
+        self
+    }
 
-/// Converts a string to a delimiter byte.
-///
-/// A delimiter byte can be specified as a string using the following notation: 1010b for binary,
-/// 10d for decimal, 0Ah for hexadecimal, and 012o for octal. All four of these notations will
-/// yield the ASCII newline character for the delimiter byte. If no radix suffix (`b`, `d`, `h`, or
-/// `o`) is present at the end of the string, then hexadecimal is assumed.
-///
-/// # Errors
-///
-/// A `ParseInt` error will occur if the string cannot be converted to a u8 (byte) value.
-fn to_framing_delimited(s: &String) -> Result<Option<Framing>> {
-    let value = match s.chars().last().unwrap() {
-        'b' => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 2)?,
-        'd' => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 10)?,
-        'h' => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 16)?,
-        'o' => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 8)?,
-        _ => u8::from_str_radix(&s, 16)?,
-    };
-    Ok(Some(Framing::Delimited(value)))
-}
-
-/// Reads exact length of bytes.
-///
-/// This assumes the first four bytes of a message are the total data
-/// length encoded as an unsigned 32-bit integer in Big Endian (Network Order). Reading continues
-/// until the End-of-File (EOF) is reached.
-///
-/// Since the data is framed, the application can read messages as they as they are "streamed" into
-/// the reader without having to read the entire stream or file into memory. Messages can be
-/// transcoded as they arrive and continuous written to output.
-fn read_exact<R: BufRead>(mut reader: R, from: FromFormat, tx: &Sender) -> Result<()> {
-    loop {
-        let mut frame_length_buf = [0; 4];
-        reader
-            .read_exact(&mut frame_length_buf)
-            .map_err(|e| match e.kind() {
-                ErrorKind::UnexpectedEof => Error::Eof,
-                _ => Error::Io(e),
-            })?;
-        let mut frame_length_cursor = Cursor::new(frame_length_buf);
-        let frame_length = frame_length_cursor.read_u32::<BigEndian>()?;
-        let mut buf = vec![0; frame_length as usize];
-        reader.read_exact(&mut buf).map_err(|e| match e.kind() {
-            ErrorKind::UnexpectedEof => Error::Eof,
-            _ => Error::Io(e),
-        })?;
-        tx.send(deserialize(&buf, from)?).unwrap();
+    /// Writes the digest of each serialized output message to the checksum writer.
+    ///
+    /// If `None`, which is the default, then no digest is written. If a digest algorithm is used,
+    /// then the output is written as usual and a line with the message number and the digest in
+    /// hexadecimal, i.e. `1 4c2f4bc0`, is written to the checksum writer for each message. The
+    /// digest does not include any framing. Use the `Style::Checksum` style to write the digests
+    /// instead of the output. The digest requires the `digest` feature.
+    pub fn checksum(mut self, digest: Option<Digest>) -> Self {
+        self.checksum = digest;
+        self
     }
-}
 
-/// Reads until a delimiter is reached.
-///
-/// Reading continues until the End-of-File (EOF) is reached.
-///
-/// Since the data is framed, the application can read messages as they as they are "streamed" into
-/// the reader without having to read the entire stream or file into memory. Messages can be
-/// transcoded as they arrive and continuous written to output.
-fn read_until<R: BufRead>(
-    mut reader: R,
-    from: FromFormat,
-    delimiter: u8,
-    tx: &Sender,
-) -> Result<()> {
-    loop {
-        let mut buf = Vec::new();
-        let bytes_count = reader
-            .read_until(delimiter, &mut buf)
-            .map_err(|e| match e.kind() {
-                ErrorKind::UnexpectedEof => Error::Eof,
-                _ => Error::Io(e),
-            })?;
-        // If the `read_until` method is at the End-of-File (EOF), then it will return zero for the
-        // number of bytes read and the buffer will be unmodified. In this case, that means an
-        // empty Vec.
-        if buf.is_empty() && bytes_count == 0 {
-            break; // EOF
-        } else {
-            tx.send(deserialize(&buf, from)?).unwrap();
-        }
+    /// Sets the writer for the digests of the `checksum` option.
+    ///
+    /// If `None`, which is the default, then the digests are discarded. The lines of the digests
+    /// are written separately from the output, so the digests can be captured, or written to
+    /// stderr, while the output is written as usual.
+    pub fn checksum_writer(mut self, writer: Option<Box<dyn Write + Send>>) -> Self {
+        self.checksum_writer = writer.map(|w| Arc::new(Mutex::new(w)));
+        self
     }
-    Ok(())
-}
 
-/// The producer loop for reading (input) and writing (output) serialized data.
+    /// Converts strings that look like numbers to numbers before serialization.
+    ///
+    /// See the `transform::coerce_numbers` function for details. This is useful for input formats
+    /// where all values are strings, like URL-encoded data. The default is `false`.
+    pub fn coerce_numbers(mut self, coerce: bool) -> Self {
+        self.coerce_numbers = coerce;
+        self
+    }
+
+    /// Converts strings that look like booleans or numbers to booleans or numbers before
+    /// serialization.
+    ///
+    /// See the `transform::coerce_scalars` function for details. This is useful for input formats
+    /// where all values are strings, like URL-encoded data and dotenv files, and it includes the
+    /// conversion of the `coerce_numbers` option. The default is `false`.
+    pub fn coerce_scalars(mut self, coerce: bool) -> Self {
+        self.coerce_scalars = coerce;
+        self
+    }
+
+    /// Collects the errors of the messages that fail to transcode instead of stopping.
+    ///
+    /// If `false`, which is the default, then the errors are handled as usual. If `true`, then a
+    /// message that fails to deserialize or serialize is skipped and its error is added to a list
+    /// instead of being returned or written to stderr, like the `continue_on_error` option. The
+    /// list is shared between the producer and consumer loops and all clones of the builder, so
+    /// the errors can be taken with the `take_errors` method of a clone after the run, since the
+    /// `run` method consumes the builder. The number of collected errors is included with the
+    /// `stats` and `stats_json` options.
+    pub fn collect_errors(mut self, collect: bool) -> Self {
+        self.collected_errors = if collect {
+            Some(Arc::new(Mutex::new(Vec::new())))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Sets when to color the bytes of the radix output.
+    ///
+    /// The bytes of MessagePack and CBOR output are colored by their role, so format markers,
+    /// lengths, and payloads are distinct. The bytes of all other output formats are not colored.
+    /// If `None`, which is the default, then the bytes are never colored. See the `Color` type for
+    /// when `Color::Auto` colors the bytes. This is ignored unless the `radix` option is used.
+    pub fn color(mut self, color: Option<Color>) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets a TCP server to write the output to instead of a file or stdout.
+    ///
+    /// If `None`, which is the default, then the output is written to the `output` destination.
+    /// The value is a `host:port` string, where the host can be a name, which is resolved, or an
+    /// IP address. TCP_NODELAY is set, so each message is sent immediately. An I/O error with the
+    /// address occurs if a connection cannot be made.
+    pub fn connect(mut self, address: Option<&str>) -> Self {
+        self.connect = address.map(|a| a.to_owned());
+        self
+    }
+
+    /// Sets the number of seconds to wait for a connection to the `connect` server.
+    ///
+    /// If `None`, which is the default, then the timeout of the operating system is used. This is
+    /// ignored unless the `connect` option is used.
+    pub fn connect_timeout(mut self, seconds: Option<u64>) -> Self {
+        self.connect_timeout = seconds;
+        self
+    }
+
+    /// Skips messages that fail to deserialize instead of stopping.
+    ///
+    /// If `false`, which is the default, then the first message that fails to deserialize stops
+    /// the transcoding and the error is returned, i.e. fail fast. If `true`, then the error is
+    /// written to stderr, the message is skipped, and reading continues with the next message.
+    /// The total number of skipped messages is written to stderr at the end. This is only useful
+    /// with framed input, since unframed input is a single message.
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// The format of the input if the `from` option is not used and the format cannot be
+    /// determined from the input, such as from the file extension.
+    ///
+    /// If `None`, which is the default, then the input format is assumed to be JSON. Unlike the
+    /// `from` option, this does not replace the format from the file extension of each input
+    /// file, so it can be set from the environment.
+    pub fn default_from(mut self, from: Option<FromFormat>) -> Self {
+        self.default_from = from;
+        self
+    }
+
+    /// The format of the output if the `to` option is not used and the format cannot be
+    /// determined from the file extension of the `output` file.
+    ///
+    /// If `None`, which is the default, then the output format is assumed to be MessagePack
+    /// (Msgpack). Unlike the `to` option, this does not replace the format from the file
+    /// extension of the output file, so it can be set from the environment.
+    pub fn default_to(mut self, to: Option<ToFormat>) -> Self {
+        self.default_to = to;
+        self
+    }
+
+    /// Sets a delimiter byte for the input and changes to framed reading of the data.
+    ///
+    /// Data is read from the input source to the next delimiter byte. When the delimiter byte is
+    /// reached, then all bytes up to the delimiter byte are transcoded. This continues until the
+    /// End-of-File (EOF) is reached. The delimiter is a byte with a radix suffix, like `0Ah`, a
+    /// single ASCII character, like `,`, or one of the `\n`, `\t`, `\r`, and `\0` escape
+    /// sequences.
+    pub fn delimited_input(mut self, delimited: Option<&str>) -> Self {
+        self.delimited_input = delimited.map(|d| d.to_owned());
+        self
+    }
+
+    /// Sets a delimiter byte for the output.
+    ///
+    /// The delimiter byte is appended to the output data. The delimiter uses the same notation as
+    /// the `delimited_input` option.
+    pub fn delimited_output(mut self, delimited: Option<&str>) -> Self {
+        self.delimited_output = delimited.map(|d| d.to_owned());
+        self
+    }
+
+    /// Compares the values of two input files instead of transcoding them.
+    ///
+    /// If `false`, which is the default, then the inputs are transcoded as usual. If `true`, then
+    /// exactly two input files are deserialized, each with the `from` format or the format from its
+    /// file extension, and a line is written to stdout for each difference between the values, so
+    /// nothing is written if the values are the same. This is useful for checking a file that was
+    /// converted to another format, like YAML to TOML. See the `diff_values` function for the
+    /// differences. The input framing is not used and the entire file is a single value.
+    ///
+    /// # Errors
+    ///
+    /// A `Differences` error will occur if the values are different, and a `Generic` error will
+    /// occur if there are not two input files.
+    pub fn diff(mut self, diff: bool) -> Self {
+        self.diff = diff;
+        self
+    }
+
+    /// Compares the output with a reference file instead of writing it.
+    ///
+    /// If `None`, which is the default, then the output is written as usual. If `Some`, then the
+    /// serialized output data, including any framing and display, is compared byte by byte with
+    /// the contents of the reference file. A line with the offset, the expected byte from the
+    /// reference, and the actual byte from the output is written for each byte that differs,
+    /// where a missing byte, because one is shorter than the other, is `--`. If they are the same,
+    /// then `identical (N bytes)` is written.
+    ///
+    /// # Errors
+    ///
+    /// A `Diff` error will occur if the output differs from the reference file.
+    pub fn diff_against(mut self, reference: Option<&str>) -> Self {
+        self.diff_against = reference.map(String::from);
+        self
+    }
+
+    /// Escapes the HTML special characters in JSON and Hjson output.
+    ///
+    /// The `<`, `>`, and `&` characters are written as the `\u003C`, `\u003E`, and `\u0026`
+    /// escape sequences, so the JSON can be embedded in an HTML page, like a `<script>` element,
+    /// without the strings closing the element. The output is the same value. This is ignored
+    /// with a warning for all other output formats.
+    pub fn escape_html(mut self, escape: bool) -> Self {
+        self.escape_html = escape;
+        self
+    }
+
+    /// Sets strings to read as the input instead of stdin or files.
+    ///
+    /// If `None`, which is the default, then the `inputs` are read. If `Some`, then the bytes of
+    /// each string are read like a separate input file, so each string is a message unless
+    /// framing is used. The `from` format, or JSON, is used for all of the strings.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if the `inputs`, `listen`, `udp_listen`, or `unix_listen`
+    /// options are also used, since the strings are the only input.
+    pub fn eval(mut self, strings: Option<Vec<&str>>) -> Self {
+        self.eval = strings.map(|s| s.iter().map(|e| String::from(*e)).collect());
+        self
+    }
+
+    /// Writes only every Nth message.
+    ///
+    /// The messages are numbered from zero (0), and only the messages with a number that is
+    /// a multiple of `n` are written, so the first message is always written. The other messages
+    /// are still read and deserialized, but they are not serialized or written. This is useful for
+    /// downsampling a high-frequency stream. The default is one (1), which writes every message,
+    /// and zero (0) is the same as one (1).
+    pub fn every(mut self, n: usize) -> Self {
+        self.every = n;
+        self
+    }
+
+    /// Waits for more data at the End-of-File (EOF) of the last input file, like `tail -f`.
+    ///
+    /// If `false`, which is the default, then the inputs are read until the EOF. If `true`, then
+    /// the last input file is read again every 100 milliseconds after reaching the EOF, so the
+    /// messages appended to the file by another process are transcoded as they arrive until the
+    /// process is stopped. A partial frame at the EOF is completed once the rest of the frame is
+    /// appended, instead of being treated as the end of the input. The earlier input files are
+    /// read until the EOF as usual. The gzip and Zstandard magic bytes are not detected for the
+    /// followed file, so a compressed file needs the extension or the `gzip_input` or `zstd_input`
+    /// option.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if there is no input framing, since the entire input is a
+    /// single message without framing.
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    /// Converts integers to floats when the `normalize_numbers` option is `true`.
+    ///
+    /// See the `transform::force_float` function for details. If `false`, which is the default,
+    /// then floats without a fractional part are converted to integers instead. This is ignored
+    /// unless the `normalize_numbers` option is `true`.
+    pub fn force_float(mut self, force: bool) -> Self {
+        self.force_float = force;
+        self
+    }
+
+    /// The format of the input.
+    ///
+    /// If `None`, which is the default, then the input format is assumed to be JSON.
+    pub fn from(mut self, from: Option<FromFormat>) -> Self {
+        self.from = from;
+        self
+    }
+
+    /// Sets the input to be a hex dump that is converted to bytes before deserialization.
+    ///
+    /// If `false`, which is the default, then the input is the serialized data. If `true`, then
+    /// the input is text with the bytes as pairs of hexadecimal digits, like the output of the
+    /// `xxd` and `hexdump -C` commands or a C array of `0x81, 0xA4` bytes. Unlike the
+    /// `radix_input` option, the formatting is forgiving: the offsets and the text columns of a
+    /// dump, the `0x` prefixes, and all other characters that are not hexadecimal digits are
+    /// ignored. See the `parse_hex_dump` function for details. If framing is used, then each
+    /// message is a hex dump.
+    ///
+    /// # Errors
+    ///
+    /// An error occurs if the `radix_input` option is also used.
+    pub fn from_base16_file(mut self, base16: bool) -> Self {
+        self.from_base16_file = base16;
+        self
+    }
+
+    /// Reads the format of each message from a one byte format tag at the start of the message.
+    ///
+    /// If `false`, which is the default, then every message is deserialized with the `from`
+    /// format. If `true`, then the first byte of each frame, or the entire input if framing is not
+    /// used, is a format tag and the remaining bytes are deserialized with the format for the tag.
+    /// This allows a single stream to have messages in different formats. See the
+    /// `FromFormat::from_tag` function for the tags. A message with an unknown tag fails to
+    /// deserialize and the `from` option is ignored.
+    pub fn from_format_header(mut self, header: bool) -> Self {
+        self.from_format_header = header;
+        self
+    }
+
+    /// Sets the number of bytes per group for the radix output and the hex dump style.
+    ///
+    /// For the radix output, the bytes within a group are concatenated and zero padded, i.e.
+    /// `81A4 626F`. If `None`, which is the default, then the bytes are not grouped for the radix
+    /// output and are displayed in groups of two (2) like the `xxd` command for the hex dump style.
+    /// This is ignored unless a radix or the `Style::Hexdump` style is used.
+    pub fn group(mut self, group: Option<usize>) -> Self {
+        self.group = group;
+        self
+    }
+
+    /// Indicates the input is gzip compressed and is decompressed before it is read.
+    ///
+    /// If `false`, which is the default, then an input file is only decompressed if it has the
+    /// `.gz` extension or starts with the gzip magic bytes, `1F 8B`, and stdin is never
+    /// decompressed. If `true`, then every input file and stdin is decompressed. Concatenated gzip
+    /// members are decompressed as one input, like the `zcat` command.
+    pub fn gzip_input(mut self, gzip: bool) -> Self {
+        self.gzip_input = gzip;
+        self
+    }
+
+    /// Sets the compression level for gzip compressing the output.
+    ///
+    /// The level is from zero (0), no compression, to nine (9), the best compression. If `None`,
+    /// which is the default, then the output is only compressed if the output file has the `.gz`
+    /// extension, which uses the default level of six (6). The framing and any display options are
+    /// applied before the output is compressed. For an output directory, the `.gz` extension is
+    /// added to each output file.
+    pub fn gzip_output(mut self, level: Option<u32>) -> Self {
+        self.gzip_output = level;
+        self
+    }
+
+    /// Rewrites each input file with the output instead of writing to the `output` destination.
+    ///
+    /// If `false`, which is the default, then the input files are not changed. If `true`, then
+    /// each input file is transcoded to a temporary file in the same directory, which replaces the
+    /// input file with a rename only after the transcoding succeeds, so a file is never left half
+    /// written. The permissions of the input file are kept, and a file with the `.gz`, `.lz4`, or
+    /// `.zst` extension is compressed again. If a file fails, then it is left untouched, the error
+    /// is written to stderr, and the remaining files are still rewritten. See the `rename_ext`
+    /// option to change the file extensions.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if there are no input files, the `also_to` option is used, or
+    /// any of the files fail.
+    pub fn in_place(mut self, in_place: bool) -> Self {
+        self.in_place = in_place;
+        self
+    }
+
+    /// Sets the character encoding of the input for the text formats.
+    ///
+    /// JSON, TOML, and YAML must be UTF-8, but some files are written with another encoding, like
+    /// Latin-1 on older Windows systems. The data of each message, without the framing, is
+    /// transcoded from the encoding to UTF-8 before it is deserialized. The input for the binary
+    /// formats, like MessagePack, is never changed. If `None`, which is the default, then the
+    /// input is UTF-8. A message that starts with a UTF-16 byte order mark (BOM), `FF FE` or
+    /// `FE FF`, is always transcoded from UTF-16 with the byte order of the BOM instead.
+    ///
+    /// # Errors
+    ///
+    /// A `Utf8` error will occur if a message has a byte sequence that is not valid for the
+    /// encoding, such as an unpaired surrogate in UTF-16.
+    pub fn input_encoding(mut self, encoding: Option<Encoding>) -> Self {
+        self.input_encoding = encoding;
+        self
+    }
+
+    /// Sets a single input file and the `from` format from its file extension.
+    ///
+    /// This is the same as the `inputs` option with one file, except the format is resolved
+    /// immediately instead of when the file is read, so
+    /// `Panser::new().input_path("a.yaml").output_path("b.toml").run()` transcodes YAML to TOML
+    /// without the `from` and `to` options. A `.gz`, `.lz4`, or `.zst` extension is ignored, so
+    /// `data.yaml.gz` is YAML. The `from` format is not changed if it is already set, so an
+    /// explicit format takes precedence over the file extension regardless of the order of the
+    /// options, or if the file extension is not for an input format.
+    pub fn input_path(mut self, path: &str) -> Self {
+        self.from = self.from.or_else(|| detect_input_format(Path::new(path)));
+        self.inputs = Some(vec![String::from(path)]);
+        self
+    }
+
+    /// The input source.
+    ///
+    /// If `None`, which is the default, then stdin is used as the source. The value is a path to
+    /// a file, or `-` for stdin, so stdin can be read between files, like
+    /// `header.json - footer.json`. Since stdin has no file extension, the `from` format, or JSON,
+    /// is used for it.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if `-` is used more than once, since stdin can only be read
+    /// once.
+    pub fn inputs(mut self, inputs: Option<Vec<&str>>) -> Self {
+        self.inputs = inputs.map(|i| i.iter().map(|f| String::from(*f)).collect::<Vec<String>>());
+        self
+    }
+
+    /// Sets the number of worker threads for transcoding messages in parallel.
+    ///
+    /// If `None`, which is the default, or one (1), then the messages are transcoded one at
+    /// a time. The output is always written in the same order as the input. This is useful for
+    /// large framed streams with output formats that are slow to serialize.
+    pub fn jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Accepts connections one after another for the `listen` and `unix_listen` options.
+    ///
+    /// If `false`, which is the default, then only one connection is accepted and transcoding ends
+    /// when the client closes the connection. If `true`, then the next connection is accepted
+    /// after each connection is closed until the process is stopped. This is ignored unless the
+    /// `listen` or `unix_listen` option is used.
+    pub fn keep_listening(mut self, keep_listening: bool) -> Self {
+        self.keep_listening = keep_listening;
+        self
+    }
+
+    /// Writes the number of the input frame, starting at one (1), and a colon before each output
+    /// message.
+    ///
+    /// The number is written as ASCII text, such as `1: `, before the output prefix and the
+    /// message, so it can be matched with the message number of an error. The input frames are
+    /// numbered, so the number of a message is the same with the `every` and `continue_on_error`
+    /// options. This is meant for text output formats and the `radix` or `style` options, because
+    /// the number corrupts a binary output format. A warning is written to stderr if it is used
+    /// with a binary output format.
+    pub fn line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    /// Writes a table of the formats to stdout instead of transcoding.
+    ///
+    /// The table has a row for each format with columns for reading (deserializing) and writing
+    /// (serializing). A format that can be read only with some values, like Bincode, is listed as
+    /// limited. All other options are ignored.
+    pub fn list_formats(mut self, list_formats: bool) -> Self {
+        self.list_formats = list_formats;
+        self
+    }
+
+    /// Sets a TCP address to listen on and read the input from a client instead of the inputs.
+    ///
+    /// If `None`, which is the default, then the input is read from the inputs, or stdin. The value
+    /// is a `host:port` string, such as `0.0.0.0:1234`. A connection is read like a file with the
+    /// input framing, and the End-of-File (EOF) of the connection ends the input cleanly. The
+    /// input format is the `from` format, or JSON. An I/O error with the address occurs if the
+    /// address cannot be bound.
+    pub fn listen(mut self, address: Option<&str>) -> Self {
+        self.listen = address.map(|a| a.to_owned());
+        self
+    }
+
+    /// Uses lowercase letters for the hexadecimal digits of the radix output.
+    ///
+    /// This is ignored unless the radix is `Radix::Hexadecimal`.
+    pub fn lower(mut self, lower: bool) -> Self {
+        self.lower = lower;
+        self
+    }
+
+    /// Indicates the input is LZ4 compressed and is decompressed before it is read.
+    ///
+    /// If `false`, which is the default, then an input file is only decompressed if it has the
+    /// `.lz4` extension or starts with the LZ4 frame magic bytes, `04 22 4D 18`, and stdin is
+    /// never decompressed. If `true`, then every input file and stdin is decompressed. The framing
+    /// is read from the decompressed data, so the frames are inside the compressed stream. This
+    /// is ignored if the `gzip_input` option is `true`.
+    pub fn lz4_input(mut self, lz4: bool) -> Self {
+        self.lz4_input = lz4;
+        self
+    }
+
+    /// Compresses the output with the LZ4 frame format.
+    ///
+    /// If `false`, which is the default, then the output is only compressed if the output file
+    /// has the `.lz4` extension. If `true`, then the output is compressed. The compression wraps
+    /// the already framed bytes, i.e. the framing and any display options are applied before the
+    /// output is compressed, so the output must be decompressed before the frames are read. LZ4
+    /// is much faster than gzip and Zstandard, but it does not compress as well, which suits
+    /// streams. For an output directory, the `.lz4` extension is added to each output file. This
+    /// is ignored if the `gzip_output` or `zstd_output` options are used.
+    pub fn lz4_output(mut self, lz4: bool) -> Self {
+        self.lz4_output = lz4;
+        self
+    }
+
+    /// Deserializes MessagePack extension types to a hexadecimal representation.
+    ///
+    /// If `true`, then an extension type is deserialized as a
+    /// `{"__ext_type__": -1, "__ext_data__": "<hex>"}` object, where the data is a lowercase
+    /// hexadecimal string. See the `msgpack_restore_ext` option for the reverse direction. This
+    /// takes precedence over the `preserve_ext` option for deserialization.
+    pub fn msgpack_ext_as_hex(mut self, as_hex: bool) -> Self {
+        self.msgpack_ext_as_hex = as_hex;
+        self
+    }
+
+    /// Serializes representations of MessagePack extension types back to extension types.
+    ///
+    /// If `true` and the output format is MessagePack, then both the hexadecimal representation
+    /// from the `msgpack_ext_as_hex` option and the tagged representation from the `preserve_ext`
+    /// option are serialized as extension types. All other output formats have the
+    /// representations written as-is.
+    pub fn msgpack_restore_ext(mut self, restore: bool) -> Self {
+        self.msgpack_restore_ext = restore;
+        self
+    }
+
+    /// Replaces non-finite floats, i.e. NaN and infinity, with null.
+    ///
+    /// This is the default behavior, but it takes precedence over the `allow_nan` option.
+    pub fn nan_as_null(mut self, null: bool) -> Self {
+        self.nan_as_null = null;
+        self
+    }
+
+    /// Writes a newline after the final message of the output.
+    ///
+    /// This is independent of framing, so a newline is written once after all of the messages,
+    /// even if a newline is also used as the delimiter. This is useful for keeping textual output,
+    /// like JSON, from running into the shell prompt.
+    pub fn newline(mut self, newline: bool) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Removes the `=` padding from the base64 style output.
+    ///
+    /// This is ignored unless the style is `Style::Base64`.
+    pub fn no_padding(mut self, no_padding: bool) -> Self {
+        self.no_padding = no_padding;
+        self
+    }
+
+    /// Normalizes numbers to a single number type before serialization.
+    ///
+    /// See the `transform::normalize_numbers` function for details. If `true`, then floats
+    /// without a fractional part are converted to integers, or integers are converted to floats
+    /// if the `force_float` option is `true`. This reduces changes of number types when
+    /// transcoding between formats with different number models, like YAML and TOML. The default
+    /// is `false`.
+    pub fn normalize_numbers(mut self, normalize: bool) -> Self {
+        self.normalize_numbers = normalize;
+        self
+    }
+
+    /// Sets the policy for replacing null values before serialization.
+    ///
+    /// See the `transform::apply_null_policy` function for details. If `None`, which is the
+    /// default, then null values are serialized as-is, unless the `null_to_default` option is
+    /// `true`. This takes precedence over the `null_to_default` option.
+    pub fn null_policy(mut self, policy: Option<NullPolicy>) -> Self {
+        self.null_policy = policy;
+        self
+    }
+
+    /// Replaces null values with the default for the output format before serialization.
+    ///
+    /// See the `NullPolicy::default_for` function for the default policy of each output format.
+    /// This is useful for output formats without a null type, like TOML, which fail to serialize
+    /// a value containing a null. This is ignored if the `null_policy` option is used.
+    pub fn null_to_default(mut self, default: bool) -> Self {
+        self.null_to_default = default;
+        self
+    }
+
+    /// The output destination.
+    ///
+    /// If `None`, which is the default, then stdout is used as the destination. The value is
+    /// a path to a file.
+    pub fn output(mut self, output: Option<&str>) -> Self {
+        self.output = output.map(|o| o.to_owned());
+        self
+    }
+
+    /// Prepends the byte order mark (BOM) of the output charset to each message of the output.
+    ///
+    /// If `false`, which is the default, then no BOM is written. If `true`, then the BOM for the
+    /// `output_charset`, or UTF-8 if no charset is used, is written at the start of each
+    /// serialized message, before the output prefix. This is only valid for the text output
+    /// formats, and transcoding fails for a binary output format, like MessagePack.
+    pub fn output_bom(mut self, bom: bool) -> Self {
+        self.output_bom = bom;
+        self
+    }
+
+    /// Sets the character encoding of the output for the text formats.
+    ///
+    /// If `None`, which is the default, then the output is UTF-8. The serialized UTF-8 text of each
+    /// message is transcoded to the charset, but the framing, prefix, and suffix are written
+    /// as-is. This is only valid for the text output formats, and transcoding fails for a binary
+    /// output format, like MessagePack.
+    pub fn output_charset(mut self, charset: Option<Charset>) -> Self {
+        self.output_charset = charset;
+        self
+    }
+
+    /// Sets a directory to write the output of each input file to a separate file.
+    ///
+    /// If `None`, which is the default, then the output of all of the input files is written to
+    /// the `output` destination. If a directory is used, then each input file is transcoded to a
+    /// file in the directory with the same name and the file extension of the `to` format. The
+    /// files found in a directory input with the `recursive` option are written to the same
+    /// relative path within the output directory, so the directory structure is kept. If more than
+    /// one input file has the same output file, such as `a/data.json` and `b/data.json`, then a
+    /// numeric suffix is added to the later output files, like `data-1.msgpack`, instead of
+    /// overwriting the earlier output. The `output` option is ignored and at least one input file
+    /// is required.
+    pub fn output_dir(mut self, dir: Option<&str>) -> Self {
+        self.output_dir = dir.map(|d| d.to_owned());
+        self
+    }
+
+    /// Writes a newline after each message of the output.
+    ///
+    /// If `false`, which is the default, then the messages are written one after another. If
+    /// `true`, then a newline (`0Ah`) is written as a binary byte after each message, including
+    /// any suffix and delimiter, so binary messages are on separate lines for tools like
+    /// `hexdump`. Unlike a newline delimiter, the newline is not part of the framing and the
+    /// output cannot be read back with a newline delimiter if a message contains a newline byte.
+    pub fn output_newline(mut self, newline: bool) -> Self {
+        self.output_newline = newline;
+        self
+    }
+
+    /// Sets the output file and the `to` format from its file extension.
+    ///
+    /// This is the same as the `output` option, except the format is resolved immediately instead
+    /// of when the output is opened. A `.gz`, `.lz4`, or `.zst` extension is ignored for the
+    /// format, so `data.json.gz` is JSON, but the output is still compressed. The `to` format is
+    /// not changed if it is already set, so an explicit format takes precedence over the file
+    /// extension regardless of the order of the options, or if the file extension is not for an
+    /// output format.
+    pub fn output_path(mut self, path: &str) -> Self {
+        self.to = self.to.or_else(|| detect_output_format(Path::new(path)));
+        self.output = Some(String::from(path));
+        self
+    }
+
+    /// Sets a sequence of bytes to prepend to each message of the output.
+    ///
+    /// The bytes are a comma- or space-separated list, where each byte uses the same notation as
+    /// a delimiter byte, i.e. `02h` or `0Dh,0Ah`. Similar to the delimiter byte, the prefix is
+    /// always written as binary data, even if a radix or style is used. The prefix is written
+    /// before any framing, such as the size for sized-based framing.
+    pub fn output_prefix(mut self, prefix: Option<&str>) -> Self {
+        self.output_prefix = prefix.map(|p| p.to_owned());
+        self
+    }
+
+    /// Sets a sequence of bytes to append to each message of the output.
+    ///
+    /// The bytes use the same notation as the `output_prefix` option. Similar to the delimiter
+    /// byte, the suffix is always written as binary data, even if a radix or style is used. The
+    /// suffix is written before the delimiter byte if delimited-based framing is used, so the
+    /// delimiter still terminates each message.
+    pub fn output_suffix(mut self, suffix: Option<&str>) -> Self {
+        self.output_suffix = suffix.map(|s| s.to_owned());
+        self
+    }
+
+    /// Zero pads each byte of the radix output to the maximum number of digits for a byte.
+    ///
+    /// If `false`, which is the default, then the bytes are written without leading zeros, so
+    /// decimal bytes vary in width, i.e. `129 164 98`. If `true`, then each byte is padded to
+    /// eight digits for binary, three digits for decimal and octal, and two digits for
+    /// hexadecimal, i.e. `129 164 098`, so the bytes line up in columns. This is ignored unless a
+    /// radix is used.
+    pub fn pad(mut self, pad: bool) -> Self {
+        self.pad = pad;
+        self
+    }
+
+    /// Sets a JSON Pointer to extract a sub-value of each message before it is transcoded.
+    ///
+    /// The pointer uses the syntax of [RFC 6901], like `/servers/0/port`, where `~0` is a `~` and
+    /// `~1` is a `/` in a key, and an empty pointer is the entire value. If `None`, which is the
+    /// default, then the entire value is transcoded.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if the pointer is not empty and does not start with a `/`, or
+    /// the pointer does not resolve to a value of a message.
+    ///
+    /// [RFC 6901]: https://www.rfc-editor.org/rfc/rfc6901
+    pub fn pointer(mut self, pointer: Option<&str>) -> Self {
+        self.pointer = pointer.map(|p| p.to_owned());
+        self
+    }
+
+    /// Prepends the radix prefix to each byte of the radix output.
+    ///
+    /// The prefix is `0b` for binary, `0o` for octal, and `0x` for hexadecimal. Decimal does not
+    /// have a prefix. This is ignored unless a radix is used.
+    pub fn prefix(mut self, prefix: bool) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Keeps MessagePack extension types, like timestamps, from the input.
+    ///
+    /// If `false`, which is the default, then an extension type is deserialized as an array of the
+    /// type and the data. If `true`, then an extension type is deserialized as a tagged
+    /// representation, i.e. `{"$msgpack_ext": {"type": -1, "data": "<base64>"}}`, where the data
+    /// is base64 encoded, and the tagged representation is serialized back to an extension type
+    /// if the output format is MessagePack. All other output formats have the tagged
+    /// representation written as-is.
+    pub fn preserve_ext(mut self, preserve: bool) -> Self {
+        self.preserve_ext = preserve;
+        self
+    }
+
+    /// Sets the number of spaces for each level of indentation of pretty JSON and Hjson output.
+    ///
+    /// If `None`, which is the default, then JSON output is compact and Hjson output is indented
+    /// with two (2) spaces. If a number is used, then the JSON and Hjson output is pretty, i.e.
+    /// each value is on its own line and indented with the number of spaces for each level of
+    /// nesting. This is ignored if the `pretty_tab` option is `true` and for all other output
+    /// formats, except YAML, which is always indented with two spaces and is an error.
+    pub fn pretty_indent(mut self, indent: Option<usize>) -> Self {
+        self.pretty_indent = indent;
+        self
+    }
+
+    /// Indents pretty JSON and Hjson output with a tab for each level of indentation.
+    ///
+    /// If `false`, which is the default, then the indentation is based on the `pretty_indent`
+    /// option. If `true`, then the JSON and Hjson output is pretty and each level of nesting is
+    /// indented with a tab. This is ignored for all other output formats, except YAML, which does
+    /// not allow tabs for indentation and is an error.
+    pub fn pretty_tab(mut self, tab: bool) -> Self {
+        self.pretty_tab = tab;
+        self
+    }
+
+    /// Writes a preview line of the bytes as text after the radix output of each message.
+    ///
+    /// The bytes are rendered as lossy UTF-8 text on a second line, where invalid sequences and
+    /// control characters are replaced with a `.`, so the field names and strings within binary
+    /// data, like MessagePack, are easy to find. This is ignored unless a radix is used.
+    pub fn preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Writes a progress line with the number of input messages and bytes to stderr while
+    /// transcoding.
+    ///
+    /// If `false`, which is the default, then nothing is written. If `true`, then the line, like
+    /// `Messages: 12345, Bytes in: 1.2 MB`, is rewritten in place every 100 milliseconds by a
+    /// background thread, and a final newline is written when the transcoding ends, even if it
+    /// fails. This is useful for seeing that a large input is still being transcoded. Nothing is
+    /// written if stderr is not a terminal, so the line never ends up in a log file.
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Suppresses the diagnostics written to stderr that are not errors.
+    ///
+    /// If `false`, which is the default, then warnings, skipped messages, and the `OK` lines of
+    /// the `check` option are written to stderr. If `true`, then only the errors, such as an
+    /// input that fails with the `check` or `in_place` options, are written to stderr, and the
+    /// `progress`, `stats`, `stats_json`, and `verbosity` options are ignored, so the output can
+    /// be piped cleanly. See the `silent` option to also suppress the errors.
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Sets the written output to be a space-separated list of bytes represented as numeric
+    /// strings with a specific radix, or notation.
+    ///
+    /// The data is still transcoded to the `to` format, but it is written to the output as
+    /// a string. This is useful for debugging and creating an interactive console where humans are
+    /// reading the serialized output.
+    pub fn radix(mut self, radix: Option<Radix>) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    /// Sets the input to be a list of bytes represented as numeric strings based on the radix.
+    ///
+    /// This is the reverse of the `radix` option. The bytes are separated by whitespace or commas
+    /// and each byte can have a radix prefix, i.e. `0b`, `0o`, or `0x`. Trailing separators and
+    /// bytes without leading zeros are allowed. The bytes are converted to binary data before
+    /// deserialization with the `from` format. If framing is used, then each message is a list of
+    /// bytes, e.g. each line of the input with the newline character as the delimiter.
+    pub fn radix_input(mut self, radix: Option<Radix>) -> Self {
+        self.radix_input = radix;
+        self
+    }
+
+    /// Reconnects to the `connect` server if the connection fails.
+    ///
+    /// If `false`, which is the default, then a failed connection stops the transcoding with an
+    /// error. If `true`, then connecting is retried up to five times with a backoff that starts at
+    /// 250 milliseconds and doubles for each attempt, and a message that fails to send is sent
+    /// again after reconnecting. Each message is sent whole, so the framing is not broken by
+    /// a reconnect. This is ignored unless the `connect` option is used.
+    pub fn reconnect(mut self, reconnect: bool) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Walks any directory in the inputs recursively and reads every file with a recognized file
+    /// extension.
+    ///
+    /// If `false`, which is the default, then each input must be a file. If `true`, then the
+    /// files in a directory, and all of its subdirectories, are read in order by name, and files
+    /// without a file extension for one of the from formats, like `.yaml`, are ignored. The
+    /// format of each file is determined from its file extension unless the `from` option is
+    /// used.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Renames the file extension of each input file rewritten with the `in_place` option to the
+    /// file extension of the `to` format.
+    ///
+    /// If `false`, which is the default, then each file keeps its name. If `true`, then
+    /// `config.json` is replaced with `config.toml` for the TOML format, for example. A file fails
+    /// if a file with the new name already exists, so an unrelated file is never overwritten.
+    /// This is ignored unless the `in_place` option is used.
+    pub fn rename_ext(mut self, rename: bool) -> Self {
+        self.rename_ext = rename;
+        self
+    }
+
+    /// Writes the Rust style output as a `&[...]` slice instead of a `vec![...]` macro.
+    ///
+    /// This is ignored unless the style is `Style::Rust`.
+    pub fn rust_slice(mut self, slice: bool) -> Self {
+        self.rust_slice = slice;
+        self
+    }
+
+    /// Create a producer-consumer architecture for reading and writing data.
+    ///
+    /// A separate thread is created and started for reading the input until End-of-File (EOF) is
+    /// reached. If reading stdin, Ctrl+D can be used to force an EOF.
+    ///
+    /// If input is `None`, then stdin is used for input. If output is `None`, then stdout is used
+    /// for output. If the input (from) format is `None`, then the format is determined from the file
+    /// extension if a file is provided and it has an extension. The default input format is JSON. If
+    /// the input format is not JSON and a file with an appropriate extension is _not_ used, then the
+    /// `from` parameter should not be `None`. A similar procedure is used for the output (to) format.
+    pub fn run(mut self) -> Result<()> {
+        self.quiet |= self.silent;
+        if self.list_formats {
+            return write_formats(io::stdout());
+        }
+        let stdin_count = self
+            .inputs
+            .as_ref()
+            .map_or(0, |i| i.iter().filter(|f| *f == STDIN_PATH).count());
+        if stdin_count > 1 {
+            return Err(Error::Generic(format!(
+                "The '{}' input for stdin can only be used once, but it is used {} times",
+                STDIN_PATH, stdin_count
+            )));
+        }
+        if self.diff {
+            return self.run_diff();
+        }
+        if self.watch {
+            return self.run_watch();
+        }
+        if let Some(dir) = self.output_dir.take() {
+            return self.run_output_dir(&dir);
+        }
+        if self.in_place {
+            return self.run_in_place();
+        }
+        if self.atomic {
+            return self.run_atomic();
+        }
+        if let Some(pointer) = self.pointer.as_ref() {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return Err(Error::Generic(format!(
+                    "The JSON Pointer '{}' must be empty or start with a '/'",
+                    pointer
+                )));
+            }
+        }
+        if self.from_base16_file && self.radix_input.is_some() {
+            return Err(Error::Generic(String::from(
+                "A hex dump input cannot be used with a radix input",
+            )));
+        }
+        if self.stxetx_checksum.is_some() && !self.stxetx_input && !self.stxetx_output {
+            return Err(Error::Generic(String::from(
+                "The STX/ETX checksum requires STX/ETX framing for the input or output",
+            )));
+        }
+        let listening =
+            self.listen.is_some() || self.udp_listen.is_some() || self.unix_listen.is_some();
+        if self.eval.is_some() && (self.inputs.is_some() || listening) {
+            return Err(Error::Generic(String::from(
+                "The eval strings cannot be used with input files or a listening socket",
+            )));
+        }
+        let mut also = Vec::with_capacity(self.also_to.len());
+        if !self.also_to.is_empty() {
+            if self.check || self.diff_against.is_some() {
+                return Err(Error::Generic(String::from(
+                    "More than one output format cannot be used with the check or diff against \
+                     options",
+                )));
+            }
+            let stem = self.output.take().ok_or_else(|| {
+                Error::Generic(String::from(
+                    "More than one output format requires an output file stem, like 'out' for \
+                     'out.json' and 'out.yaml', since the formats cannot all be written to stdout",
+                ))
+            })?;
+            let to = self.to.unwrap_or_else(|| self.fallback_to());
+            self.to = Some(to);
+            self.output = Some(format!("{}.{}", stem, self.output_extension(to)));
+            for &format in &self.also_to {
+                also.push((format, format!("{}.{}", stem, self.output_extension(format))));
+            }
+        }
+        let framed = self.delimited_input.is_some() || self.sized_input || self.stxetx_input;
+        if self.follow && !framed {
+            return Err(Error::Generic(String::from(
+                "An input file can only be followed with input framing, since the entire input is \
+                 a single message without framing",
+            )));
+        }
+        if self.inputs.is_some() {
+            self.inputs = Some(
+                self.input_files()?
+                    .into_iter()
+                    .map(|(file, _)| file)
+                    .collect(),
+            );
+            self.recursive = false;
+        }
+        let start = Instant::now();
+        let (tx, rx) = mpsc::channel::<Message>();
+        let udp_max_size = self.udp_max_size.unwrap_or(net::MAX_DATAGRAM_SIZE);
+        let datagrams = match self.udp_listen.as_ref() {
+            Some(address) => Some(UdpDatagrams::bind(address, udp_max_size)?),
+            None => None,
+        };
+        // Each connection to a listener is a separate input, like a file, so the connections are
+        // accepted by the read thread as they are needed. The inputs are not opened when listening.
+        let inputs: Box<dyn Iterator<Item = io::Result<Input>> + Send> =
+            if datagrams.is_some() {
+                Box::new(iter::empty())
+            } else if let Some(strings) = self.eval.take() {
+                let from = self.from.unwrap_or_else(|| self.fallback_from());
+                Box::new(strings.into_iter().enumerate().map(move |(n, s)| {
+                    let reader = Box::new(Cursor::new(s.into_bytes())) as Box<dyn BufRead + Send>;
+                    Ok((reader, from, format!("eval {}", n + 1)))
+                }))
+            } else if let Some(path) = self.unix_listen.as_ref() {
+                let from = self.from.unwrap_or_else(|| self.fallback_from());
+                let connections = net::unix_listen(path, self.keep_listening, self.unix_mode)?;
+                Box::new(connections.map(move |c| c.map(|(reader, name)| (reader, from, name))))
+            } else if let Some(address) = self.listen.as_ref() {
+                let from = self.from.unwrap_or_else(|| self.fallback_from());
+                let connections = TcpInputs::bind(address, self.keep_listening)?;
+                Box::new(connections.map(move |c| {
+                    c.map(|(reader, name)| {
+                        (Box::new(reader) as Box<dyn BufRead + Send>, from, name)
+                    })
+                }))
+            } else {
+                // Use `BufRead` instead of `Read` to add additional reading methods, like
+                // `read_until`. The `Send` trait is needed to move the reader to the read thread.
+                // The format from the `Content-Type` header of each URL input is kept for
+                // determining the input format.
+                let mut url_formats = Vec::new();
+                let readers: Vec<Box<dyn BufRead + Send>> = {
+                    if let Some(i) = self.inputs.as_ref() {
+                        // There has to be a way to do this with map and collect.
+                        let mut files: Vec<Box<dyn BufRead + Send>> = Vec::new();
+                        let (gzip, lz4, zstd) = (self.gzip_input, self.lz4_input, self.zstd_input);
+                        for (n, f) in i.iter().enumerate() {
+                            if net::is_url(f) {
+                                let (body, from) = net::http_get(f)?;
+                                let path = Path::new(net::url_path(f));
+                                let body = BufReader::new(body);
+                                files.push(decompress(body, path, gzip, lz4, zstd, true)?);
+                                url_formats.push(from);
+                                continue;
+                            }
+                            let path = Path::new(f);
+                            let follow = self.follow && n + 1 == i.len();
+                            files.push(open_input(path, gzip, lz4, zstd, follow)?);
+                            url_formats.push(None);
+                        }
+                        files
+                    } else if self.gzip_input {
+                        let stdin = BufReader::new(io::stdin());
+                        vec![Box::new(BufReader::new(MultiGzDecoder::new(stdin)))]
+                    } else if self.zstd_input {
+                        let stdin = BufReader::new(io::stdin());
+                        vec![Box::new(BufReader::new(zstd::Decoder::with_buffer(stdin)?))]
+                    } else if self.lz4_input {
+                        let stdin = BufReader::new(io::stdin());
+                        vec![Box::new(BufReader::new(lz4_flex::frame::FrameDecoder::new(stdin)))]
+                    } else {
+                        vec![Box::new(BufReader::new(io::stdin()))]
+                    }
+                };
+                let names = self.inputs.as_ref().map_or_else(
+                    || vec![String::from("stdin")],
+                    |i| {
+                        i.iter()
+                            .map(|f| if f == STDIN_PATH { "stdin" } else { f })
+                            .map(String::from)
+                            .collect()
+                    },
+                );
+                let froms = {
+                    if let Some(files) = self.inputs.as_ref() {
+                        files
+                            .iter()
+                            .zip(url_formats)
+                            .map(|(f, content_type)| {
+                                let path = Path::new(net::url_path(f));
+                                self.from.unwrap_or_else(|| {
+                                    content_type
+                                        .or_else(|| detect_input_format(path))
+                                        .unwrap_or_else(|| self.fallback_from())
+                                })
+                            })
+                            .collect()
+                    } else {
+                        vec![self.from.unwrap_or_else(|| self.fallback_from())]
+                    }
+                };
+                Box::new(
+                    readers
+                        .into_iter()
+                        .zip(froms)
+                        .zip(names)
+                        .map(|((reader, from), name)| Ok((reader, from, name))),
+                )
+            };
+        let writer: Box<dyn Write + Send> = {
+            if self.check {
+                Box::new(io::sink())
+            } else if let Some(o) = self.output.as_ref() {
+                if self.append {
+                    Box::new(OpenOptions::new().append(true).create(true).open(o)?)
+                } else {
+                    Box::new(File::create(o)?)
+                }
+            } else if let Some(address) = self.connect.as_ref() {
+                let timeout = self.connect_timeout.map(Duration::from_secs);
+                Box::new(TcpWriter::connect(address, timeout, self.reconnect)?)
+            } else if let Some(address) = self.udp_send.as_ref() {
+                Box::new(UdpWriter::connect(address, udp_max_size, self.quiet)?)
+            } else if let Some(path) = self.unix_connect.as_ref() {
+                net::unix_connect(path)?
+            } else {
+                Box::new(io::stdout())
+            }
+        };
+        let gzip_output = self.gzip_output.or_else(|| {
+            self.output
+                .as_ref()
+                .filter(|o| is_gzip(Path::new(o)))
+                .map(|_| Compression::default().level())
+        });
+        let zstd_output = self.zstd_output.or_else(|| {
+            self.output
+                .as_ref()
+                .filter(|o| is_zstd(Path::new(o)))
+                .map(|_| ZSTD_DEFAULT_LEVEL)
+        });
+        let lz4_output =
+            self.lz4_output || self.output.as_ref().map_or(false, |o| is_lz4(Path::new(o)));
+        let compressed = gzip_output.is_some() || lz4_output || zstd_output.is_some();
+        let writer = compress_output(writer, gzip_output, lz4_output, zstd_output)?;
+        let to = self.to.unwrap_or_else(|| {
+            self.output
+                .as_ref()
+                .and_then(|o| detect_output_format(Path::new(o)))
+                .unwrap_or_else(|| self.fallback_to())
+        });
+        let to = if self.cbor_diag { ToFormat::Cbor } else { to };
+        self.check_output_format(to)?;
+        for &(format, _) in &also {
+            self.check_output_format(format)?;
+        }
+        let input_framing = self.delimited_input.as_ref().map_or_else(
+            || {
+                if self.sized_input {
+                    Ok(Some(Framing::Sized))
+                } else if self.stxetx_input {
+                    Ok(Some(Framing::StxEtx(self.stxetx_checksum)))
+                } else {
+                    Ok(None)
+                }
+            },
+            to_framing_delimited,
+        )?;
+        let output_framing = self.delimited_output.as_ref().map_or_else(
+            || {
+                if self.sized_output {
+                    Ok(Some(Framing::Sized))
+                } else if self.stxetx_output {
+                    Ok(Some(Framing::StxEtx(self.stxetx_checksum)))
+                } else {
+                    Ok(None)
+                }
+            },
+            to_framing_delimited,
+        )?;
+        let text_append = self.append && self.output.is_some() && output_framing.is_none();
+        if text_append && is_text(to) && !self.quiet {
+            eprintln!(
+                "Warning: The {} output is appended without framing, so the output file may not \
+                 parse as a single document. Use the '--delimited-output' option to separate \
+                 the messages.",
+                to
+            );
+        }
+        // Set the panic hook to do nothing. This suppresses the
+        //
+        // >thread '<unnamed>' panicked at 'Box<Any>', src/panser.rs:223
+        // >note: Run with `RUST_BACKTRACE=1` for a backtrace."
+        //
+        // message when an error occurs. The read thread panics on an error, but it is not really
+        // a panic. This ensure the application exits in a clean fashion everytime and with the
+        // appropriate error code without having to implement redundant error message printing and
+        // exiting. This probably shold be changed in the future to user a verbose flag to re-enable
+        // the full panic message when debugging.
+        panic::set_hook(Box::new(|_| {}));
+        let allow_nan = self.allow_nan && !self.nan_as_null;
+        let verbose = match self.verbose_writer.clone() {
+            Some(writer) => Some(writer),
+            None if atty::is(atty::Stream::Stderr) => {
+                Some(Arc::new(Mutex::new(Box::new(io::stderr()) as Box<dyn Write + Send>)))
+            }
+            None => None,
+        }
+        .filter(|_| self.verbosity > 0 && !self.quiet)
+        .map(|writer| Verbose {
+            level: self.verbosity,
+            writer,
+        });
+        let read_options = ReadOptions {
+            continue_on_error: self.continue_on_error,
+            encoding: self.input_encoding,
+            errors: self.collected_errors.clone(),
+            format_header: self.from_format_header,
+            hex_dump: self.from_base16_file,
+            quiet: self.quiet,
+            radix: self.radix_input,
+            transform: self.input_transform.clone(),
+            value: ValueOptions {
+                allow_nan,
+                bytes_as_base64: self.bytes_as_base64,
+                ext: if self.msgpack_ext_as_hex {
+                    Some(ExtRepresentation::Hex)
+                } else if self.preserve_ext {
+                    Some(ExtRepresentation::Tagged)
+                } else {
+                    None
+                },
+                stringify_keys: self.stringify_keys,
+            },
+            verbose: verbose.clone(),
+        };
+        let write_options = WriteOptions {
+            allow_nan,
+            ascii_only: self.ascii_only,
+            base64_to_bytes: self.base64_to_bytes,
+            bom: self.output_bom,
+            charset: self.output_charset,
+            coerce_numbers: self.coerce_numbers,
+            coerce_scalars: self.coerce_scalars,
+            errors: self.collected_errors.clone(),
+            escape_html: self.escape_html,
+            every: self.every.max(1),
+            force_float: self.force_float,
+            indent: if self.pretty_tab {
+                Some(b"\t".to_vec())
+            } else {
+                self.pretty_indent.map(|n| vec![b' '; n])
+            },
+            jobs: self.jobs.unwrap_or(1),
+            line_numbers: self.line_numbers,
+            newline: self.newline,
+            normalize_numbers: self.normalize_numbers,
+            null_policy: self.null_policy.or_else(|| {
+                if self.null_to_default {
+                    NullPolicy::default_for(to)
+                } else {
+                    None
+                }
+            }),
+            output_newline: self.output_newline,
+            pointer: self.pointer.clone(),
+            prefix: self.output_prefix.as_ref().map_or(Ok(Vec::new()), |p| to_bytes(p))?,
+            restore_ext: self.preserve_ext || self.msgpack_restore_ext,
+            stats: self.stats && !self.quiet,
+            stop_after: self.stop_after,
+            suffix: self.output_suffix.as_ref().map_or(Ok(Vec::new()), |s| to_bytes(s))?,
+            transform: self.output_transform.clone(),
+            trim_newline: !self.trailing_newline,
+            verbose,
+        };
+        let check = self.check;
+        let (quiet, silent) = (self.quiet, self.silent);
+        let stop = Arc::new(AtomicBool::new(false));
+        let read_stop = Arc::clone(&stop);
+        // The counts are shared, so the actual counts are available even if the read thread is not
+        // joined because of the `stop_after` option.
+        let counts = Arc::new(Mutex::new(ReadCounts::default()));
+        let read_counts = Arc::clone(&counts);
+        // The progress line is stopped when it is dropped, including when an error is returned.
+        let progress = if self.progress && !self.quiet && atty::is(atty::Stream::Stderr) {
+            Some(Progress::start(Arc::clone(&counts)))
+        } else {
+            None
+        };
+        let udp_from = self.from.unwrap_or_else(|| self.fallback_from());
+        let handle = thread::spawn(move || -> Result<usize> {
+            if let Some(datagrams) = datagrams {
+                read_datagrams(
+                    datagrams,
+                    udp_from,
+                    udp_max_size,
+                    &read_options,
+                    &read_counts,
+                    &tx,
+                    &read_stop,
+                )
+                .or_else(|e| match e {
+                    Error::Eof => Ok(()),
+                    _ => Err(e),
+                })?;
+                return Ok(0);
+            }
+            let mut failed = 0;
+            for input in inputs {
+                if read_stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let (reader, from, name) = input?;
+                let result = read(
+                    reader,
+                    from,
+                    input_framing,
+                    &read_options,
+                    &read_counts,
+                    &tx,
+                    &read_stop,
+                )
+                .or_else(|e| match e {
+                    Error::Eof => Ok(()),
+                    _ => Err(e),
+                });
+                if check {
+                    match result {
+                        Ok(_) if quiet => {}
+                        Ok(_) => eprintln!("{}: OK", name),
+                        Err(e) => {
+                            if !silent {
+                                eprintln!("{}: {}", name, e);
+                            }
+                            failed += 1;
+                        }
+                    }
+                    continue;
+                }
+                // The error is returned from the thread, so the type of error, like an I/O error
+                // for a client that resets the connection, is kept when the thread is joined.
+                result?;
+            }
+            Ok(failed)
+        });
+        let display = Display {
+            annotate: self.annotate && (self.radix.is_some() || self.style.is_some()),
+            base64_url: self.base64_url,
+            c_declaration: self.c_declaration,
+            cbor_diag: self.cbor_diag,
+            checksum: self.checksum,
+            checksum_writer: self.checksum_writer.clone(),
+            color: match self.color {
+                Some(Color::Always) => true,
+                Some(Color::Auto) => {
+                    self.output.is_none()
+                        && self.connect.is_none()
+                        && self.udp_send.is_none()
+                        && self.unix_connect.is_none()
+                        && !compressed
+                        && atty::is(atty::Stream::Stdout)
+                        && env::var_os("NO_COLOR").map_or(true, |v| v.is_empty())
+                }
+                _ => false,
+            },
+            group: self.group,
+            no_padding: self.no_padding,
+            preview: self.preview,
+            radix: self.radix.map(|radix| RadixStyle {
+                lower: self.lower,
+                pad: self.pad,
+                prefix: self.prefix,
+                ..RadixStyle::new(radix)
+            }),
+            rust_slice: self.rust_slice,
+            style: self.style,
+            tee_radix: self.tee_radix.map(|radix| RadixStyle {
+                lower: self.lower,
+                pad: self.pad,
+                prefix: self.prefix,
+                ..RadixStyle::new(radix)
+            }),
+            width: self.width,
+        };
+        // Each of the other output formats has a separate consumer loop and output file, and a
+        // copy of every message is sent to each of them before the consumer loop of the `to`
+        // format.
+        let mut also_handles = Vec::with_capacity(also.len());
+        let rx = if also.is_empty() {
+            rx
+        } else {
+            let mut senders = Vec::with_capacity(also.len());
+            for (format, path) in also {
+                let file: Box<dyn Write + Send> = if self.append {
+                    Box::new(OpenOptions::new().append(true).create(true).open(path)?)
+                } else {
+                    Box::new(File::create(path)?)
+                };
+                let writer = compress_output(file, gzip_output, lz4_output, zstd_output)?;
+                let mut options = write_options.clone();
+                options.null_policy = self.null_policy.or_else(|| {
+                    if self.null_to_default {
+                        NullPolicy::default_for(format)
+                    } else {
+                        None
+                    }
+                });
+                let display = Display {
+                    color: false,
+                    ..display.clone()
+                };
+                let (also_tx, also_rx) = mpsc::channel::<Message>();
+                senders.push(also_tx);
+                also_handles.push(thread::spawn(move || {
+                    write(writer, format, output_framing, &options, &display, also_rx)
+                }));
+            }
+            let (to_tx, to_rx) = mpsc::channel::<Message>();
+            thread::spawn(move || {
+                for message in rx {
+                    for sender in &senders {
+                        // A consumer loop that failed is joined later for its error.
+                        let _ = sender.send(message.clone());
+                    }
+                    if to_tx.send(message).is_err() {
+                        break;
+                    }
+                }
+            });
+            to_rx
+        };
+        let mut differences = 0;
+        let write_counts = if self.check {
+            // Nothing is written, but the messages must still be received.
+            for _ in rx {}
+            WriteCounts::default()
+        } else if let Some(reference) = self.diff_against.as_ref() {
+            let expected = fs::read(reference)?;
+            let mut actual = Vec::new();
+            let counts = write(&mut actual, to, output_framing, &write_options, &display, rx)?;
+            differences = write_diff(writer, &expected, &actual, display.color)?;
+            counts
+        } else {
+            write(writer, to, output_framing, &write_options, &display, rx)?
+        };
+        let stopped = write_options
+            .stop_after
+            .map_or(false, |n| !self.check && write_counts.messages >= n);
+        let failed = if stopped {
+            // The read thread may be blocked on reading the input, like an open pipe, so it is
+            // signaled to stop and not joined. The counts so far are used.
+            stop.store(true, Ordering::SeqCst);
+            0
+        } else {
+            handle.join()??
+        };
+        if !stopped {
+            for also_handle in also_handles {
+                also_handle.join()??;
+            }
+        }
+        drop(progress);
+        let read_counts = lock_counts(&counts).clone();
+        if read_counts.skipped > 0 && !self.quiet {
+            eprintln!(
+                "Skipped {} message(s) that failed to deserialize",
+                read_counts.skipped
+            );
+        }
+        if (self.stats || self.stats_json) && !self.quiet {
+            let mut stats = RunStats::new(read_counts, write_counts, to, start.elapsed());
+            if let Some(errors) = self.collected_errors.as_ref() {
+                stats.errors_collected = lock_errors(errors).len();
+            }
+            if self.stats {
+                stats.write_text(write_options.every > 1);
+            }
+            if self.stats_json {
+                eprintln!("{}", serde_json::to_string(&stats)?);
+            }
+        }
+        if failed > 0 {
+            return Err(Error::Generic(format!(
+                "{} input(s) failed to deserialize",
+                failed
+            )));
+        }
+        if differences > 0 {
+            return Err(Error::Diff(differences));
+        }
+        Ok(())
+    }
+
+    /// Gets the input files and the path of each file relative to its input.
+    ///
+    /// An input with a glob pattern, like `logs/**/*.msgpack`, is expanded to the matching paths
+    /// in sorted order, where the directories are ignored unless the `recursive` option is used.
+    /// A directory input is walked recursively if the `recursive` option is used, and the
+    /// relative path of each file found in it is the path within the directory. The relative
+    /// path of any other input is its file name.
+    fn input_files(&self) -> Result<Vec<(String, PathBuf)>> {
+        let mut files = Vec::new();
+        let inputs = self.inputs.as_ref().map_or(&[][..], |i| &i[..]);
+        for input in inputs.iter().map(|i| expand_glob(i, self.recursive)) {
+            for input in &input? {
+                self.push_input_files(input, &mut files)?;
+            }
+        }
+        Ok(files)
+    }
+
+    /// Adds an input, or the files found in it if it is a directory with the `recursive` option,
+    /// and the path of each file relative to the input to the list of input files.
+    fn push_input_files(&self, input: &str, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+        let root = Path::new(input);
+        if self.recursive && root.is_dir() {
+            let walker = WalkDir::new(root).sort_by(|a, b| a.file_name().cmp(b.file_name()));
+            for entry in walker {
+                let entry = entry.map_err(io::Error::from)?;
+                let recognized = detect_input_format(entry.path()).is_some();
+                if entry.file_type().is_file() && recognized {
+                    let file = entry.path().to_string_lossy().into_owned();
+                    let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+                    files.push((file, relative.to_path_buf()));
+                }
+            }
+        } else {
+            let relative = root.file_name().map_or_else(|| PathBuf::from(input), PathBuf::from);
+            files.push((String::from(input), relative));
+        }
+        Ok(())
+    }
+
+    /// Transcodes the inputs and then again each time an input file changes.
+    ///
+    /// The parent directory of each input file is watched instead of the file, so a change is
+    /// still found if an editor saves a file by replacing it.
+    fn run_watch(mut self) -> Result<()> {
+        self.watch = false;
+        let files = self
+            .input_files()?
+            .into_iter()
+            .map(|(file, _)| fs::canonicalize(file))
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        if files.is_empty() {
+            return Err(Error::Generic(String::from(
+                "An input file is required to watch for changes",
+            )));
+        }
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::watcher(tx, WATCH_DEBOUNCE).map_err(watch_error)?;
+        for file in &files {
+            if let Some(parent) = file.parent() {
+                watcher
+                    .watch(parent, RecursiveMode::NonRecursive)
+                    .map_err(watch_error)?;
+            }
+        }
+        if let Err(e) = self.clone().run() {
+            if !self.silent {
+                eprintln!("{}", e);
+            }
+        }
+        for event in rx {
+            let changed = match event {
+                DebouncedEvent::Create(ref p) | DebouncedEvent::Write(ref p) => Some(p),
+                DebouncedEvent::Rename(_, ref p) => Some(p),
+                DebouncedEvent::Error(e, _) => return Err(watch_error(e)),
+                _ => None,
+            };
+            if changed.map_or(false, |p| files.contains(p)) {
+                if let Err(e) = self.clone().run() {
+                    if !self.silent {
+                        eprintln!("{}", e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares the values of the two input files and writes the differences to stdout.
+    fn run_diff(&self) -> Result<()> {
+        let inputs = self.inputs.as_ref().map_or(&[][..], |i| &i[..]);
+        if inputs.len() != 2 {
+            return Err(Error::Generic(String::from(
+                "Exactly two input files are required to compare the values",
+            )));
+        }
+        let mut values = Vec::with_capacity(2);
+        for input in inputs {
+            let path = Path::new(input);
+            let from = self
+                .from
+                .or_else(|| detect_input_format(path))
+                .unwrap_or_else(|| self.fallback_from());
+            let mut data = Vec::new();
+            let (gzip, lz4, zstd) = (self.gzip_input, self.lz4_input, self.zstd_input);
+            open_input(path, gzip, lz4, zstd, false)?.read_to_end(&mut data)?;
+            values.push(deserialize(&data, from)?);
+        }
+        let differences = diff_values(&values[0], &values[1]);
+        let stdout = io::stdout();
+        let mut writer = stdout.lock();
+        for difference in &differences {
+            writeln!(writer, "{}", difference)?;
+        }
+        writer.flush()?;
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Differences(differences.len()))
+        }
+    }
+
+    /// Transcodes each input file to a separate file in the output directory.
+    fn run_output_dir(self, dir: &str) -> Result<()> {
+        let to = self.to.unwrap_or_else(|| self.fallback_to());
+        let files = self.input_files()?;
+        if files.is_empty() {
+            return Err(Error::Generic(String::from(
+                "An input file is required to write to an output directory",
+            )));
+        }
+        let ext = self.output_extension(to);
+        let mut used = HashSet::new();
+        for (input, relative) in files {
+            // The `.gz`, `.lz4`, and `.zst` extensions are removed, so `data.json.gz` is written to
+            // `data.msgpack`.
+            let relative = if is_compressed(&relative) {
+                relative.with_extension("")
+            } else {
+                relative
+            };
+            let mut stem = Path::new(dir).join(&relative).with_extension("");
+            // Input files with the same name from different directories, or with different
+            // extensions, get a numeric suffix instead of overwriting the earlier output.
+            let mut n = 0;
+            while !used.insert(stem.clone()) {
+                n += 1;
+                let name = relative
+                    .file_stem()
+                    .map_or_else(Default::default, |s| s.to_string_lossy());
+                stem.set_file_name(format!("{}-{}", name, n));
+            }
+            // The output is a stem for more than one output format, which adds the extensions.
+            let mut output = stem.into_os_string();
+            if self.also_to.is_empty() {
+                output.push(".");
+                output.push(&ext);
+            }
+            let output = PathBuf::from(output);
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut panser = self.clone();
+            panser.inputs = Some(vec![input]);
+            panser.recursive = false;
+            panser.output = Some(output.to_string_lossy().into_owned());
+            panser.to = Some(to);
+            panser.run()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the output to a temporary file, which is synced and renamed to the output file after
+    /// the run succeeds.
+    fn run_atomic(self) -> Result<()> {
+        let output = self.output.clone().ok_or_else(|| {
+            Error::Generic(String::from("An output file is required to write atomically"))
+        })?;
+        if self.append || !self.also_to.is_empty() {
+            return Err(Error::Generic(String::from(
+                "An output file cannot be written atomically when appending or with more than \
+                 one output format",
+            )));
+        }
+        let path = Path::new(&output);
+        let temp = format!("{}.tmp", output);
+        let mut panser = self.clone();
+        panser.atomic = false;
+        // The temporary file has the `.tmp` extension, so the format and compression are set from
+        // the output file.
+        panser.output = Some(temp.clone());
+        panser.to = Some(
+            self.to.or_else(|| detect_output_format(path)).unwrap_or_else(|| self.fallback_to()),
+        );
+        if is_gzip(path) {
+            panser.gzip_output = self.gzip_output.or(Some(Compression::default().level()));
+        } else if is_zstd(path) {
+            panser.zstd_output = self.zstd_output.or(Some(ZSTD_DEFAULT_LEVEL));
+        } else if is_lz4(path) {
+            panser.lz4_output = true;
+        }
+        // The temporary file is synced through a writable handle, since Windows cannot flush the
+        // buffers of a read-only handle.
+        let result = panser
+            .run()
+            .and_then(|_| Ok(OpenOptions::new().write(true).open(&temp)?.sync_all()?))
+            .and_then(|_| Ok(fs::rename(&temp, path)?))
+            .and_then(|_| sync_parent(path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    /// Rewrites each input file with its output, where the output replaces the file only after
+    /// the file is transcoded successfully.
+    fn run_in_place(self) -> Result<()> {
+        let to = self.to.unwrap_or_else(|| self.fallback_to());
+        let files = self.input_files()?;
+        if files.is_empty() {
+            return Err(Error::Generic(String::from(
+                "An input file is required to convert in place",
+            )));
+        }
+        if !self.also_to.is_empty() {
+            return Err(Error::Generic(String::from(
+                "More than one output format cannot be used to convert in place",
+            )));
+        }
+        let mut failed = 0;
+        for (input, _) in files {
+            let path = Path::new(&input);
+            let target = if self.rename_ext {
+                // The `.gz`, `.lz4`, and `.zst` extensions are replaced, too.
+                let path = if is_compressed(path) {
+                    path.with_extension("")
+                } else {
+                    path.to_path_buf()
+                };
+                path.with_extension(self.output_extension(to))
+            } else {
+                path.to_path_buf()
+            };
+            // The temporary file is in the same directory, so the rename does not move the file to
+            // another file system, which would not be atomic.
+            let name = path.file_name().map_or_else(Default::default, |n| n.to_string_lossy());
+            let temp = path.with_file_name(format!(".{}.panser-tmp", name));
+            let mut panser = self.clone();
+            panser.in_place = false;
+            panser.inputs = Some(vec![input.clone()]);
+            panser.recursive = false;
+            panser.output = Some(temp.to_string_lossy().into_owned());
+            panser.to = Some(to);
+            // The temporary file has the `.panser-tmp` extension, so the compression is set from
+            // the input file, which keeps its name and extension.
+            if !self.rename_ext && is_compressed(path) {
+                panser.gzip_output = None;
+                panser.lz4_output = false;
+                panser.zstd_output = None;
+                if is_gzip(path) {
+                    panser.gzip_output = self.gzip_output.or(Some(Compression::default().level()));
+                } else if is_zstd(path) {
+                    panser.zstd_output = self.zstd_output.or(Some(ZSTD_DEFAULT_LEVEL));
+                } else {
+                    panser.lz4_output = true;
+                }
+            }
+            // A renamed output never replaces an unrelated file.
+            let result = if target != path && target.exists() {
+                Err(Error::Generic(format!(
+                    "The output file '{}' already exists",
+                    target.display()
+                )))
+            } else {
+                panser.run()
+            };
+            let result = result
+                .and_then(|_| Ok(fs::set_permissions(&temp, fs::metadata(path)?.permissions())?))
+                .and_then(|_| Ok(fs::rename(&temp, &target)?))
+                .and_then(|_| {
+                    if target != path {
+                        fs::remove_file(path)?;
+                    }
+                    Ok(())
+                });
+            if let Err(e) = result {
+                let _ = fs::remove_file(&temp);
+                if !self.silent {
+                    eprintln!("{}: {}", input, e);
+                }
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            return Err(Error::Generic(format!(
+                "{} input(s) failed to convert in place",
+                failed
+            )));
+        }
+        Ok(())
+    }
+
+    /// Gets the input format if it is not set and cannot be determined from the input.
+    fn fallback_from(&self) -> FromFormat {
+        self.default_from.unwrap_or(FromFormat::Json)
+    }
+
+    /// Gets the output format if it is not set and cannot be determined from the output file.
+    fn fallback_to(&self) -> ToFormat {
+        self.default_to.unwrap_or(ToFormat::Msgpack)
+    }
+
+    /// Gets the file extension of an output file for the output format, including the `.gz`,
+    /// `.lz4`, or `.zst` extension if the output is compressed.
+    fn output_extension(&self, to: ToFormat) -> String {
+        if self.gzip_output.is_some() {
+            format!("{}.gz", extension(to))
+        } else if self.zstd_output.is_some() {
+            format!("{}.zst", extension(to))
+        } else if self.lz4_output {
+            format!("{}.lz4", extension(to))
+        } else {
+            String::from(extension(to))
+        }
+    }
+
+    /// Checks the options can be used with an output format, and warns about the options that
+    /// corrupt or are ignored for the output format unless the `quiet` option is used.
+    fn check_output_format(&self, to: ToFormat) -> Result<()> {
+        let displayed = self.radix.is_some() || self.style.is_some() || self.cbor_diag;
+        let warn = !self.quiet;
+        if warn && self.line_numbers && !displayed && is_binary(to) {
+            eprintln!(
+                "Warning: The line numbers corrupt the {} output. The '--line-numbers' flag only \
+                 makes sense with a text output format or the '-r,--radix' option.",
+                to
+            );
+        }
+        if warn && self.ascii_only && !is_json(to) {
+            eprintln!(
+                "Warning: The '--ascii-only' flag is ignored for the {} output format. It only \
+                 applies to the JSON and Hjson output formats.",
+                to
+            );
+        }
+        if warn && self.escape_html && !is_json(to) {
+            eprintln!(
+                "Warning: The '--escape-html' flag is ignored for the {} output format. It only \
+                 applies to the JSON and Hjson output formats.",
+                to
+            );
+        }
+        if self.base64_to_bytes && (self.allow_nan || self.msgpack_restore_ext) {
+            return Err(Error::Generic(String::from(
+                "The '--base64-to-bytes' flag cannot be used with the '--allow-nan' or \
+                 '--msgpack-restore-ext' flags",
+            )));
+        }
+        if (self.output_bom || self.output_charset.is_some()) && is_binary(to) {
+            return Err(Error::Generic(format!(
+                "A byte order mark or charset cannot be used with the binary {} output format",
+                to
+            )));
+        }
+        if let (true, ToFormat::Yaml) = (self.pretty_indent.is_some() || self.pretty_tab, to) {
+            // The YAML serializer always indents with two spaces, and YAML does not allow tabs.
+            return Err(Error::Generic(String::from(
+                "The indentation cannot be changed for the YAML output format, which is always \
+                 indented with two spaces",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Suppresses everything written to stderr, including the errors that do not stop the run.
+    ///
+    /// If `false`, which is the default, then the errors of each input that fails with the
+    /// `check` or `in_place` options, or of each run with the `watch` option, are written to
+    /// stderr. If `true`, then these errors are not written, and the `quiet` option is used. The
+    /// error returned from the `run` method is not changed, so an application can still report
+    /// it or use its code.
+    pub fn silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
+    /// Indicates the first four bytes is the total data length and changes to framed reading of
+    /// the data.
+    ///
+    /// The first four bytes are read as an unsigned 32-bit integer (u32) in Big Endian (Network
+    /// Order). Then N number of bytes are read, where N is the size converted from the first
+    /// four bytes. Once N bytes are read, all bytes up to the size are transcoded. This
+    /// continues until the End-of-File (EOF) is reached.
+    pub fn sized_input(mut self, sized: bool) -> Self {
+        self.sized_input = sized;
+        self
+    }
+
+    /// Prepends the length of the data to the output.
+    ///
+    /// The size of the output is prepended as an unsigned 32-bit integer (u32) in Big Endian
+    /// (Network Order).
+    pub fn sized_output(mut self, sized: bool) -> Self {
+        self.sized_output = sized;
+        self
+    }
+
+    /// Writes the size of each serialized output message and a summary to stderr.
+    ///
+    /// If `false`, which is the default, then nothing is written to stderr. If `true`, then the
+    /// size of each serialized output message is written to stderr as it is written to the output.
+    /// After all of the messages, a summary of the number of messages, the total input and output
+    /// bytes, the minimum, mean, and maximum output message size, and the elapsed time is written
+    /// to stderr. Sizes do not include any framing, i.e. the size, delimiter, STX/ETX bytes, or
+    /// checksum. Use a sink, like `/dev/null`, for the output to only measure the sizes.
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Writes a summary of the run to stderr as a single line of JSON.
+    ///
+    /// If `false`, which is the default, then nothing is written to stderr. If `true`, then an
+    /// object with the `messages`, `received`, `skipped`, `errors_collected`, `input_bytes`,
+    /// `output_bytes`, `min_bytes`, `mean_bytes`, `max_bytes`, `elapsed_seconds`,
+    /// `input_formats`, and `output_format` fields is written to stderr after all of the
+    /// messages, where the `input_formats` are the number of messages deserialized for each input
+    /// format and the `errors_collected` are the number of errors collected with the
+    /// `collect_errors` option. Unlike the `stats` option, the size of each message is not
+    /// written.
+    pub fn stats_json(mut self, stats: bool) -> Self {
+        self.stats_json = stats;
+        self
+    }
+
+    /// Stops after writing a number of messages.
+    ///
+    /// If `None`, which is the default, then all of the messages are written. If `Some`, then
+    /// the output is flushed after the number of messages are written, the reading of the input is
+    /// cancelled, and the `run` method returns without waiting for the rest of the input. This is
+    /// useful for taking the first few messages from a continuous stream. The read thread stops
+    /// before its next message, but it is not joined, so a read that is blocked on an open pipe
+    /// does not block the return.
+    pub fn stop_after(mut self, stop_after: Option<usize>) -> Self {
+        self.stop_after = stop_after;
+        self
+    }
+
+    /// Converts the boolean and number keys of maps to strings when deserializing.
+    ///
+    /// If `false`, which is the default, then a map with a key that is not a string, like the
+    /// integer keys of a MessagePack map, fails to deserialize, because the keys of a JSON object
+    /// are strings. If `true`, then the keys of the MessagePack, CBOR, and YAML input formats are
+    /// converted to strings, i.e. `1` is `"1"`, and a map with an array or map key still fails.
+    /// This is lossy, since the keys are always serialized as strings, even for the reverse trip
+    /// to MessagePack. The `coerce_scalars` option only converts values, not keys.
+    pub fn stringify_keys(mut self, stringify_keys: bool) -> Self {
+        self.stringify_keys = stringify_keys;
+        self
+    }
+
+    /// Sets the checksum for STX/ETX framing.
+    ///
+    /// If `None`, which is the default, then no checksum is expected after the End-of-Text (ETX)
+    /// byte for the input and no checksum is appended after the ETX byte for the output.
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if neither the `stxetx_input` nor the `stxetx_output` option
+    /// is used, since the checksum would be ignored.
+    pub fn stxetx_checksum(mut self, checksum: Option<Checksum>) -> Self {
+        self.stxetx_checksum = checksum;
+        self
+    }
+
+    /// Indicates each message is wrapped with the Start-of-Text (STX, `0x02`) and End-of-Text
+    /// (ETX, `0x03`) bytes and changes to framed reading of the data.
+    ///
+    /// Data is read from the input source to the next ETX byte. The leading STX byte, the ETX
+    /// byte, and the checksum, if any, are removed before the message is transcoded. This
+    /// continues until the End-of-File (EOF) is reached.
+    pub fn stxetx_input(mut self, stxetx: bool) -> Self {
+        self.stxetx_input = stxetx;
+        self
+    }
+
+    /// Wraps the output with the Start-of-Text (STX, `0x02`) and End-of-Text (ETX, `0x03`)
+    /// bytes.
+    ///
+    /// The STX byte is prepended and the ETX byte, followed by the checksum if any, is appended to
+    /// the output data.
+    pub fn stxetx_output(mut self, stxetx: bool) -> Self {
+        self.stxetx_output = stxetx;
+        self
+    }
+
+    /// Sets the written output to be the serialized data displayed as source code, or text, in
+    /// a specific style.
+    ///
+    /// Similar to the `radix` option, the data is still transcoded to the `to` format, but it is
+    /// written to the output as a string. If both a style and a radix are used, then the style
+    /// takes precedence.
+    pub fn style(mut self, style: Option<Style>) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Takes the errors collected with the `collect_errors` option, leaving the list empty.
+    ///
+    /// The list is shared with the clones of the builder, so the errors of a run of a clone are
+    /// taken. An empty list is returned if the `collect_errors` option is not used.
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        self.collected_errors
+            .as_ref()
+            .map_or_else(Vec::new, |e| mem::take(&mut *lock_errors(e)))
+    }
+
+    /// Also writes each message with a radix to stderr.
+    ///
+    /// If `None`, which is the default, then nothing extra is written. If `Some`, then the output
+    /// is written as usual and each framed message is also written to stderr as a space-separated
+    /// list of bytes formatted with the radix, like the `radix` option, after the frame annotation
+    /// of the `annotate` option. This is useful for seeing the binary frames sent to another
+    /// program. The text of each message is written all at once, so it is not interleaved with
+    /// error messages.
+    pub fn tee_radix(mut self, radix: Option<Radix>) -> Self {
+        self.tee_radix = radix;
+        self
+    }
+
+    /// The format of the output.
+    ///
+    /// If `None`, which is the default, then the output format is assumed to be MessagePack
+    /// (Msgpack).
+    pub fn to(mut self, to: Option<ToFormat>) -> Self {
+        self.to = to;
+        self
+    }
+
+    /// Keeps the trailing newline of text output formats.
+    ///
+    /// The serializers for some text formats, like YAML and TOML, end the output with a newline.
+    /// If `true`, which is the default, then the output is written as serialized. If `false`, then
+    /// a final newline is removed from each JSON, Hjson, TOML, and YAML message before it is
+    /// written, which is useful for concatenating messages or comparing the output byte for byte.
+    /// This has no effect on the other output formats.
+    pub fn trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Sets a UDP address to listen on and read the input from datagrams instead of the inputs.
+    ///
+    /// If `None`, which is the default, then the input is read from the inputs, or stdin. The value
+    /// is a `host:port` string, such as `0.0.0.0:1234`. Each datagram is a message, so the input
+    /// framing is not used, and datagrams are read until the process is stopped or the
+    /// `stop_after` option is reached. A datagram larger than the `udp_max_size` option is
+    /// skipped with a warning instead of being deserialized, since it may have been truncated.
+    /// The input format is the `from` format, or JSON. An I/O error with the address occurs if the
+    /// address cannot be bound.
+    pub fn udp_listen(mut self, address: Option<&str>) -> Self {
+        self.udp_listen = address.map(|a| a.to_owned());
+        self
+    }
+
+    /// Sets the maximum size, in bytes, of a datagram for the `udp_listen` and `udp_send` options.
+    ///
+    /// If `None`, which is the default, then the maximum is 65,507 bytes, which is the largest
+    /// payload of a UDP datagram over IPv4. A larger datagram is skipped when reading, and a larger
+    /// message is not sent when writing. A warning is written to stderr for either.
+    pub fn udp_max_size(mut self, max_size: Option<usize>) -> Self {
+        self.udp_max_size = max_size;
+        self
+    }
+
+    /// Sets a UDP address to send each message of the output to as a datagram instead of writing
+    /// to a file or stdout.
+    ///
+    /// If `None`, which is the default, then the output is written to the `output` destination.
+    /// The value is a `host:port` string, where the host can be a name, which is resolved, or an
+    /// IP address. Any framing, prefix, and suffix are included in the datagram of each message.
+    /// An I/O error with the address occurs if the address cannot be resolved.
+    pub fn udp_send(mut self, address: Option<&str>) -> Self {
+        self.udp_send = address.map(|a| a.to_owned());
+        self
+    }
+
+    /// Sets the path of a Unix domain socket file to write the output to instead of a file or
+    /// stdout.
+    ///
+    /// If `None`, which is the default, then the output is written to the `output` destination.
+    /// This is the same as the `connect` option, but for a local socket file instead of a TCP
+    /// server. An I/O error with the path occurs if a connection cannot be made or the platform
+    /// does not have Unix domain sockets.
+    pub fn unix_connect(mut self, path: Option<&str>) -> Self {
+        self.unix_connect = path.map(|p| p.to_owned());
+        self
+    }
+
+    /// Sets the path of a Unix domain socket file to listen on and read the input from a client
+    /// instead of the inputs.
+    ///
+    /// If `None`, which is the default, then the input is read from the inputs, or stdin. This is
+    /// the same as the `listen` option, including the `keep_listening` option, but for a local
+    /// socket file instead of a TCP address. A stale socket file is removed before listening, but
+    /// the socket file of a running server, or any other file, is never removed. The socket file
+    /// is removed when transcoding ends. An I/O error with the path occurs if the path cannot be
+    /// bound or the platform does not have Unix domain sockets.
+    pub fn unix_listen(mut self, path: Option<&str>) -> Self {
+        self.unix_listen = path.map(|p| p.to_owned());
+        self
+    }
+
+    /// Sets the permissions of the socket file created for the `unix_listen` option.
+    ///
+    /// If `None`, which is the default, then the permissions are from the umask of the process.
+    /// The mode is the permission bits, such as `0o600` for only the owner to read and write.
+    pub fn unix_mode(mut self, mode: Option<u32>) -> Self {
+        self.unix_mode = mode;
+        self
+    }
+
+    /// Sets the writer for the verbose log of the `verbosity` option.
+    ///
+    /// If `None`, which is the default, then the log is written to stderr, but only if stderr is a
+    /// terminal (TTY), so the log does not pollute stderr when it is piped or redirected to a
+    /// file.
+    pub fn verbose_writer(mut self, writer: Option<Box<dyn Write + Send>>) -> Self {
+        self.verbose_writer = writer.map(|w| Arc::new(Mutex::new(w)));
+        self
+    }
+
+    /// Sets the level of the verbose log of each message.
+    ///
+    /// If zero (0), which is the default, then nothing is logged. For a level of one (1), the
+    /// input format, the size and first 64 bytes of the raw input as hexadecimal, the deserialized
+    /// value as compact JSON, and the size of the serialized output data are logged for each
+    /// message, where each line starts with the input frame number in brackets, like `[3]`. For a
+    /// level of two (2) or more, all of the input bytes and the output bytes are logged. See the
+    /// `verbose_writer` option for where the log is written.
+    pub fn verbosity(mut self, level: u8) -> Self {
+        self.verbosity = level;
+        self
+    }
+
+    /// Transcodes the inputs again each time an input file changes.
+    ///
+    /// If `false`, which is the default, then the inputs are transcoded once. If `true`, then the
+    /// inputs are transcoded once and then again after each change to any of the input files until
+    /// the process is stopped. Rapid changes within 200 milliseconds are combined into a single
+    /// change. The output is overwritten each time unless the `append` option is used. An error
+    /// while transcoding, such as a partially written input file, is written to stderr and does not
+    /// stop the watching. At least one input file is required.
+    pub fn watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// The number of bytes per line for the displayed output.
+    ///
+    /// If `None`, which is the default, then all of the bytes for a message are displayed on
+    /// a single line, except for the C declaration, which uses twelve (12) bytes per line like the
+    /// `xxd -i` command, and the hex dump, which uses sixteen (16) bytes per line like the `xxd`
+    /// command. This is ignored unless a radix or style is used.
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Indicates the input is Zstandard compressed and is decompressed before it is read.
+    ///
+    /// If `false`, which is the default, then an input file is only decompressed if it has the
+    /// `.zst` extension or starts with the Zstandard magic bytes, `28 B5 2F FD`, and stdin is never
+    /// decompressed. If `true`, then every input file and stdin is decompressed. The framing is
+    /// read from the decompressed data, so the frames are inside the compressed stream. This is
+    /// ignored if the `gzip_input` option is `true`.
+    pub fn zstd_input(mut self, zstd: bool) -> Self {
+        self.zstd_input = zstd;
+        self
+    }
+
+    /// Sets the compression level for Zstandard compressing the output.
+    ///
+    /// The level is from one (1), the fastest, to twenty-two (22), the best compression. If
+    /// `None`, which is the default, then the output is only compressed if the output file has the
+    /// `.zst` extension, which uses the default level of three (3). For an output directory, the
+    /// `.zst` extension is added to each output file. This is ignored if the `gzip_output` option
+    /// is used.
+    pub fn zstd_output(mut self, level: Option<i32>) -> Self {
+        self.zstd_output = level;
+        self
+    }
+
+    /// Sets a transform of the raw bytes of each input message before it is deserialized.
+    ///
+    /// The transform is applied to the data of each frame, without the framing, after the
+    /// `radix_input` or `from_base16_file` option, if any, and before the format tag of the
+    /// `from_format_header` option is read. This is useful for removing metadata that cannot be
+    /// deserialized, like a sequence number before the serialized data. An error from the
+    /// transform is handled like an error deserializing the message, so the message is skipped
+    /// with the `continue_on_error` option. Unlike the `transform` module, which changes the
+    /// deserialized values, the transform changes the bytes.
+    pub fn with_input_transform<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Vec<u8>) -> Result<Vec<u8>> + Send + 'static,
+    {
+        self.input_transform = Some(Arc::new(Mutex::new(Box::new(f))));
+        self
+    }
+
+    /// Sets a transform of the serialized bytes of each output message before it is written.
+    ///
+    /// The transform is applied after the output charset and byte order mark (BOM), if any, and
+    /// before the framing, prefix, and suffix are added, so the size of the `sized_output` option
+    /// is the size of the transformed bytes. The transform is applied to the messages in the same
+    /// order as the input, even with more than one job, so this is useful for adding metadata,
+    /// like a sequence number, before the serialized data.
+    pub fn with_output_transform<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Vec<u8>) -> Result<Vec<u8>> + Send + 'static,
+    {
+        self.output_transform = Some(Arc::new(Mutex::new(Box::new(f))));
+        self
+    }
+}
+
+/// Deserialize to a universal, arbitrary value.
+///
+/// The `serde_json::Value` type is used as a container for an arbitrary deserialized value. All
+/// formats are deserialized to a `serde_json::Value` type.
+pub fn deserialize(input: &[u8], from: FromFormat) -> Result<serde_json::Value> {
+    let input = strip_bom(input, from);
+    Ok({
+        match from {
+            FromFormat::Bincode => bincode::deserialize::<serde_json::Value>(input)?,
+            FromFormat::Cbor => serde_cbor::from_slice::<serde_json::Value>(input)?,
+            FromFormat::Dotenv => dotenv_from_slice(input)?,
+            FromFormat::Envy => envy::from_env::<serde_json::Value>()?,
+            FromFormat::FlexBuffers => flexbuffers::from_slice::<serde_json::Value>(input)?,
+            // TODO: Change to use Hjson serde library. Until the Hjson crate is updated to work
+            // with serde v0.9 or newer, the serde_json create is used. The Hjson crate currently
+            // uses serde v0.8 and causes compiler errors.
+            FromFormat::Hjson => serde_json::from_slice::<serde_json::Value>(input)?,
+            FromFormat::Json => serde_json::from_slice::<serde_json::Value>(input)?,
+            FromFormat::Msgpack => rmp_serde::from_slice::<serde_json::Value>(input)?,
+            FromFormat::Pickle => serde_pickle::from_slice::<serde_json::Value>(
+                input,
+                serde_pickle::DeOptions::default(),
+            )?,
+            FromFormat::Resp => resp::from_slice(input)?,
+            FromFormat::Smile => smile::from_slice(input)?,
+            FromFormat::Toml => toml::from_str::<serde_json::Value>(str::from_utf8(input)?)?,
+            FromFormat::Tsv => tsv::from_slice(input)?,
+            FromFormat::Url => serde_urlencoded::from_bytes::<serde_json::Value>(input)?,
+            FromFormat::Yaml => serde_yaml::from_slice::<serde_json::Value>(input)?,
+        }
+    })
+}
+
+/// Deserializes the `KEY=VALUE` lines of a dotenv file to an object of strings.
+///
+/// The lines are parsed like the `dotenvy` crate would load them, including quotes, comments,
+/// and substitutions, but the environment of the process is not changed.
+fn dotenv_from_slice(input: &[u8]) -> Result<serde_json::Value> {
+    let mut map = serde_json::Map::new();
+    for item in dotenvy::from_read_iter(input) {
+        let (key, value) = item?;
+        map.insert(key, serde_json::Value::String(value));
+    }
+    Ok(serde_json::Value::Object(map))
+}
+
+/// Deserialize to a universal, arbitrary value with options for values that JSON cannot represent.
+///
+/// Only CBOR, MessagePack, TOML, and YAML can have values that JSON cannot represent. All other
+/// formats are deserialized the same as the `deserialize` function.
+fn deserialize_with(
+    input: &[u8],
+    from: FromFormat,
+    options: ValueOptions,
+) -> Result<serde_json::Value> {
+    let default = !options.allow_nan && !options.bytes_as_base64 && options.ext.is_none();
+    if default && !options.stringify_keys {
+        return deserialize(input, from);
+    }
+    let input = strip_bom(input, from);
+    // Only MessagePack has extension types.
+    let ext = match from {
+        FromFormat::Msgpack => options.ext,
+        _ => None,
+    };
+    let seed = ValueSeed(ValueOptions { ext, ..options });
+    Ok({
+        match from {
+            FromFormat::Cbor => {
+                let mut deserializer = serde_cbor::Deserializer::from_slice(input);
+                let value = seed.deserialize(&mut deserializer)?;
+                deserializer.end()?;
+                value
+            }
+            FromFormat::Msgpack => {
+                seed.deserialize(&mut rmp_serde::Deserializer::from_read_ref(input))?
+            }
+            FromFormat::Toml => seed.deserialize(toml::Deserializer::new(str::from_utf8(input)?))?,
+            FromFormat::Yaml => seed.deserialize(serde_yaml::Deserializer::from_slice(input))?,
+            _ => deserialize(input, from)?,
+        }
+    })
+}
+
+/// Expands an input with a glob pattern, like `logs/**/*.msgpack`, to the matching paths in
+/// sorted order.
+///
+/// An input without any of the `*`, `?`, or `[` glob characters, a URL, or a file that exists
+/// with the characters in its name, is not expanded. This does the expansion that a shell would
+/// do, which is needed on Windows. The matching directories are ignored unless `directories` is
+/// `true`.
+///
+/// # Errors
+///
+/// A `Generic` error will occur if the pattern is not valid or the pattern does not match any
+/// paths.
+fn expand_glob(input: &str, directories: bool) -> Result<Vec<String>> {
+    let pattern = input.contains(|c| c == '*' || c == '?' || c == '[');
+    if !pattern || net::is_url(input) || Path::new(input).exists() {
+        return Ok(vec![String::from(input)]);
+    }
+    let paths = glob::glob(input)
+        .map_err(|e| Error::Generic(format!("The '{}' pattern is not valid: {}", input, e)))?;
+    let mut matches = Vec::new();
+    for path in paths {
+        let path = path.map_err(io::Error::from)?;
+        if directories || !path.is_dir() {
+            matches.push(path.to_string_lossy().into_owned());
+        }
+    }
+    if matches.is_empty() {
+        return Err(Error::Generic(format!(
+            "The '{}' pattern does not match any files",
+            input
+        )));
+    }
+    matches.sort();
+    Ok(matches)
+}
+
+/// Removes a UTF-8 byte order mark (BOM) from the start of the input for the text formats.
+///
+/// Some editors on Windows write a BOM at the start of a text file, which is not valid JSON, TOML,
+/// etc. The input for the binary formats is never changed.
+fn strip_bom(input: &[u8], from: FromFormat) -> &[u8] {
+    let bom = Charset::Utf8.bom();
+    if !is_binary_input(from) && input.starts_with(bom) {
+        &input[bom.len()..]
+    } else {
+        input
+    }
+}
+
+/// Serialize from a universal, arbitrary value.
+///
+/// The `serde_json::Value` type is used as a container for an arbitrary value that can be
+/// serialized to any format.
+pub fn serialize(value: serde_json::Value, to: ToFormat) -> Result<Vec<u8>> {
+    serialize_value(&value, to)
+}
+
+/// Serialize from a borrowed universal, arbitrary value.
+///
+/// This is the same as the `serialize` function, but the value is borrowed instead of moved.
+pub fn serialize_value(value: &serde_json::Value, to: ToFormat) -> Result<Vec<u8>> {
+    serialize_any(value, to)
+}
+
+/// Serialize from any serializable value.
+fn serialize_any<T: Serialize>(value: &T, to: ToFormat) -> Result<Vec<u8>> {
+    Ok({
+        match to {
+            ToFormat::Bincode => bincode::serialize(value)?,
+            ToFormat::Cbor => serde_cbor::to_vec(value)?,
+            ToFormat::FlexBuffers => flexbuffers::to_vec(value)?,
+            // TODO: Change to use Hjson serde library. Until the Hjson crate is updated to work
+            // with serde v0.9 or newer, the serde_json create is used. The Hjson crate currently
+            // uses serde v0.8 and causes compiler errors.
+            ToFormat::Hjson => serde_json::to_vec_pretty(value)?,
+            ToFormat::Json => serde_json::to_vec(value)?,
+            ToFormat::Markdown => markdown::to_vec(&serde_json::to_value(value)?)?,
+            ToFormat::Msgpack => rmp_serde::to_vec(value)?,
+            ToFormat::Pickle => serde_pickle::to_vec(value, serde_pickle::SerOptions::default())?,
+            ToFormat::Resp => resp::to_vec(&serde_json::to_value(value)?),
+            ToFormat::Smile => smile::to_vec(&serde_json::to_value(value)?),
+            // An array of objects is serialized as an array of tables, i.e. `[[servers]]`, and
+            // tables are written after all other values of the parent table.
+            ToFormat::Toml => toml::to_string(value)?.into_bytes(),
+            ToFormat::Tsv => tsv::to_vec(&serde_json::to_value(value)?)?,
+            ToFormat::Url => serde_urlencoded::to_string(value)?.into_bytes(),
+            ToFormat::Yaml => serde_yaml::to_string(value)?.into_bytes(),
+        }
+    })
+}
+
+/// Serialize to pretty JSON, where each level of nesting is indented with the indent.
+fn serialize_pretty<T: Serialize>(value: &T, indent: &[u8]) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent);
+    value.serialize(&mut serde_json::Serializer::with_formatter(&mut buf, formatter))?;
+    Ok(buf)
+}
+
+/// Serialize from a universal, arbitrary value that holds non-finite floats.
+///
+/// The non-finite floats are written as the non-standard `NaN`, `Infinity`, and `-Infinity`
+/// literals for JSON and Hjson, where JSON is pretty if there is an indent. All other formats
+/// have the non-finite floats written as floats.
+fn serialize_non_finite(
+    value: &serde_json::Value,
+    to: ToFormat,
+    indent: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    match to {
+        ToFormat::Hjson => Ok(value::to_json_literals(value, Some(indent.unwrap_or(b"  ")))?),
+        ToFormat::Json => Ok(value::to_json_literals(value, indent)?),
+        _ => serialize_any(&NonFinite(value), to),
+    }
+}
+
+/// Serialize from a universal, arbitrary value that holds representations of MessagePack extension
+/// types to MessagePack.
+///
+/// The representations are written as extension types. Any held non-finite floats are written as
+/// floats if `allow_nan` is `true`.
+fn serialize_ext(value: &serde_json::Value, allow_nan: bool) -> Result<Vec<u8>> {
+    serialize_any(&Ext(value, allow_nan), ToFormat::Msgpack)
+}
+
+/// Convert the input in one format to the output of another format.
+///
+/// This does allocate memory, as not all serde-based libraries support allocation-free
+/// transcoding. However, if used in a producer-consumer architecture with framing,
+/// the memory usage should be minimized.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate panser;
+///
+/// use panser::{FromFormat, ToFormat};
+///
+/// fn main() {
+///     let input = "{\"bool\":true}";
+///     let output = panser::transcode(
+///         input.as_bytes(),
+///         FromFormat::Json,
+///         ToFormat::Msgpack
+///     ).unwrap();
+///     assert_eq!(output, vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+/// }
+/// ```
+pub fn transcode(input: &[u8], from: FromFormat, to: ToFormat) -> Result<Vec<u8>> {
+    serialize(deserialize(input, from)?, to)
+}
+
+/// Convert a universal, arbitrary value to the output of a format.
+///
+/// This is the same as the `transcode` function, but for a value that has already been
+/// deserialized, such as a value created with the `serde_json::json!` macro. This avoids
+/// needlessly serializing the value to JSON only to deserialize it again.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_json;
+/// extern crate panser;
+///
+/// use panser::ToFormat;
+///
+/// fn main() {
+///     let value = json!({"bool": true});
+///     let output = panser::transcode_value(&value, ToFormat::Msgpack).unwrap();
+///     assert_eq!(output, vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+/// }
+/// ```
+pub fn transcode_value(value: &serde_json::Value, to: ToFormat) -> Result<Vec<u8>> {
+    serialize_value(value, to)
+}
+
+/// Compares two universal, arbitrary values and gets the differences.
+///
+/// The keys of objects are compared by name, and the items of arrays are compared by index, so an
+/// item inserted in an array changes every item after it. Any other values, including values of
+/// different types, are compared by equality, so the integer `1` and the float `1.0` are
+/// different. The differences are in the sorted order of the keys and the order of the items, and
+/// an empty list means the values are the same.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_json;
+/// extern crate panser;
+///
+/// use panser::Difference;
+///
+/// fn main() {
+///     let a = json!({"name": "panser", "tags": ["a"]});
+///     let b = json!({"name": "Panser", "tags": ["a", "b"]});
+///     assert_eq!(
+///         panser::diff_values(&a, &b),
+///         vec![
+///             Difference::Changed(String::from("/name"), json!("panser"), json!("Panser")),
+///             Difference::Added(String::from("/tags/1"), json!("b")),
+///         ]
+///     );
+/// }
+/// ```
+pub fn diff_values(a: &serde_json::Value, b: &serde_json::Value) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_into(&mut differences, String::new(), a, b);
+    differences
+}
+
+fn diff_into(
+    differences: &mut Vec<Difference>,
+    path: String,
+    a: &serde_json::Value,
+    b: &serde_json::Value,
+) {
+    match (a, b) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                // The `~` and `/` characters of a key are escaped for a JSON Pointer.
+                let path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff_into(differences, path, a, b),
+                    (Some(a), None) => differences.push(Difference::Removed(path, a.clone())),
+                    (None, Some(b)) => differences.push(Difference::Added(path, b.clone())),
+                    (None, None) => {}
+                }
+            }
+        }
+        (serde_json::Value::Array(a), serde_json::Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let path = format!("{}/{}", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(a), Some(b)) => diff_into(differences, path, a, b),
+                    (Some(a), None) => differences.push(Difference::Removed(path, a.clone())),
+                    (None, Some(b)) => differences.push(Difference::Added(path, b.clone())),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ => {
+            if a != b {
+                differences.push(Difference::Changed(path, a.clone(), b.clone()));
+            }
+        }
+    }
+}
+
+/// Converts an error from watching the input files to a generic error.
+fn watch_error(err: notify::Error) -> Error {
+    Error::Generic(format!("Failed to watch the input files: {}", err))
+}
+
+/// Indicates if an input file is gzip compressed based on the `.gz` file extension.
+fn is_gzip(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |e| e.eq_ignore_ascii_case("gz"))
+}
+
+/// Indicates if an input file is LZ4 compressed based on the `.lz4` file extension.
+fn is_lz4(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |e| e.eq_ignore_ascii_case("lz4"))
+}
+
+/// Indicates if an input file is Zstandard compressed based on the `.zst` file extension.
+fn is_zstd(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map_or(false, |e| e.eq_ignore_ascii_case("zst"))
+}
+
+/// Indicates if an input file is compressed based on the `.gz`, `.lz4`, or `.zst` file extension.
+fn is_compressed(path: &Path) -> bool {
+    is_gzip(path) || is_lz4(path) || is_zstd(path)
+}
+
+/// Opens an input file, which is decompressed if it is gzip, LZ4, or Zstandard compressed.
+///
+/// The file is decompressed if `gzip`, `lz4`, or `zstd` is `true`, it has the `.gz`, `.lz4`, or
+/// `.zst` extension, or it starts with the gzip, LZ4, or Zstandard magic bytes, so each input
+/// file is detected separately. The framing is read from the decompressed data. If `follow` is
+/// `true`, then the file is read again after the EOF and the magic bytes are not detected, since
+/// the file may still be empty. A path of `-` is stdin, which is never followed and is only
+/// decompressed with the `gzip`, `lz4`, or `zstd` option, like when there are no input files, so
+/// reading the earlier files does not wait for the start of stdin.
+fn open_input(
+    path: &Path,
+    gzip: bool,
+    lz4: bool,
+    zstd: bool,
+    follow: bool,
+) -> io::Result<Box<dyn BufRead + Send>> {
+    let stdin = path == Path::new(STDIN_PATH);
+    let reader: BufReader<Box<dyn Read + Send>> = if stdin {
+        BufReader::new(Box::new(io::stdin()))
+    } else if follow {
+        BufReader::new(Box::new(Follow(File::open(path)?)))
+    } else {
+        BufReader::new(Box::new(File::open(path)?))
+    };
+    decompress(reader, path, gzip, lz4, zstd, !(follow || stdin))
+}
+
+/// Decompresses an input if it is gzip, LZ4, or Zstandard compressed.
+///
+/// The input is decompressed if `gzip`, `lz4`, or `zstd` is `true`, the path has the `.gz`,
+/// `.lz4`, or `.zst` extension, or `detect` is `true` and the input starts with the gzip, LZ4, or
+/// Zstandard magic bytes.
+fn decompress(
+    mut reader: BufReader<Box<dyn Read + Send>>,
+    path: &Path,
+    gzip: bool,
+    lz4: bool,
+    zstd: bool,
+    detect: bool,
+) -> io::Result<Box<dyn BufRead + Send>> {
+    let (gzip, lz4, zstd) = {
+        let start = if detect { reader.fill_buf()? } else { &[][..] };
+        (
+            gzip || is_gzip(path) || start.starts_with(GZIP_MAGIC),
+            lz4 || is_lz4(path) || start.starts_with(LZ4_MAGIC),
+            zstd || is_zstd(path) || start.starts_with(ZSTD_MAGIC),
+        )
+    };
+    if gzip {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if zstd {
+        Ok(Box::new(BufReader::new(zstd::Decoder::with_buffer(reader)?)))
+    } else if lz4 {
+        Ok(Box::new(BufReader::new(lz4_flex::frame::FrameDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Compresses the output with gzip or Zstandard at the level, if any, or LZ4 if `lz4` is `true`.
+///
+/// The end of the compressed data is written when the encoder is dropped after the last message.
+fn compress_output(
+    writer: Box<dyn Write + Send>,
+    gzip: Option<u32>,
+    lz4: bool,
+    zstd: Option<i32>,
+) -> io::Result<Box<dyn Write + Send>> {
+    Ok(match (gzip, zstd) {
+        (Some(level), _) => Box::new(GzEncoder::new(writer, Compression::new(level))),
+        (None, Some(level)) => Box::new(zstd::Encoder::new(writer, level)?.auto_finish()),
+        (None, None) if lz4 => Box::new(lz4_flex::frame::FrameEncoder::new(writer).auto_finish()),
+        (None, None) => writer,
+    })
+}
+
+/// A reader that waits for more data at the End-of-File (EOF), like `tail -f`.
+///
+/// A read at the EOF is retried after a short delay until more data is appended, so the reader
+/// never ends and a frame that is partially written is completed by a later read.
+struct Follow<R>(R);
+
+impl<R: Read> Read for Follow<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            match self.0.read(buf)? {
+                0 => thread::sleep(FOLLOW_POLL),
+                n => return Ok(n),
+            }
+        }
+    }
+}
+
+/// A background thread that rewrites a progress line with the counts of the producer loop on
+/// stderr until it is dropped.
+struct Progress {
+    done: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Progress {
+    /// Starts writing the progress line every `PROGRESS_INTERVAL`.
+    fn start(counts: Arc<Mutex<ReadCounts>>) -> Progress {
+        let done = Arc::new(AtomicBool::new(false));
+        let thread_done = Arc::clone(&done);
+        let handle = thread::spawn(move || loop {
+            // The line is written once more after the transcoding is done, so the final counts
+            // are shown.
+            let finished = thread_done.load(Ordering::SeqCst);
+            let (frames, bytes) = {
+                let counts = lock_counts(&counts);
+                (counts.frames, counts.bytes)
+            };
+            eprint!("\rMessages: {}, Bytes in: {}", frames, format_size(bytes));
+            if finished {
+                eprintln!();
+                break;
+            }
+            thread::park_timeout(PROGRESS_INTERVAL);
+        });
+        Progress {
+            done,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Formats a number of bytes with a decimal (SI) unit and one decimal place, like `1.2 MB`.
+fn format_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit + 1 < UNITS.len() {
+        size /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Gets the input format from the file name or file extension of an input file.
+///
+/// A `.gz`, `.lz4`, or `.zst` extension is ignored, so the format of `data.json.gz` is JSON. A
+/// `.env` file has no extension, so it is matched by name. `None` is returned if the format is
+/// unknown.
+fn detect_input_format(path: &Path) -> Option<FromFormat> {
+    let path = if is_compressed(path) {
+        Path::new(path.file_stem()?)
+    } else {
+        path
+    };
+    if path.file_name().map_or(false, |n| n == ".env") {
+        // A dotfile has no extension.
+        Some(FromFormat::Dotenv)
+    } else {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(FromFormat::detect_from_extension)
+    }
+}
+
+/// Gets the output format from the file extension of an output file, if the extension is for an
+/// output format.
+///
+/// The `.gz`, `.lz4`, and `.zst` extensions are ignored, so `data.json.gz` is JSON.
+fn detect_output_format(path: &Path) -> Option<ToFormat> {
+    let path = if is_compressed(path) {
+        path.file_stem().map_or(path, Path::new)
+    } else {
+        path
+    };
+    path.extension()
+        .and_then(|e| e.to_str())
+        .and_then(|e| ToFormat::from_str(e).ok())
+}
+
+/// Syncs the directory of a file to the disk, so a rename of the file is durable.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Syncs the directory of a file to the disk, which is not possible on this platform, so a
+/// rename is only as durable as the file system makes it.
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Gets the file extension for the output format.
+fn extension(to: ToFormat) -> &'static str {
+    match to {
+        ToFormat::Bincode => "bincode",
+        ToFormat::Cbor => "cbor",
+        ToFormat::FlexBuffers => "flexbuf",
+        ToFormat::Hjson => "hjson",
+        ToFormat::Json => "json",
+        ToFormat::Markdown => "md",
+        ToFormat::Msgpack => "msgpack",
+        ToFormat::Pickle => "pickle",
+        ToFormat::Resp => "resp",
+        ToFormat::Smile => "sml",
+        ToFormat::Toml => "toml",
+        ToFormat::Tsv => "tsv",
+        ToFormat::Url => "url",
+        ToFormat::Yaml => "yaml",
+    }
+}
+
+/// Writes a table of the formats with the support for reading and writing each format.
+///
+/// The formats are the variants of the `FromFormat` and `ToFormat` types, so the table stays in
+/// sync with the formats that can be used. The reading of a format that can also be written is
+/// checked by writing a sample value and reading it back, so a format that cannot read an
+/// arbitrary value, like Bincode, is listed as limited.
+fn write_formats<W: Write>(mut writer: W) -> Result<()> {
+    let from_names = FromFormat::possible_values()
+        .into_iter()
+        .filter_map(|v| FromFormat::from_str(v).ok())
+        .map(|f| f.to_string());
+    let to_names = ToFormat::possible_values()
+        .into_iter()
+        .filter_map(|v| ToFormat::from_str(v).ok())
+        .map(|t| t.to_string());
+    let mut names: Vec<String> = from_names.chain(to_names).collect();
+    names.sort_by_key(|n| n.to_lowercase());
+    names.dedup();
+    let mut sample = serde_json::Map::new();
+    sample.insert(String::from("count"), serde_json::Value::from(1));
+    sample.insert(String::from("name"), serde_json::Value::from("panser"));
+    let sample = serde_json::Value::Object(sample);
+    writeln!(writer, "{:<11} {:<8} Write", "Format", "Read")?;
+    for name in names {
+        let to = ToFormat::from_str(&name).ok();
+        let read = match (FromFormat::from_str(&name).ok(), to) {
+            (Some(f), Some(t)) => {
+                match serialize(sample.clone(), t).and_then(|d| deserialize(&d, f)) {
+                    Ok(_) => "yes",
+                    Err(_) => "limited",
+                }
+            }
+            (Some(_), None) => "yes",
+            (None, _) => "no",
+        };
+        let write = if to.is_some() { "yes" } else { "no" };
+        writeln!(writer, "{:<11} {:<8} {}", name, read, write)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Converts a string to a delimiter byte.
+///
+/// See the `to_delimiter` function for the notation.
+///
+/// # Errors
+///
+/// A `ParseInt` error will occur if the string cannot be converted to a u8 (byte) value.
+fn to_framing_delimited(s: &String) -> Result<Option<Framing>> {
+    Ok(Some(Framing::Delimited(to_delimiter(s)?)))
+}
+
+/// Converts a string to a delimiter byte.
+///
+/// The string is one of the `\n`, `\t`, `\r`, and `\0` escape sequences, a byte in the
+/// notation of the `to_byte` function, or a single character that is one byte in UTF-8, like `,`.
+/// The notation of the `to_byte` function is tried before a character, so a single hexadecimal
+/// digit, like `0` or `A`, is a number instead of a character.
+///
+/// # Errors
+///
+/// A `Generic` error will occur if the string is a single character that is more than one byte in
+/// UTF-8. A `ParseInt` error will occur if the string is not a single character and cannot be
+/// converted to a u8 (byte) value.
+fn to_delimiter(s: &str) -> Result<u8> {
+    match s {
+        "\\n" => return Ok(b'\n'),
+        "\\t" => return Ok(b'\t'),
+        "\\r" => return Ok(b'\r'),
+        "\\0" => return Ok(0),
+        _ => {}
+    }
+    to_byte(s).or_else(|err| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii() => Ok(c as u8),
+            (Some(c), None) => Err(Error::Generic(format!(
+                "The delimiter '{}' is not a single byte in UTF-8",
+                c
+            ))),
+            _ => Err(err),
+        }
+    })
+}
+
+/// Converts a string to a byte.
+///
+/// A byte can be specified as a string using the following notation: 1010b for binary, 10d for
+/// decimal, 0Ah for hexadecimal, and 012o for octal. All four of these notations will yield the
+/// ASCII newline character. If no radix suffix (`b`, `d`, `h`, or `o`) is present at the end of
+/// the string, then hexadecimal is assumed.
+///
+/// # Errors
+///
+/// A `ParseInt` error will occur if the string cannot be converted to a u8 (byte) value.
+fn to_byte(s: &str) -> Result<u8> {
+    let value = match s.chars().last() {
+        Some('b') => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 2)?,
+        Some('d') => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 10)?,
+        Some('h') => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 16)?,
+        Some('o') => u8::from_str_radix(&s.chars().take(s.len() - 1).collect::<String>(), 8)?,
+        _ => u8::from_str_radix(s, 16)?,
+    };
+    Ok(value)
+}
+
+/// Converts a whitespace- or comma-separated list of numeric strings to bytes based on the radix.
+///
+/// This is the reverse of the `-r,--radix` output. Each numeric string can have a radix prefix,
+/// i.e. `0b`, `0o`, or `0x`, in either case. Decimal does not have a prefix. Empty strings, such
+/// as from trailing separators, are ignored.
+///
+/// # Errors
+///
+/// A `ParseInt` error with the numeric string will occur if a numeric string cannot be converted
+/// to a u8 (byte) value.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate panser;
+///
+/// use panser::Radix;
+///
+/// fn main() {
+///     let bytes = panser::parse_radix_bytes("0x81, 0xA4 62 6F 6F 6C C3 ", Radix::Hexadecimal);
+///     assert_eq!(bytes.unwrap(), vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+/// }
+/// ```
+pub fn parse_radix_bytes(s: &str, radix: Radix) -> Result<Vec<u8>> {
+    let (prefix, base) = match radix {
+        Radix::Binary => ("0b", 2),
+        Radix::Decimal => ("", 10),
+        Radix::Hexadecimal => ("0x", 16),
+        Radix::Octal => ("0o", 8),
+    };
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            let digits = if !prefix.is_empty() && t.to_lowercase().starts_with(prefix) {
+                &t[prefix.len()..]
+            } else {
+                t
+            };
+            u8::from_str_radix(digits, base).map_err(|err| Error::ParseInt(err, Some(t.to_owned())))
+        })
+        .collect()
+}
+
+/// Converts a hex dump to a sequence of bytes.
+///
+/// The dump is forgiving of formatting, so the output of the `xxd` and `hexdump -C` commands, a C
+/// array, and hex pasted from documentation can all be converted. Only the text between the braces
+/// of a C array is used, if any. The offset at the start of a line is ignored if it ends with a
+/// colon, like `00000000:` for `xxd`, or if the line has a `|` text column, like `hexdump -C`,
+/// where the lines without a text column are ignored. The text column of a line is ignored,
+/// which starts at a `|` or two spaces after an offset with a colon. A `0x` prefix is removed
+/// from each byte and all other characters that are not hexadecimal digits are ignored. The
+/// remaining digits are paired into bytes.
+///
+/// # Errors
+///
+/// A `Generic` error will occur if the number of hexadecimal digits is odd.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate panser;
+///
+/// fn main() {
+///     let c = "unsigned char data[] = {0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3};";
+///     let xxd = "00000000: 81a4 626f 6f6c c3                        ..bool.";
+///     let expected = vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3];
+///     assert_eq!(panser::parse_hex_dump(c).unwrap(), expected);
+///     assert_eq!(panser::parse_hex_dump(xxd).unwrap(), expected);
+/// }
+/// ```
+pub fn parse_hex_dump(s: &str) -> Result<Vec<u8>> {
+    let s = match (s.find('{'), s.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &s[start + 1..end],
+        _ => s,
+    };
+    // The `hexdump -C` command writes the offset, the bytes, and a `|` text column for each line
+    // and the total size as an offset on the last line, which is not part of the bytes.
+    let hexdump = s.contains('|');
+    let mut digits = Vec::new();
+    for line in s.lines() {
+        let hex = match line.find('|') {
+            Some(column) => line[..column].trim_start().splitn(2, char::is_whitespace).nth(1),
+            None if hexdump => None,
+            None => match line.find(':') {
+                // The `xxd` command writes the offset with a colon, the bytes, and a text column
+                // after two spaces.
+                Some(colon) if line[..colon].trim().chars().all(|c| c.is_ascii_hexdigit()) => {
+                    let bytes = line[colon + 1..].trim_start();
+                    bytes.find("  ").map(|column| &bytes[..column]).or(Some(bytes))
+                }
+                _ => Some(line),
+            },
+        }
+        .unwrap_or("");
+        let mut chars = hex.chars().peekable();
+        let mut previous = ' ';
+        while let Some(c) = chars.next() {
+            let prefix = c == '0' && !previous.is_ascii_alphanumeric();
+            if prefix && chars.peek().map_or(false, |n| *n == 'x' || *n == 'X') {
+                chars.next();
+                previous = 'x';
+                continue;
+            }
+            if let Some(digit) = c.to_digit(16) {
+                digits.push(digit as u8);
+            }
+            previous = c;
+        }
+    }
+    if digits.len() % 2 != 0 {
+        return Err(Error::Generic(format!(
+            "The hex dump has an odd number of hexadecimal digits ({})",
+            digits.len()
+        )));
+    }
+    Ok(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+/// Converts a comma- or space-separated list of strings to a sequence of bytes.
+///
+/// Each byte in the list uses the same notation as the `to_byte` function, so `02h,03h` and
+/// `02h 03h` are both the ASCII STX and ETX bytes.
+///
+/// # Errors
+///
+/// A `ParseInt` error will occur if any string in the list cannot be converted to a u8 (byte)
+/// value.
+fn to_bytes(s: &str) -> Result<Vec<u8>> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|b| !b.is_empty())
+        .map(to_byte)
+        .collect()
+}
+
+/// The number of bytes for a checksum.
+fn checksum_len(checksum: Checksum) -> usize {
+    match checksum {
+        Checksum::Lrc => 1,
+        Checksum::Crc16 => 2,
+    }
+}
+
+/// Calculates the checksum for STX/ETX framing.
+///
+/// The LRC is the XOR of all bytes. The CRC-16 uses the reflected 0x8005 polynomial with a zero
+/// initial value (CRC-16/ARC) and it is returned in Big Endian (Network Order).
+fn calculate_checksum(checksum: Checksum, data: &[u8]) -> Vec<u8> {
+    match checksum {
+        Checksum::Lrc => vec![data.iter().fold(0, |lrc, b| lrc ^ b)],
+        Checksum::Crc16 => {
+            let mut crc: u16 = 0;
+            for byte in data {
+                crc ^= u16::from(*byte);
+                for _ in 0..8 {
+                    crc = if crc & 0x0001 != 0 {
+                        (crc >> 1) ^ 0xA001
+                    } else {
+                        crc >> 1
+                    };
+                }
+            }
+            let mut buf = vec![0; 2];
+            BigEndian::write_u16(&mut buf, crc);
+            buf
+        }
+    }
+}
+
+/// Deserializes a message and sends it to the consumer loop.
+///
+/// If the `continue_on_error` option is `true` and the message fails to deserialize, then the
+/// error is written to stderr, the `skipped` count is incremented, and the message is dropped
+/// instead of returning the error.
+fn send(
+    buf: &[u8],
+    frame: FrameInfo,
+    from: FromFormat,
+    options: &ReadOptions,
+    counts: &Mutex<ReadCounts>,
+    tx: &Sender,
+) -> Result<()> {
+    let text = options.radix.is_some() || options.hex_dump;
+    let result = match (text, options.transform.as_ref()) {
+        (false, None) => deserialize_message(buf, from, options),
+        (true, transform) => str::from_utf8(buf)
+            .map_err(Error::from)
+            .and_then(|text| match options.radix {
+                Some(r) => Radix::from_str_bytes(text, r),
+                None => parse_hex_dump(text),
+            })
+            .and_then(|bytes| transform_bytes(transform, bytes))
+            .and_then(|bytes| deserialize_message(&bytes, from, options)),
+        (false, transform) => transform_bytes(transform, buf.to_vec())
+            .and_then(|bytes| deserialize_message(&bytes, from, options)),
+    };
+    match result {
+        Ok((format, value)) => {
+            *lock_counts(counts).formats.entry(format.to_string()).or_insert(0) += 1;
+            log_input(options.verbose.as_ref(), frame, format, buf, &value)?;
+            // The consumer loop has stopped, such as with the `stop_after` option, so there is
+            // nothing left to read.
+            if tx.send(Message { frame, value }).is_err() {
+                return Err(Error::Eof);
+            }
+        }
+        Err(e) => match options.errors {
+            Some(ref errors) => {
+                lock_counts(counts).skipped += 1;
+                lock_errors(errors).push(e);
+            }
+            None if options.continue_on_error => {
+                lock_counts(counts).skipped += 1;
+                if !options.quiet {
+                    eprintln!("Skipping message: {}", e);
+                }
+            }
+            None => return Err(e),
+        },
+    }
+    Ok(())
+}
+
+/// Writes the input format, the raw input bytes, and the deserialized value of a message to the
+/// verbose log, if any.
+fn log_input(
+    verbose: Option<&Verbose>,
+    frame: FrameInfo,
+    from: FromFormat,
+    data: &[u8],
+    value: &serde_json::Value,
+) -> Result<()> {
+    if let Some(v) = verbose {
+        let mut writer = v.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(
+            writer,
+            "[{}] input: {}, {} bytes: {}",
+            frame.index,
+            from,
+            data.len(),
+            log_bytes(data, v.level)
+        )?;
+        writeln!(writer, "[{}] value: {}", frame.index, value)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Writes the output format and the size of the serialized output data of a message to the
+/// verbose log, if any. The output bytes are also written for a level of two (2) or more.
+fn log_output(
+    verbose: Option<&Verbose>,
+    frame: FrameInfo,
+    to: ToFormat,
+    data: &[u8],
+) -> Result<()> {
+    if let Some(v) = verbose {
+        let mut writer = v.writer.lock().unwrap_or_else(|e| e.into_inner());
+        write!(writer, "[{}] output: {}, {} bytes", frame.index, to, data.len())?;
+        if v.level > 1 {
+            write!(writer, ": {}", log_bytes(data, v.level))?;
+        }
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Formats the bytes of a message for the verbose log as space-separated hexadecimal.
+///
+/// Only the first 64 bytes are formatted for a level of one (1), followed by `...` if there are
+/// more bytes.
+fn log_bytes(data: &[u8], level: u8) -> String {
+    let shown = if level > 1 {
+        data.len()
+    } else {
+        data.len().min(VERBOSE_BYTES)
+    };
+    let mut hex = data[..shown]
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<String>>()
+        .join(" ");
+    if shown < data.len() {
+        hex.push_str(" ...");
+    }
+    hex
+}
+
+/// Applies the transform, if any, to the raw bytes of a message.
+fn transform_bytes(transform: Option<&Transform>, bytes: Vec<u8>) -> Result<Vec<u8>> {
+    match transform {
+        Some(t) => (t.lock().unwrap_or_else(|e| e.into_inner()))(bytes),
+        None => Ok(bytes),
+    }
+}
+
+/// Deserializes a message with the format from its format tag, if the `format_header` option is
+/// `true`, or the `from` format. The format of the message is returned with the value.
+fn deserialize_message(
+    buf: &[u8],
+    from: FromFormat,
+    options: &ReadOptions,
+) -> Result<(FromFormat, serde_json::Value)> {
+    let (from, data) = if options.format_header {
+        let (tag, data) = buf
+            .split_first()
+            .ok_or_else(|| Error::Generic(String::from("Missing format tag")))?;
+        let from = FromFormat::from_tag(*tag)
+            .ok_or_else(|| Error::Generic(format!("Unknown format tag: {}", tag)))?;
+        (from, data)
+    } else {
+        (from, buf)
+    };
+    if is_binary_input(from) {
+        return Ok((from, deserialize_with(data, from, options.value)?));
+    }
+    // A UTF-16 byte order mark (BOM) takes precedence over the input encoding, like it does for a
+    // web browser. A UTF-8 BOM is removed when the message is deserialized.
+    let (data, encoding) = if data.starts_with(Charset::Utf16Le.bom()) {
+        (&data[2..], Some(Encoding::Utf16Le))
+    } else if data.starts_with(Charset::Utf16Be.bom()) {
+        (&data[2..], Some(Encoding::Utf16Be))
+    } else {
+        (data, options.encoding)
+    };
+    match encoding {
+        Some(encoding) => {
+            let data = decode_charset(data, encoding)?;
+            Ok((from, deserialize_with(&data, from, options.value)?))
+        }
+        None => Ok((from, deserialize_with(data, from, options.value)?)),
+    }
+}
+
+/// Converts text from the encoding to UTF-8.
+///
+/// Since the `Utf8Error` type cannot be created directly, a byte sequence that is not valid for
+/// the encoding is reported as the invalid UTF-8 byte at the same position in the decoded text.
+fn decode_charset(text: &[u8], encoding: Encoding) -> Result<Vec<u8>> {
+    let encoding = match encoding {
+        Encoding::Latin1 => return Ok(text.iter().map(|&b| b as char).collect::<String>().into()),
+        Encoding::Utf16Be => encoding_rs::UTF_16BE,
+        Encoding::Utf16Le => encoding_rs::UTF_16LE,
+        Encoding::Utf8 => encoding_rs::UTF_8,
+        Encoding::Windows1252 => encoding_rs::WINDOWS_1252,
+    };
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let length = decoder
+        .max_utf8_buffer_length_without_replacement(text.len())
+        .ok_or_else(|| Error::Generic(String::from("The input is too large to decode")))?;
+    let mut decoded = vec![0; length];
+    let (result, _, written) = decoder.decode_to_utf8_without_replacement(text, &mut decoded, true);
+    decoded.truncate(written);
+    match result {
+        encoding_rs::DecoderResult::InputEmpty => Ok(decoded),
+        encoding_rs::DecoderResult::Malformed(..) | encoding_rs::DecoderResult::OutputFull => {
+            decoded.push(0xFF);
+            Err(str::from_utf8(&decoded).unwrap_err().into())
+        }
+    }
+}
+
+/// An iterator over the messages of a framed stream.
+///
+/// Each item is a message deserialized from the `from` format to a universal, arbitrary value.
+/// The framing determines how the messages are separated in the stream. If the framing is `None`,
+/// then the entire stream is read as a single message. The iterator ends at the End-of-File (EOF),
+/// including an EOF in the middle of a sized message, so `Error::Eof` is never an item. The
+/// iterator also ends after the first error.
+///
+/// Since the data is framed, messages are read as they are "streamed" into the reader without
+/// having to read the entire stream or file into memory.
+///
+/// # Example
+///
+/// ```rust
+/// use panser::{FrameReader, Framing, FromFormat};
+///
+/// let input = "{\"bool\":true}\n{\"bool\":false}\n".as_bytes();
+/// let values = FrameReader::new(input, FromFormat::Json, Some(Framing::Delimited(b'\n')))
+///     .collect::<panser::Result<Vec<_>>>()
+///     .unwrap();
+/// assert_eq!(values.len(), 2);
+/// assert_eq!(values[1]["bool"], false);
+/// ```
+pub struct FrameReader<R: BufRead> {
+    done: bool,
+    framing: Option<Framing>,
+    from: FromFormat,
+    offset: usize,
+    reader: R,
+}
+
+impl<R: BufRead> FrameReader<R> {
+    /// Creates a new `FrameReader` for the messages of the reader.
+    pub fn new(reader: R, from: FromFormat, framing: Option<Framing>) -> FrameReader<R> {
+        FrameReader {
+            done: false,
+            framing,
+            from,
+            offset: 0,
+            reader,
+        }
+    }
+
+    /// The number of bytes read from the reader, including any framing.
+    ///
+    /// Before the `next_frame` method, or the iterator, is called, this is the offset of the
+    /// start of the next frame within the stream.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Reads the data of the next message without deserializing it.
+    ///
+    /// The framing, i.e. the size, the delimiter, or the STX and ETX bytes and the checksum, is
+    /// removed from the data. Returns `None` at the End-of-File (EOF).
+    ///
+    /// # Errors
+    ///
+    /// A `Generic` error will occur if a STX/ETX framed message does not start with the STX byte,
+    /// does not end with the ETX byte, or the checksum does not match.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.done {
+            return Ok(None);
+        }
+        let result = match self.framing {
+            Some(Framing::Sized) => read_sized(&mut self.reader),
+            Some(Framing::Delimited(delimiter)) => read_delimited(&mut self.reader, delimiter),
+            Some(Framing::StxEtx(checksum)) => read_stxetx(&mut self.reader, checksum),
+            None => {
+                self.done = true;
+                read_unframed(&mut self.reader)
+            }
+        };
+        match result {
+            Ok(Some((frame, bytes_count))) => {
+                self.offset += bytes_count;
+                Ok(Some(frame))
+            }
+            Ok(None) | Err(Error::Eof) => {
+                self.done = true;
+                Ok(None)
+            }
+            Err(e) => {
+                self.done = true;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for FrameReader<R> {
+    type Item = Result<serde_json::Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_frame() {
+            Ok(Some(frame)) => Some(deserialize(&frame, self.from)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Reads a message with an exact length.
+///
+/// This assumes the first four bytes of a message are the total data length encoded as an
+/// unsigned 32-bit integer in Big Endian (Network Order). The message is returned with the number
+/// of bytes read, including the framing, as are the messages of all of the other `read_*`
+/// functions.
+fn read_sized<R: BufRead>(reader: &mut R) -> Result<Option<(Vec<u8>, usize)>> {
+    let mut frame_length_buf = [0; 4];
+    reader
+        .read_exact(&mut frame_length_buf)
+        .map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => Error::Eof,
+            _ => Error::Io(e),
+        })?;
+    let mut frame_length_cursor = Cursor::new(frame_length_buf);
+    let frame_length = frame_length_cursor.read_u32::<BigEndian>()?;
+    let mut buf = vec![0; frame_length as usize];
+    reader.read_exact(&mut buf).map_err(|e| match e.kind() {
+        ErrorKind::UnexpectedEof => Error::Eof,
+        _ => Error::Io(e),
+    })?;
+    let bytes_count = buf.len() + frame_length_buf.len();
+    Ok(Some((buf, bytes_count)))
+}
+
+/// Reads a message until a delimiter is reached.
+///
+/// The delimiter is removed from the message. The final message may not end with the delimiter.
+fn read_delimited<R: BufRead>(reader: &mut R, delimiter: u8) -> Result<Option<(Vec<u8>, usize)>> {
+    let mut buf = Vec::new();
+    let bytes_count = reader
+        .read_until(delimiter, &mut buf)
+        .map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => Error::Eof,
+            _ => Error::Io(e),
+        })?;
+    // If the `read_until` method is at the End-of-File (EOF), then it will return zero for the
+    // number of bytes read and the buffer will be unmodified. In this case, that means an
+    // empty Vec.
+    if buf.is_empty() && bytes_count == 0 {
+        return Ok(None); // EOF
+    }
+    if buf.last() == Some(&delimiter) {
+        buf.pop();
+    }
+    Ok(Some((buf, bytes_count)))
+}
+
+/// Reads a message until an End-of-Text (ETX) byte is reached.
+///
+/// Each message must start with a Start-of-Text (STX) byte and end with an ETX byte. If
+/// a checksum is used, then the checksum immediately follows the ETX byte and it is verified
+/// before the message is returned.
+///
+/// Note, the ETX byte is not escaped within a message, so a message containing the ETX byte will
+/// be mis-framed.
+///
+/// # Errors
+///
+/// A `Generic` error will occur if a message does not start with the STX byte, does not end with
+/// the ETX byte, or the checksum does not match.
+fn read_stxetx<R: BufRead>(
+    reader: &mut R,
+    checksum: Option<Checksum>,
+) -> Result<Option<(Vec<u8>, usize)>> {
+    let mut buf = Vec::new();
+    let bytes_count = reader.read_until(ETX, &mut buf)?;
+    if buf.is_empty() && bytes_count == 0 {
+        return Ok(None); // EOF
+    }
+    if buf.first() != Some(&STX) || buf.last() != Some(&ETX) {
+        return Err(Error::Generic(String::from(
+            "Mis-framed message, expected STX (0x02) at the start and ETX (0x03) at the end",
+        )));
+    }
+    if let Some(c) = checksum {
+        let mut expected = vec![0; checksum_len(c)];
+        reader.read_exact(&mut expected).map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => {
+                Error::Generic(format!("Missing {} checksum after ETX (0x03)", c))
+            }
+            _ => Error::Io(e),
+        })?;
+        if expected != calculate_checksum(c, &buf[1..]) {
+            return Err(Error::Generic(format!("The {} checksum does not match", c)));
+        }
+    }
+    let checksum_count = checksum.map_or(0, checksum_len);
+    Ok(Some((buf[1..buf.len() - 1].to_vec(), bytes_count + checksum_count)))
+}
+
+/// Reads the entire stream, or file, as a single message.
+///
+/// If framing is not used, then the end of the stream or file must be read before transcoding
+/// begins. This is the only real universal way to transcode a non-framed stream.
+fn read_unframed<R: BufRead>(reader: &mut R) -> Result<Option<(Vec<u8>, usize)>> {
+    let mut buf = Vec::new();
+    let bytes_count = reader.read_to_end(&mut buf)?;
+    if buf.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some((buf, bytes_count)))
+    }
+}
+
+/// Locks the counts of the producer loop.
 ///
-/// Determines the appropriating reading paradiagm based on the framing.
+/// The counts are only incremented while locked, so the counts are still usable if a thread
+/// panicked while holding the lock.
+fn lock_counts<'a>(counts: &'a Mutex<ReadCounts>) -> MutexGuard<'a, ReadCounts> {
+    counts.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Locks the collected errors, even if a thread panicked while holding the lock.
+fn lock_errors<'a>(errors: &'a Errors) -> MutexGuard<'a, Vec<Error>> {
+    errors.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The producer loop for reading (input) and writing (output) serialized data.
+///
+/// Each message is read with a `FrameReader` based on the framing and sent to the consumer loop.
 fn read<R: BufRead>(
-    mut reader: R,
+    reader: R,
     from: FromFormat,
     framing: Option<Framing>,
+    options: &ReadOptions,
+    counts: &Mutex<ReadCounts>,
     tx: &Sender,
+    stop: &AtomicBool,
 ) -> Result<()> {
-    if let Some(f) = framing {
-        match f {
-            Framing::Sized => read_exact(reader, from, &tx)?,
-            Framing::Delimited(delimiter) => read_until(reader, from, delimiter, &tx)?,
+    let mut frames = FrameReader::new(reader, from, framing);
+    while !stop.load(Ordering::SeqCst) {
+        let offset = frames.offset();
+        let data = match frames.next_frame()? {
+            Some(d) => d,
+            None => break,
+        };
+        let frame = {
+            let mut counts = lock_counts(counts);
+            counts.bytes += data.len();
+            counts.frames += 1;
+            FrameInfo {
+                index: counts.frames,
+                length: data.len(),
+                offset,
+            }
+        };
+        send(&data, frame, from, options, counts, tx)?;
+    }
+    Ok(())
+}
+
+/// The producer loop for reading datagrams, where each datagram is a message.
+///
+/// The framing is not used, since a datagram is already a complete message. A datagram that is
+/// larger than the maximum size is skipped with a warning, since it may have been truncated when
+/// it was received.
+fn read_datagrams(
+    datagrams: UdpDatagrams,
+    from: FromFormat,
+    max_size: usize,
+    options: &ReadOptions,
+    counts: &Mutex<ReadCounts>,
+    tx: &Sender,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let mut offset = 0;
+    for datagram in datagrams {
+        if stop.load(Ordering::SeqCst) {
+            break;
         }
-    } else {
-        // If framing is not used, then the end of the stream or file must be read before transcoding
-        // begins. This is the only real universal way to transcode a non-framed stream.
-        let mut buf = Vec::new();
-        let bytes_count = reader.read_to_end(&mut buf)?;
-        if bytes_count > 0 {
-            if !buf.is_empty() {
-                tx.send(deserialize(&buf, from)?).unwrap();
+        let (data, peer) = datagram?;
+        if data.len() > max_size {
+            if !options.quiet {
+                eprintln!(
+                    "Warning: Skipping a datagram from {} that is larger than the maximum \
+                     datagram size of {} bytes",
+                    peer, max_size
+                );
             }
+            continue;
         }
+        let frame = {
+            let mut counts = lock_counts(counts);
+            counts.bytes += data.len();
+            counts.frames += 1;
+            FrameInfo {
+                index: counts.frames,
+                length: data.len(),
+                offset,
+            }
+        };
+        offset += data.len();
+        send(&data, frame, from, options, counts, tx)?;
     }
     Ok(())
 }
 
 /// Writes the serialized output data.
 ///
-/// If the `display` is `None`, then the data is written "as-is". This means serialized binary
-/// data, like the MessagePack format, are written as binary data and may not be human readable.
-/// However, if the `display` is a `Radix` value, then the serialized output data is written as
-/// a space-separated list of bytes, where each byte is a string formatted using the radix. This
-/// can be used to visual, or display, serialized binary data in a more human readable fashion.
-fn write_data<W: Write>(mut writer: W, data: &[u8], radix: Option<Radix>) -> Result<()> {
-    if let Some(r) = radix {
-        for byte in data.iter() {
-            match r {
-                Radix::Binary => write!(&mut writer, "{:b} ", byte)?,
-                Radix::Decimal => write!(&mut writer, "{} ", byte)?,
-                Radix::Hexadecimal => write!(&mut writer, "{:0X} ", byte)?,
-                Radix::Octal => write!(&mut writer, "{:o} ", byte)?,
+/// If the `display` has no style or radix, then the data is written "as-is". This means
+/// serialized binary data, like the MessagePack format, are written as binary data and may not be
+/// human readable. However, if the `display` has a `Radix` value, then the serialized output data
+/// is written as a space-separated list of bytes, where each byte is a string formatted using the
+/// radix. This can be used to visual, or display, serialized binary data in a more human readable
+/// fashion. Similarly, if the `display` has a `Style` value, then the serialized output data is
+/// written as source code, or text, in the style. The `kinds` of the bytes, if any, are used to
+/// color the radix output.
+fn write_data<W: Write>(
+    mut writer: W,
+    data: &[u8],
+    kinds: Option<&[Kind]>,
+    display: &Display,
+) -> Result<()> {
+    if let Some(s) = display.style {
+        match s {
+            Style::Base64 => write_base64(&mut writer, data, display)?,
+            Style::CArray => write_c_array(&mut writer, data, display)?,
+            Style::Escaped => write!(&mut writer, "{}", escape_bytes(data))?,
+            Style::Hexdump => write_hexdump(&mut writer, data, display)?,
+            Style::PythonBytes => write!(&mut writer, "b\"{}\"", escape_bytes(data))?,
+            Style::Rust => write_rust(&mut writer, data, display)?,
+            // The `write_frame` function writes the digest with the message number instead.
+            Style::Checksum(d) => writeln!(&mut writer, "{}", hash::to_hex(d, data)?)?,
+        }
+    } else if let Some(r) = display.radix {
+        write_radix(&mut writer, data, kinds, &r, display)?;
+        if display.preview {
+            write!(&mut writer, "\n{}", preview(data))?;
+        }
+    } else {
+        writer.write_all(data)?;
+    }
+    Ok(())
+}
+
+/// Writes the data as a space-separated list of bytes, where each byte is a numeric string based on
+/// the radix.
+///
+/// If the display has a `group` larger than one (1), then the bytes within a group are zero padded
+/// and concatenated behind a single prefix, so `81 A4 62` in groups of two is `81A4 62`, where the
+/// final group of a message may be shorter. If the display has a `width`, then the bytes are
+/// wrapped to lines of `width` bytes and the groups start over on each line. If the kind of each
+/// byte is given, then each byte is painted with the color for its kind.
+fn write_radix<W: Write>(
+    mut writer: W,
+    data: &[u8],
+    kinds: Option<&[Kind]>,
+    style: &RadixStyle,
+    display: &Display,
+) -> Result<()> {
+    let group = display.group.unwrap_or(1).max(1);
+    let width = display.width.unwrap_or(data.len()).max(1);
+    let bytes = data
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            let kind = kinds.and_then(|k| k.get(i)).cloned();
+            (*b, kind.unwrap_or(Kind::Payload))
+        })
+        .collect::<Vec<(u8, Kind)>>();
+    let lines = bytes
+        .chunks(width)
+        .map(|line| {
+            line.chunks(group)
+                .map(|g| {
+                    if group > 1 {
+                        let digits_style = RadixStyle {
+                            pad: true,
+                            prefix: false,
+                            ..*style
+                        };
+                        let digits = g
+                            .iter()
+                            .map(|&(b, k)| highlight::paint(k, &format_byte(b, &digits_style)))
+                            .collect::<String>();
+                        if style.prefix {
+                            format!("{}{} ", radix_prefix(style.radix), digits)
+                        } else {
+                            format!("{} ", digits)
+                        }
+                    } else {
+                        let (b, k) = g[0];
+                        format!("{} ", highlight::paint(k, &format_byte(b, style)))
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>();
+    write!(&mut writer, "{}", lines.join("\n"))?;
+    Ok(())
+}
+
+/// Renders the data as lossy UTF-8 text for previewing the strings within binary data.
+///
+/// Invalid UTF-8 sequences and control characters are replaced with a `.`, so the MessagePack
+/// bytes of `{"bool":true}` are `..bool.`, similar to the `strings` command.
+fn preview(data: &[u8]) -> String {
+    String::from_utf8_lossy(data)
+        .chars()
+        .map(|c| {
+            if c.is_control() || c == std::char::REPLACEMENT_CHARACTER {
+                '.'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Gets the prefix for a numeric string based on the radix.
+fn radix_prefix(radix: Radix) -> &'static str {
+    match radix {
+        Radix::Binary => "0b",
+        Radix::Decimal => "",
+        Radix::Hexadecimal => "0x",
+        Radix::Octal => "0o",
+    }
+}
+
+/// Formats a byte as a numeric string based on the radix style.
+///
+/// This is how each byte is written with the `-r,--radix` option. The digits are zero padded to
+/// the maximum number of digits for a byte of the radix if the style has `pad`, and the prefix for
+/// the radix is prepended if the style has `prefix`. Decimal has no prefix and no letters, so the
+/// `prefix` and `lower` options have no effect for decimal.
+///
+/// # Example
+///
+/// ```rust
+/// use panser::{Radix, RadixStyle};
+///
+/// let style = RadixStyle::new(Radix::Hexadecimal);
+/// assert_eq!(panser::format_byte(10, &style), "A");
+/// let style = RadixStyle {
+///     lower: true,
+///     pad: true,
+///     prefix: true,
+///     ..style
+/// };
+/// assert_eq!(panser::format_byte(10, &style), "0x0a");
+/// ```
+pub fn format_byte(byte: u8, style: &RadixStyle) -> String {
+    let prefix = if style.prefix {
+        radix_prefix(style.radix)
+    } else {
+        ""
+    };
+    let digits = match (style.radix, style.pad) {
+        (Radix::Binary, true) => format!("{:08b}", byte),
+        (Radix::Binary, false) => format!("{:b}", byte),
+        (Radix::Decimal, true) => format!("{:03}", byte),
+        (Radix::Decimal, false) => format!("{}", byte),
+        (Radix::Hexadecimal, true) if style.lower => format!("{:02x}", byte),
+        (Radix::Hexadecimal, true) => format!("{:02X}", byte),
+        (Radix::Hexadecimal, false) if style.lower => format!("{:x}", byte),
+        (Radix::Hexadecimal, false) => format!("{:X}", byte),
+        (Radix::Octal, true) => format!("{:03o}", byte),
+        (Radix::Octal, false) => format!("{:o}", byte),
+    };
+    format!("{}{}", prefix, digits)
+}
+
+/// Writes the data as base64 text.
+///
+/// The display's `base64_url` and `no_padding` options select the alphabet and padding. Similar
+/// to a radix, no newline is written after the text, so the delimiter for delimited-based framing
+/// follows the text directly.
+fn write_base64<W: Write>(mut writer: W, data: &[u8], display: &Display) -> Result<()> {
+    let config = match (display.base64_url, display.no_padding) {
+        (false, false) => base64::STANDARD,
+        (false, true) => base64::STANDARD_NO_PAD,
+        (true, false) => base64::URL_SAFE,
+        (true, true) => base64::URL_SAFE_NO_PAD,
+    };
+    write!(&mut writer, "{}", base64::encode_config(data, config))?;
+    Ok(())
+}
+
+/// Formats the data as an escaped string.
+///
+/// Printable ASCII characters are as-is, except for the double quote and backslash, which are
+/// escaped with a backslash. All other bytes are lowercase hexadecimal escapes, i.e. `\x81`.
+fn escape_bytes(data: &[u8]) -> String {
+    let mut escaped = String::with_capacity(data.len());
+    for &byte in data {
+        match byte {
+            b'"' => escaped.push_str("\\\""),
+            b'\\' => escaped.push_str("\\\\"),
+            b if b.is_ascii_graphic() || b == b' ' => escaped.push(b as char),
+            b => escaped.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    escaped
+}
+
+/// Formats the data as lines of comma-separated hexadecimal literals, i.e. `0x81, 0xa4`.
+///
+/// Each line has at most `width` literals and it is prefixed with the `indent`. The lines do not
+/// have a trailing comma.
+fn hex_literal_lines(data: &[u8], width: usize, indent: &str) -> Vec<String> {
+    data.chunks(width)
+        .map(|chunk| {
+            let literals = chunk
+                .iter()
+                .map(|b| format!("0x{:02x}", b))
+                .collect::<Vec<String>>();
+            format!("{}{}", indent, literals.join(", "))
+        })
+        .collect()
+}
+
+/// Writes the data as a comma-separated list of C-style hexadecimal literals.
+///
+/// The literals are wrapped to the `width` of the display, if any. If the display has the
+/// `c_declaration` option, then the literals are wrapped in an array declaration followed by
+/// a length declaration.
+fn write_c_array<W: Write>(mut writer: W, data: &[u8], display: &Display) -> Result<()> {
+    let width = display
+        .width
+        .unwrap_or(if display.c_declaration { 12 } else { data.len() })
+        .max(1);
+    let indent = if display.c_declaration { "  " } else { "" };
+    let lines = hex_literal_lines(data, width, indent);
+    if display.c_declaration {
+        writeln!(&mut writer, "static const uint8_t data[] = {{")?;
+        writeln!(&mut writer, "{}", lines.join(",\n"))?;
+        writeln!(&mut writer, "}};")?;
+        writeln!(&mut writer, "static const size_t data_len = {};", data.len())?;
+    } else {
+        write!(&mut writer, "{}", lines.join(",\n"))?;
+    }
+    Ok(())
+}
+
+/// Writes the data as a hex dump similar to the `xxd` command.
+///
+/// Each line is an eight-digit hexadecimal offset, the bytes as lowercase hexadecimal in groups
+/// of the display's `group` size, and the bytes as ASCII characters, where non-printable bytes are
+/// replaced with a `.`. The number of bytes per line is the `width` of the display, or sixteen
+/// (16) like `xxd` if no width is used. The offset starts at zero for each message.
+fn write_hexdump<W: Write>(mut writer: W, data: &[u8], display: &Display) -> Result<()> {
+    let width = display.width.unwrap_or(16).max(1);
+    let group = display.group.unwrap_or(2).max(1);
+    let hex_len = width * 2 + (width + group - 1) / group;
+    for (line, chunk) in data.chunks(width).enumerate() {
+        let mut hex = String::with_capacity(hex_len);
+        for (i, byte) in chunk.iter().enumerate() {
+            hex.push_str(&format!("{:02x}", byte));
+            if (i + 1) % group == 0 {
+                hex.push(' ');
             }
         }
+        let ascii = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect::<String>();
+        writeln!(
+            &mut writer,
+            "{:08x}: {:<width$} {}",
+            line * width,
+            hex,
+            ascii,
+            width = hex_len
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a byte-by-byte comparison of the actual output data with the expected reference data.
+///
+/// If the data are the same, then `identical (N bytes)` is written. Otherwise, a line is written
+/// for each byte that differs with the offset, the expected byte, and the actual byte, followed by
+/// a summary. Bytes are written as lowercase hexadecimal like the `hexdump` style, and a missing
+/// byte, because the data have different lengths, is written as `--`. If `color` is `true`, then
+/// the expected bytes are red and the actual bytes are green. Returns the number of bytes that
+/// differ.
+fn write_diff<W: Write>(
+    mut writer: W,
+    expected: &[u8],
+    actual: &[u8],
+    color: bool,
+) -> Result<usize> {
+    if expected == actual {
+        writeln!(&mut writer, "identical ({} bytes)", actual.len())?;
+        writer.flush()?;
+        return Ok(0);
+    }
+    let style = RadixStyle {
+        lower: true,
+        pad: true,
+        ..RadixStyle::new(Radix::Hexadecimal)
+    };
+    let show = |byte: Option<&u8>, kind: Kind, width: usize| {
+        let text = byte.map_or_else(|| String::from("--"), |&b| format_byte(b, &style));
+        let text = format!("{:<width$}", text, width = width);
+        if color {
+            highlight::paint(kind, &text)
+        } else {
+            text
+        }
+    };
+    writeln!(&mut writer, "Offset    Expected  Actual")?;
+    let mut differences = 0;
+    for offset in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(offset), actual.get(offset));
+        if e != a {
+            differences += 1;
+            writeln!(
+                &mut writer,
+                "{:08x}  {}  {}",
+                offset,
+                show(e, Kind::Expected, 8),
+                show(a, Kind::Actual, 0)
+            )?;
+        }
+    }
+    writeln!(
+        &mut writer,
+        "{} byte(s) differ, expected {} bytes, actual {} bytes",
+        differences,
+        expected.len(),
+        actual.len()
+    )?;
+    writer.flush()?;
+    Ok(differences)
+}
+
+/// Writes the data as a Rust `vec!` macro, or slice, of hexadecimal byte literals.
+///
+/// If the display has a `width`, then the literals are wrapped and indented like `rustfmt`
+/// would. Each message is written as one literal followed by a newline. If the display has the
+/// `rust_slice` option, then a `&[...]` slice is written instead of a `vec![...]` macro.
+fn write_rust<W: Write>(mut writer: W, data: &[u8], display: &Display) -> Result<()> {
+    let (open, close) = if display.rust_slice {
+        ("&[", "]")
+    } else {
+        ("vec![", "]")
+    };
+    if let Some(width) = display.width {
+        writeln!(&mut writer, "{}", open)?;
+        for line in hex_literal_lines(data, width.max(1), "    ") {
+            writeln!(&mut writer, "{},", line)?;
+        }
+        writeln!(&mut writer, "{}", close)?;
     } else {
-        writer.write(&data)?;
+        writeln!(
+            &mut writer,
+            "{}{}{}",
+            open,
+            hex_literal_lines(data, data.len().max(1), "").join(""),
+            close
+        )?;
     }
     Ok(())
 }
 
+/// A writer for the messages of a framed stream.
+///
+/// Each value is serialized to a format and written with the framing, so the output is the same
+/// as the output of the CLI with the same options. If the framing is `None`, then the messages
+/// are written back to back. If a radix is used, then each message is written as
+/// a space-separated list of bytes formatted with the radix style, similar to the `-r,--radix`
+/// option.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate panser;
+/// extern crate serde_json;
+///
+/// use panser::{FrameWriter, Framing, ToFormat};
+///
+/// # fn main() {
+/// let value = serde_json::from_str("{\"bool\":true}").unwrap();
+/// let mut output = Vec::new();
+/// {
+///     let mut writer = FrameWriter::new(&mut output, Some(Framing::Sized));
+///     writer.write_value(&value, ToFormat::Msgpack).unwrap();
+/// }
+/// assert_eq!(output, vec![0x00, 0x00, 0x00, 0x07, 0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+/// # }
+/// ```
+pub struct FrameWriter<W: Write> {
+    display: Display,
+    framing: Option<Framing>,
+    messages: usize,
+    writer: W,
+}
+
+impl<W: Write> FrameWriter<W> {
+    /// Creates a new `FrameWriter` for writing messages to the writer with the framing.
+    pub fn new(writer: W, framing: Option<Framing>) -> FrameWriter<W> {
+        FrameWriter {
+            display: Display::default(),
+            framing,
+            messages: 0,
+            writer,
+        }
+    }
+
+    /// Writes each message as a space-separated list of bytes formatted with the radix style.
+    ///
+    /// If `None`, which is the default, then the messages are written as serialized.
+    pub fn radix(mut self, radix: Option<RadixStyle>) -> Self {
+        self.display.radix = radix;
+        self
+    }
+
+    /// Serializes the value to the `to` format and writes it as a framed message.
+    ///
+    /// The writer is flushed after each message.
+    pub fn write_value(&mut self, value: &serde_json::Value, to: ToFormat) -> Result<()> {
+        let encoded_data = serialize_value(value, to)?;
+        self.messages += 1;
+        write_frame(
+            &mut self.writer,
+            &encoded_data,
+            self.messages,
+            to,
+            self.framing,
+            &WriteOptions::default(),
+            &self.display,
+        )
+    }
+
+    /// Unwraps this `FrameWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
 /// The consumer loop for the producer-consumer architecture for reading (input) and writing
 /// (output).
 ///
 /// The consumer loop listens for serialized messages from the producer (input) loop. When
 /// a message is received, the serialized input data is transcoded based on the `from` format to
 /// the serialized output data based on the `to` format. After transcoding, the serialized output
-/// data is written to the output with the `writer` based on the `framing`.
+/// data is written to the output with the `writer` based on the `framing`. If the `options` have
+/// more than one job, then the messages are transcoded in parallel, see the `write_parallel`
+/// function. If the `options` have the `newline` option, then a newline is written after all of
+/// the messages. The counts of the serialized output messages are returned.
 ///
-/// The `display` value is ignored for writing the delimiter if delimited-based framing is used.
-/// This makes it easier to create an interactive console with the application.
+/// If a message fails to transcode or write, then the writer is flushed before the error is
+/// returned, so the messages written before the error are not lost.
+///
+/// The framed message, i.e. the size, STX/ETX bytes, and checksum along with the serialized
+/// output data, is displayed as a whole. The `display` value is ignored for writing the delimiter
+/// if delimited-based framing is used. This makes it easier to create an interactive console with
+/// the application.
 fn write<W: Write>(
     mut writer: W,
     to: ToFormat,
     framing: Option<Framing>,
-    radix: Option<Radix>,
+    options: &WriteOptions,
+    display: &Display,
+    rx: Receiver,
+) -> Result<WriteCounts> {
+    let mut counts = WriteCounts::default();
+    let result = if options.jobs > 1 {
+        write_parallel(&mut writer, to, framing, options, display, rx, &mut counts)
+    } else {
+        write_serial(&mut writer, to, framing, options, display, rx, &mut counts)
+    };
+    if let Err(e) = result {
+        // The messages written before the error are flushed, so the output is not truncated. An
+        // error flushing the output is ignored in favor of the error that stopped the writing.
+        let _ = writer.flush();
+        return Err(e);
+    }
+    if options.newline {
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(counts)
+}
+
+/// The consumer loop without worker threads, where each message is transcoded and written before
+/// the next message is received.
+fn write_serial<W: Write>(
+    mut writer: W,
+    to: ToFormat,
+    framing: Option<Framing>,
+    options: &WriteOptions,
+    display: &Display,
+    rx: Receiver,
+    counts: &mut WriteCounts,
+) -> Result<()> {
+    while let Ok(message) = rx.recv() {
+        counts.received += 1;
+        if (counts.received - 1) % options.every != 0 {
+            continue;
+        }
+        let result = encode(message.value, to, options)
+            .and_then(|data| transform_bytes(options.transform.as_ref(), data));
+        let encoded_data = match (result, options.errors.as_ref()) {
+            (Ok(data), _) => data,
+            (Err(e), Some(errors)) => {
+                lock_errors(errors).push(e);
+                continue;
+            }
+            (Err(e), None) => return Err(e),
+        };
+        log_output(options.verbose.as_ref(), message.frame, to, &encoded_data)?;
+        counts.add(encoded_data.len(), options.stats);
+        let index = counts.messages;
+        if display.annotate {
+            write_annotation(&mut writer, message.frame)?;
+        }
+        write_line_number(&mut writer, message.frame, options, display)?;
+        write_frame(&mut writer, &encoded_data, index, to, framing, options, display)?;
+        if let Some(style) = display.tee_radix {
+            write_tee(&encoded_data, message.frame, framing, &style, display)?;
+        }
+        if options.stop_after == Some(index) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The consumer loop with a pool of worker threads for transcoding.
+///
+/// Each message is given a sequence number as it is received by one of the workers. The workers
+/// transcode the messages in parallel and send the serialized output data back with the sequence
+/// number. The serialized output data is held until all of the previous messages are written, so
+/// the output is in the same order as the input.
+fn write_parallel<W: Write>(
+    mut writer: W,
+    to: ToFormat,
+    framing: Option<Framing>,
+    options: &WriteOptions,
+    display: &Display,
     rx: Receiver,
+    counts: &mut WriteCounts,
 ) -> Result<()> {
-    loop {
-        if let Ok(data) = rx.recv() {
-            let encoded_data = serialize(data, to)?;
-            if let Some(f) = framing {
-                match f {
-                    Framing::Sized => {
-                        let mut frame_length = [0; 4];
-                        BigEndian::write_u32(&mut frame_length, encoded_data.len() as u32);
-                        write_data(&mut writer, &frame_length, radix)?;
+    let (results_tx, results_rx) = mpsc::channel::<(usize, FrameInfo, Result<Vec<u8>>)>();
+    // The receiver and the next sequence number are locked together, so the sequence numbers are
+    // in the same order as the messages. The number of messages received is shared separately, so
+    // it can be read without waiting for a worker that is waiting on the receiver.
+    let work = Arc::new(Mutex::new((rx, 0)));
+    let received = Arc::new(AtomicUsize::new(0));
+    let mut workers = Vec::with_capacity(options.jobs);
+    for _ in 0..options.jobs {
+        let work = Arc::clone(&work);
+        let received = Arc::clone(&received);
+        let results_tx = results_tx.clone();
+        let options = options.clone();
+        workers.push(thread::spawn(move || loop {
+            let (sequence, message) = {
+                let mut work = match work.lock() {
+                    Ok(w) => w,
+                    Err(_) => break,
+                };
+                let message = loop {
+                    match work.0.recv() {
+                        Ok(message) => {
+                            if received.fetch_add(1, Ordering::SeqCst) % options.every == 0 {
+                                break Some(message);
+                            }
+                        }
+                        Err(_) => break None,
                     }
-                    _ => {}
+                };
+                match message {
+                    Some(message) => {
+                        work.1 += 1;
+                        (work.1 - 1, message)
+                    }
+                    None => break,
                 }
+            };
+            let result = encode(message.value, to, &options);
+            if results_tx.send((sequence, message.frame, result)).is_err() {
+                break;
             }
-            write_data(&mut writer, &encoded_data, radix)?;
-            if let Some(f) = framing {
-                match f {
-                    Framing::Delimited(delimiter) => {
-                        // The delimiter should _not_ be written as a string if there is some
-                        // display value. An ASCII newline character ('\n') is often used as
-                        // a delimiter to create an interactive console. If the newline character
-                        // is written as a string byte, then the cursor will not appear after
-                        // space-separated list of bytes of the output. It is awkward looking. This
-                        // ensures the delimiter is always written as binary data and the cursor is
-                        // printed on the following line of the output when creating an interactive
-                        // console.
-                        writer.write(&[delimiter; 1])?;
-                    }
-                    _ => {}
+        }));
+    }
+    // The workers hold the only remaining senders, so the results channel is closed once all of
+    // the workers are done.
+    drop(results_tx);
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    for (sequence, frame, result) in results_rx {
+        pending.insert(sequence, (frame, result));
+        while let Some((frame, result)) = pending.remove(&next) {
+            // The output transform is applied here, instead of by the workers, so it sees the
+            // messages in the same order as the input.
+            let result = result.and_then(|data| transform_bytes(options.transform.as_ref(), data));
+            let encoded_data = match (result, options.errors.as_ref()) {
+                (Ok(data), _) => data,
+                (Err(e), Some(errors)) => {
+                    lock_errors(errors).push(e);
+                    next += 1;
+                    continue;
                 }
+                (Err(e), None) => return Err(e),
+            };
+            log_output(options.verbose.as_ref(), frame, to, &encoded_data)?;
+            counts.add(encoded_data.len(), options.stats);
+            let index = counts.messages;
+            if display.annotate {
+                write_annotation(&mut writer, frame)?;
+            }
+            write_line_number(&mut writer, frame, options, display)?;
+            write_frame(&mut writer, &encoded_data, index, to, framing, options, display)?;
+            if let Some(style) = display.tee_radix {
+                write_tee(&encoded_data, frame, framing, &style, display)?;
             }
-            writer.flush()?;
+            if options.stop_after == Some(index) {
+                // The workers may be waiting on a read that is blocked, so they are not joined.
+                counts.received = received.load(Ordering::SeqCst);
+                return Ok(());
+            }
+            next += 1;
+        }
+    }
+    for worker in workers {
+        worker.join()?;
+    }
+    counts.received = received.load(Ordering::SeqCst);
+    Ok(())
+}
+
+/// Writes a comment-style header line with the metadata of the input frame of a message, such as
+/// `# frame 17, 203 bytes, offset 0x1A40`.
+fn write_annotation<W: Write>(mut writer: W, frame: FrameInfo) -> Result<()> {
+    writeln!(
+        &mut writer,
+        "# frame {}, {} bytes, offset 0x{:X}",
+        frame.index, frame.length, frame.offset
+    )?;
+    Ok(())
+}
+
+/// Writes the number of the input frame of a message followed by a colon and a space, such as
+/// `4721: `, if the `line_numbers` option is `true`.
+///
+/// The input frame is numbered, instead of the output message, so the number still matches the
+/// input with the `every` and `continue_on_error` options. Nothing is written for the checksum
+/// style, which already numbers each line.
+fn write_line_number<W: Write>(
+    mut writer: W,
+    frame: FrameInfo,
+    options: &WriteOptions,
+    display: &Display,
+) -> Result<()> {
+    match display.style {
+        Some(Style::Checksum(_)) => {}
+        _ if options.line_numbers => write!(&mut writer, "{}: ", frame.index)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Transcodes a message, i.e. applies any transformations and serializes the value to the `to`
+/// format.
+fn encode(mut data: serde_json::Value, to: ToFormat, options: &WriteOptions) -> Result<Vec<u8>> {
+    if let Some(pointer) = options.pointer.as_ref() {
+        data = data.pointer_mut(pointer).map(serde_json::Value::take).ok_or_else(|| {
+            Error::Generic(format!("The JSON Pointer '{}' does not resolve to a value", pointer))
+        })?;
+    }
+    if let Some(policy) = options.null_policy {
+        data = transform::apply_null_policy(data, policy);
+    }
+    if options.coerce_scalars {
+        data = transform::coerce_scalars(data);
+    } else if options.coerce_numbers {
+        data = transform::coerce_numbers(data);
+    }
+    if options.normalize_numbers {
+        data = if options.force_float {
+            transform::force_float(data)
         } else {
-            break;
+            transform::normalize_numbers(data)
+        };
+    }
+    // Every output format is serialized with the held non-finite floats restored, so they are
+    // never written as strings.
+    let mut encoded = match (options.indent.as_ref(), to) {
+        (_, ToFormat::Msgpack) if options.restore_ext => serialize_ext(&data, options.allow_nan)?,
+        (indent, _) if options.allow_nan => {
+            serialize_non_finite(&data, to, indent.map(|i| i.as_slice()))?
         }
+        (Some(indent), ToFormat::Hjson) | (Some(indent), ToFormat::Json)
+            if options.base64_to_bytes =>
+        {
+            serialize_pretty(&Base64Bytes(&data), indent)?
+        }
+        (_, _) if options.base64_to_bytes => serialize_any(&Base64Bytes(&data), to)?,
+        (Some(indent), ToFormat::Hjson) | (Some(indent), ToFormat::Json) => {
+            serialize_pretty(&data, indent)?
+        }
+        _ => serialize(data, to)?,
+    };
+    if (options.ascii_only || options.escape_html) && is_json(to) {
+        encoded = escape_json(&encoded, options.ascii_only, options.escape_html)?;
+    }
+    if options.trim_newline && is_text(to) && encoded.last() == Some(&b'\n') {
+        encoded.pop();
+    }
+    if options.bom || options.charset.is_some() {
+        encoded = encode_charset(&encoded, options.charset.unwrap_or(Charset::Utf8), options.bom)?;
+    }
+    Ok(encoded)
+}
+
+/// Escapes the characters of serialized JSON as `\uXXXX` escape sequences.
+///
+/// If `non_ascii` is `true`, then every non-ASCII character is escaped, where a character outside
+/// the Basic Multilingual Plane is escaped as a surrogate pair. If `html` is `true`, then the `<`,
+/// `>`, and `&` characters are escaped. The JSON is escaped after it is serialized instead of with
+/// a custom `serde_json` formatter, so it works with the compact, pretty, and non-finite literal
+/// serializers alike. None of the escaped characters are part of the JSON syntax, so they can only
+/// be in a string or a key and every one of them in the text is escaped.
+fn escape_json(json: &[u8], non_ascii: bool, html: bool) -> Result<Vec<u8>> {
+    let text = str::from_utf8(json)?;
+    let mut escaped = String::with_capacity(text.len());
+    let mut units = [0; 2];
+    for c in text.chars() {
+        match c {
+            '<' if html => escaped.push_str("\\u003C"),
+            '>' if html => escaped.push_str("\\u003E"),
+            '&' if html => escaped.push_str("\\u0026"),
+            c if non_ascii && !c.is_ascii() => {
+                for unit in c.encode_utf16(&mut units).iter() {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            c => escaped.push(c),
+        }
+    }
+    Ok(escaped.into_bytes())
+}
+
+/// Converts serialized UTF-8 text to the charset and prepends the byte order mark (BOM) of the
+/// charset if `bom` is `true`.
+fn encode_charset(text: &[u8], charset: Charset, bom: bool) -> Result<Vec<u8>> {
+    let text = str::from_utf8(text)?;
+    let mut encoded = Vec::with_capacity(text.len() * 2 + 3);
+    if bom {
+        encoded.extend_from_slice(charset.bom());
+    }
+    match charset {
+        Charset::Utf8 => encoded.extend_from_slice(text.as_bytes()),
+        Charset::Utf16Be => {
+            for unit in text.encode_utf16() {
+                encoded.extend_from_slice(&unit.to_be_bytes());
+            }
+        }
+        Charset::Utf16Le => {
+            for unit in text.encode_utf16() {
+                encoded.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+    }
+    Ok(encoded)
+}
+
+/// Indicates if the input format is a binary format.
+fn is_binary_input(from: FromFormat) -> bool {
+    match from {
+        FromFormat::Bincode
+        | FromFormat::Cbor
+        | FromFormat::FlexBuffers
+        | FromFormat::Msgpack
+        | FromFormat::Pickle
+        | FromFormat::Resp
+        | FromFormat::Smile => true,
+        _ => false,
+    }
+}
+
+/// Indicates if the output format is a binary format.
+fn is_binary(to: ToFormat) -> bool {
+    match to {
+        ToFormat::Bincode
+        | ToFormat::Cbor
+        | ToFormat::FlexBuffers
+        | ToFormat::Msgpack
+        | ToFormat::Pickle
+        | ToFormat::Resp
+        | ToFormat::Smile => true,
+        _ => false,
+    }
+}
+
+/// Indicates if the output format is written as JSON, i.e. JSON or Hjson.
+fn is_json(to: ToFormat) -> bool {
+    match to {
+        ToFormat::Hjson | ToFormat::Json => true,
+        _ => false,
+    }
+}
+
+/// Indicates if the output format is a text format that may end with a newline.
+fn is_text(to: ToFormat) -> bool {
+    match to {
+        ToFormat::Hjson | ToFormat::Json | ToFormat::Toml | ToFormat::Yaml => true,
+        _ => false,
+    }
+}
+
+/// Adds the framing, except for a delimiter, to the serialized output data of a message.
+///
+/// A delimiter is written after the prefix and suffix, so it is not part of the frame.
+fn frame_data(encoded_data: &[u8], framing: Option<Framing>) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(encoded_data.len() + 4);
+    if let Some(f) = framing {
+        match f {
+            Framing::Sized => {
+                let mut frame_length = [0; 4];
+                BigEndian::write_u32(&mut frame_length, encoded_data.len() as u32);
+                frame.extend_from_slice(&frame_length);
+            }
+            Framing::StxEtx(_) => frame.push(STX),
+            _ => {}
+        }
+    }
+    frame.extend_from_slice(encoded_data);
+    if let Some(Framing::StxEtx(checksum)) = framing {
+        frame.push(ETX);
+        if let Some(c) = checksum {
+            frame.extend(calculate_checksum(c, &frame[1..]));
+        }
+    }
+    frame
+}
+
+/// Writes the framed serialized output data of a message with the radix style to stderr.
+///
+/// The frame annotation is written before the bytes and a newline after. The text for the message
+/// is buffered and written all at once, so it is not interleaved with other messages to stderr,
+/// like errors.
+fn write_tee(
+    encoded_data: &[u8],
+    frame: FrameInfo,
+    framing: Option<Framing>,
+    style: &RadixStyle,
+    display: &Display,
+) -> Result<()> {
+    let mut buf = Vec::new();
+    write_annotation(&mut buf, frame)?;
+    write_radix(&mut buf, &frame_data(encoded_data, framing), None, style, display)?;
+    buf.push(b'\n');
+    let stderr = io::stderr();
+    let mut handle = stderr.lock();
+    handle.write_all(&buf)?;
+    handle.flush()?;
+    Ok(())
+}
+
+/// Writes the serialized output data of a message with the framing, prefix, and suffix.
+///
+/// The `index` is the message number, which starts at one (1). If the display has a checksum,
+/// then the message number and the digest of the serialized output data are written to the
+/// checksum writer, if any.
+/// If the display has the checksum style, then the message number and the digest are written
+/// instead of the framed message.
+fn write_frame<W: Write>(
+    mut writer: W,
+    encoded_data: &[u8],
+    index: usize,
+    to: ToFormat,
+    framing: Option<Framing>,
+    options: &WriteOptions,
+    display: &Display,
+) -> Result<()> {
+    if let (Some(d), Some(w)) = (display.checksum, display.checksum_writer.as_ref()) {
+        let mut checksum_writer = w.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(checksum_writer, "{} {}", index, hash::to_hex(d, encoded_data)?)?;
+        checksum_writer.flush()?;
+    }
+    if display.cbor_diag {
+        // The notation replaces the data, so the framing, prefix, and suffix are not written.
+        writeln!(&mut writer, "{}", cbor_diag::to_string(encoded_data)?)?;
+        writer.flush()?;
+        return Ok(());
+    }
+    if let Some(Style::Checksum(d)) = display.style {
+        // The digest replaces the data, so the framing, prefix, and suffix are not written.
+        writeln!(&mut writer, "{} {}", index, hash::to_hex(d, encoded_data)?)?;
+        writer.flush()?;
+        return Ok(());
+    }
+    let frame = frame_data(encoded_data, framing);
+    let kinds = if display.color {
+        let mut kinds = Vec::with_capacity(frame.len());
+        match framing {
+            Some(Framing::Sized) => kinds.extend_from_slice(&[Kind::Length; 4]),
+            Some(Framing::StxEtx(_)) => kinds.push(Kind::Marker),
+            _ => {}
+        }
+        kinds.extend(highlight::kinds(encoded_data, to));
+        if let Some(Framing::StxEtx(_)) = framing {
+            kinds.push(Kind::Marker);
+        }
+        // The checksum, if any, is not colored.
+        kinds.resize(frame.len(), Kind::Payload);
+        Some(kinds)
+    } else {
+        None
+    };
+    writer.write_all(&options.prefix)?;
+    write_data(&mut writer, &frame, kinds.as_ref().map(|k| &k[..]), display)?;
+    writer.write_all(&options.suffix)?;
+    if let Some(Framing::Delimited(delimiter)) = framing {
+        // The delimiter should _not_ be written as a string if there is some display
+        // value. An ASCII newline character ('\n') is often used as a delimiter to create
+        // an interactive console. If the newline character is written as a string byte,
+        // then the cursor will not appear after space-separated list of bytes of the
+        // output. It is awkward looking. This ensures the delimiter is always written as
+        // binary data and the cursor is printed on the following line of the output when
+        // creating an interactive console.
+        writer.write_all(&[delimiter; 1])?;
+    }
+    if options.output_newline {
+        writer.write_all(b"\n")?;
     }
+    writer.flush()?;
     Ok(())
 }
@@ -0,0 +1,194 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chaining multiple transcoding steps into a single pipeline.
+//!
+//! A pipeline is a declarative list of steps, such as transcoding, filtering, and sorting keys,
+//! that are executed in order on a single input. The input is deserialized with the first
+//! `Step::Transcode`, all other steps are applied to the deserialized value, and the value is
+//! serialized to the output format of the last `Step::Transcode`.
+
+use serde_json::{Map, Value};
+
+use super::{deserialize, serialize, Error, FromFormat, Result, ToFormat};
+
+/// A step of a pipeline.
+pub enum Step {
+    /// Deserializes the data from a format and serializes it to another format.
+    ///
+    /// If a value has already been deserialized by an earlier step, then it is serialized to the
+    /// output format of the earlier step before it is deserialized with the `from` format.
+    Transcode {
+        /// The format of the data for this step.
+        from: FromFormat,
+        /// The format of the data after this step.
+        to: ToFormat,
+    },
+    /// Keeps the elements of an array for which the predicate is `true`.
+    ///
+    /// If the value is not an array, then the value itself is tested, and the output of the
+    /// pipeline is empty if the predicate is `false`.
+    Filter(Box<dyn Fn(&Value) -> bool + Send>),
+    /// Replaces the value with the value returned by the function.
+    Map(Box<dyn Fn(Value) -> Value + Send>),
+    /// Recursively sorts the keys of every object.
+    SortKeys,
+    /// Wraps the value in an array with one element.
+    WrapArray,
+    /// Replaces an array with exactly one element with the element. Any other value is unchanged.
+    UnwrapArray,
+    /// Replaces the value with a nested value.
+    ///
+    /// If the string starts with a `/`, then it is a JSON Pointer, such as `/data/0/name`.
+    /// Otherwise, it is a key of an object. It is an error if the nested value does not exist.
+    Extract(String),
+}
+
+/// A list of steps that are executed in order on a single input.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate panser;
+///
+/// use panser::{pipeline, FromFormat, Pipeline, Step, ToFormat};
+///
+/// fn main() {
+///     let pipeline = Pipeline::new()
+///         .step(Step::Transcode { from: FromFormat::Yaml, to: ToFormat::Json })
+///         .step(Step::Extract(String::from("servers")))
+///         .step(Step::SortKeys);
+///     let output = pipeline::run(b"servers:\n  port: 80\n  host: a\n", &pipeline).unwrap();
+///     assert_eq!(output, b"{\"host\":\"a\",\"port\":80}".to_vec());
+/// }
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Step>,
+}
+
+impl Pipeline {
+    /// Creates a new pipeline without any steps.
+    ///
+    /// A pipeline without any steps copies the input to the output.
+    pub fn new() -> Self {
+        Pipeline { steps: Vec::new() }
+    }
+
+    /// Appends a step to the end of the pipeline.
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// The steps of the pipeline in the order they are executed.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+/// Executes the steps of a pipeline in order on the input.
+///
+/// The first step that is not a `Step::Transcode` must come after a `Step::Transcode`, because
+/// the format of the input is unknown until then. If there are no `Step::Transcode` steps, then
+/// the input is copied to the output.
+pub fn run(input: &[u8], pipeline: &Pipeline) -> Result<Vec<u8>> {
+    let mut data = input.to_vec();
+    let mut current: Option<(Value, ToFormat)> = None;
+    for step in &pipeline.steps {
+        if let Step::Transcode { from, to } = *step {
+            if let Some((value, format)) = current.take() {
+                data = serialize(value, format)?;
+            }
+            current = Some((deserialize(&data, from)?, to));
+            continue;
+        }
+        let (value, format) = current.take().ok_or_else(|| {
+            Error::Generic(String::from(
+                "A transcode step is required before any other step of a pipeline",
+            ))
+        })?;
+        match apply(step, value)? {
+            Some(value) => current = Some((value, format)),
+            None => return Ok(Vec::new()),
+        }
+    }
+    match current {
+        Some((value, format)) => serialize(value, format),
+        None => Ok(data),
+    }
+}
+
+/// Applies a step other than `Step::Transcode` to a value.
+///
+/// `None` is returned if the value is removed by a filter.
+fn apply(step: &Step, value: Value) -> Result<Option<Value>> {
+    Ok(Some(match *step {
+        Step::Transcode { .. } => value,
+        Step::Filter(ref predicate) => match value {
+            Value::Array(values) => {
+                Value::Array(values.into_iter().filter(|v| predicate(v)).collect())
+            }
+            v => {
+                if predicate(&v) {
+                    v
+                } else {
+                    return Ok(None);
+                }
+            }
+        },
+        Step::Map(ref f) => f(value),
+        Step::SortKeys => sort_keys(value),
+        Step::WrapArray => Value::Array(vec![value]),
+        Step::UnwrapArray => match value {
+            Value::Array(mut values) => {
+                if values.len() == 1 {
+                    values.remove(0)
+                } else {
+                    Value::Array(values)
+                }
+            }
+            v => v,
+        },
+        Step::Extract(ref path) => {
+            let extracted = if path.starts_with('/') {
+                value.pointer(path).cloned()
+            } else {
+                value.get(path.as_str()).cloned()
+            };
+            extracted.ok_or_else(|| Error::Generic(format!("The '{}' value does not exist", path)))?
+        }
+    }))
+}
+
+/// Recursively sorts the keys of every object.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        Value::Object(values) => {
+            let mut entries: Vec<(String, Value)> = values.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect::<Map<String, Value>>(),
+            )
+        }
+        v => v,
+    }
+}
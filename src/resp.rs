@@ -0,0 +1,304 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serialization and deserialization of a universal, arbitrary value as the [RESP] wire format of
+//! Redis.
+//!
+//! Every value is written with the RESP3 types, where strings are bulk strings, so a command like
+//! `["GET", "key"]` is written the same as a Redis client would send it. Objects are written as
+//! maps with bulk string keys, and an object with an `error` key is written as a map, not an
+//! error.
+//!
+//! Reading supports both RESP2 and RESP3, but some types cannot be represented by a universal,
+//! arbitrary value:
+//!
+//! - A simple or bulk error is read as an object with the message as the `error` key.
+//! - A bulk or verbatim string that is not valid UTF-8 is read as an array of byte values.
+//! - A map key that is not a string is read as the JSON text of the key.
+//! - A set and a push are read as arrays, and the attributes of a value are skipped.
+//! - A big number that does not fit in 64 bits is read as a 64-bit float, so precision may be
+//!   lost.
+//! - A non-finite double, i.e. `nan` and infinity, and the null bulk string and array of RESP2 are
+//!   read as a null.
+//!
+//! [RESP]: https://redis.io/docs/reference/protocol-spec/
+
+use serde_json::{Map, Number, Value};
+use std::str;
+
+use super::{Error, Result};
+
+/// The end of every line of a RESP value.
+const CRLF: &[u8; 2] = b"\r\n";
+
+/// The maximum number of nested aggregates, like arrays and maps, which keeps a malicious value
+/// from overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+const SIMPLE_STRING: u8 = b'+';
+const SIMPLE_ERROR: u8 = b'-';
+const INTEGER: u8 = b':';
+const BULK_STRING: u8 = b'$';
+const ARRAY: u8 = b'*';
+const NULL: u8 = b'_';
+const BOOLEAN: u8 = b'#';
+const DOUBLE: u8 = b',';
+const BIG_NUMBER: u8 = b'(';
+const BULK_ERROR: u8 = b'!';
+const VERBATIM_STRING: u8 = b'=';
+const MAP: u8 = b'%';
+const ATTRIBUTE: u8 = b'|';
+const SET: u8 = b'~';
+const PUSH: u8 = b'>';
+
+/// Serializes a value to RESP3.
+pub fn to_vec(value: &Value) -> Vec<u8> {
+    let mut resp = Vec::new();
+    write_value(&mut resp, value);
+    resp
+}
+
+fn write_value(resp: &mut Vec<u8>, value: &Value) {
+    match *value {
+        Value::Null => write_line(resp, NULL, ""),
+        Value::Bool(b) => write_line(resp, BOOLEAN, if b { "t" } else { "f" }),
+        Value::Number(ref number) => {
+            if let Some(n) = number.as_i64() {
+                write_line(resp, INTEGER, &n.to_string());
+            } else if let Some(n) = number.as_u64() {
+                write_line(resp, BIG_NUMBER, &n.to_string());
+            } else if let Some(n) = number.as_f64() {
+                write_line(resp, DOUBLE, &n.to_string());
+            }
+        }
+        Value::String(ref string) => write_bulk_string(resp, string),
+        Value::Array(ref array) => {
+            write_line(resp, ARRAY, &array.len().to_string());
+            for value in array {
+                write_value(resp, value);
+            }
+        }
+        Value::Object(ref object) => {
+            write_line(resp, MAP, &object.len().to_string());
+            for (key, value) in object {
+                write_bulk_string(resp, key);
+                write_value(resp, value);
+            }
+        }
+    }
+}
+
+fn write_line(resp: &mut Vec<u8>, kind: u8, line: &str) {
+    resp.push(kind);
+    resp.extend_from_slice(line.as_bytes());
+    resp.extend_from_slice(CRLF);
+}
+
+fn write_bulk_string(resp: &mut Vec<u8>, string: &str) {
+    write_line(resp, BULK_STRING, &string.len().to_string());
+    resp.extend_from_slice(string.as_bytes());
+    resp.extend_from_slice(CRLF);
+}
+
+/// Deserializes a RESP2 or RESP3 value.
+///
+/// # Errors
+///
+/// A `Resp` error occurs if the input is truncated, there is an invalid type or length, the
+/// aggregates are nested more than 128 levels deep, or there are bytes after the value.
+pub fn from_slice(input: &[u8]) -> Result<Value> {
+    let mut decoder = Decoder { input, position: 0, depth: 0 };
+    let value = decoder.value()?;
+    if decoder.position != input.len() {
+        return Err(Error::Resp(String::from("Trailing bytes after the RESP value")));
+    }
+    Ok(value)
+}
+
+/// A reader of the lines and bulk data of a RESP value.
+struct Decoder<'a> {
+    input: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Reads the bytes up to the next CRLF, and skips the CRLF.
+    fn line(&mut self) -> Result<&'a str> {
+        let input = self.input;
+        let len = input[self.position..]
+            .windows(2)
+            .position(|w| w == CRLF)
+            .ok_or_else(|| Error::Resp(String::from("Unexpected end of the RESP input")))?;
+        let line = &input[self.position..self.position + len];
+        self.position += len + CRLF.len();
+        str::from_utf8(line)
+            .map_err(|e| Error::Resp(format!("Invalid UTF-8 in a RESP line: {}", e)))
+    }
+
+    /// Reads the bytes of a bulk value and the CRLF after them.
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let input = self.input;
+        let end = self.position.saturating_add(len);
+        if end.saturating_add(CRLF.len()) > input.len() {
+            return Err(Error::Resp(String::from("Unexpected end of the RESP input")));
+        }
+        if &input[end..end + CRLF.len()] != CRLF {
+            return Err(Error::Resp(String::from("Missing the CRLF after RESP bulk data")));
+        }
+        let bytes = &input[self.position..end];
+        self.position = end + CRLF.len();
+        Ok(bytes)
+    }
+
+    /// Reads a length, or `None` for the `-1` length of a RESP2 null bulk string or array.
+    fn length(&mut self) -> Result<Option<usize>> {
+        let line = self.line()?;
+        if line == "-1" {
+            return Ok(None);
+        }
+        line.parse::<usize>()
+            .map(Some)
+            .map_err(|_| Error::Resp(format!("Invalid RESP length '{}'", line)))
+    }
+
+    /// Reads the values of an aggregate one level deeper.
+    fn nested<T, F>(&mut self, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::Resp(format!(
+                "The RESP value is nested more than {} levels deep",
+                MAX_DEPTH
+            )));
+        }
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn values(&mut self, len: usize) -> Result<Vec<Value>> {
+        let mut values = Vec::new();
+        for _ in 0..len {
+            values.push(self.value()?);
+        }
+        Ok(values)
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let kind = *self
+            .input
+            .get(self.position)
+            .ok_or_else(|| Error::Resp(String::from("Unexpected end of the RESP input")))?;
+        self.position += 1;
+        Ok(match kind {
+            SIMPLE_STRING => Value::from(self.line()?),
+            SIMPLE_ERROR => error(Value::from(self.line()?)),
+            INTEGER => {
+                let line = self.line()?;
+                line.parse::<i64>()
+                    .map(Value::from)
+                    .map_err(|_| Error::Resp(format!("Invalid RESP integer '{}'", line)))?
+            }
+            BULK_STRING => match self.length()? {
+                Some(len) => bytes(self.take(len)?),
+                None => Value::Null,
+            },
+            BULK_ERROR => match self.length()? {
+                Some(len) => error(bytes(self.take(len)?)),
+                None => Value::Null,
+            },
+            VERBATIM_STRING => match self.length()? {
+                // The first four bytes are the encoding, like `txt:`.
+                Some(len) if len >= 4 => bytes(&self.take(len)?[4..]),
+                _ => return Err(Error::Resp(String::from("Invalid RESP verbatim string"))),
+            },
+            ARRAY | SET | PUSH => match self.length()? {
+                Some(len) => Value::Array(self.nested(|decoder| decoder.values(len))?),
+                None => Value::Null,
+            },
+            NULL => {
+                self.line()?;
+                Value::Null
+            }
+            BOOLEAN => match self.line()? {
+                "t" => Value::Bool(true),
+                "f" => Value::Bool(false),
+                b => return Err(Error::Resp(format!("Invalid RESP boolean '{}'", b))),
+            },
+            DOUBLE => {
+                let line = self.line()?;
+                line.parse::<f64>()
+                    .map(|n| Number::from_f64(n).map_or(Value::Null, Value::Number))
+                    .map_err(|_| Error::Resp(format!("Invalid RESP double '{}'", line)))?
+            }
+            BIG_NUMBER => {
+                let line = self.line()?;
+                if let Ok(n) = line.parse::<i64>() {
+                    Value::from(n)
+                } else if let Ok(n) = line.parse::<u64>() {
+                    Value::from(n)
+                } else {
+                    line.parse::<f64>()
+                        .map(|n| Number::from_f64(n).map_or(Value::Null, Value::Number))
+                        .map_err(|_| Error::Resp(format!("Invalid RESP big number '{}'", line)))?
+                }
+            }
+            MAP => Value::Object(self.nested(Decoder::map)?),
+            // The attribute is skipped, but the value after it is nested, so a chain of attributes
+            // is limited, too.
+            ATTRIBUTE => self.nested(|decoder| {
+                decoder.map()?;
+                decoder.value()
+            })?,
+            _ => return Err(Error::Resp(format!("Invalid RESP type 0x{:02X}", kind))),
+        })
+    }
+
+    fn map(&mut self) -> Result<Map<String, Value>> {
+        let len = self
+            .length()?
+            .ok_or_else(|| Error::Resp(String::from("Invalid RESP map length '-1'")))?;
+        let mut map = Map::new();
+        for _ in 0..len {
+            let key = match self.value()? {
+                Value::String(key) => key,
+                key => key.to_string(),
+            };
+            let value = self.value()?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+/// Converts the bytes of a bulk string to a string, or an array of byte values if the bytes are
+/// not valid UTF-8.
+fn bytes(bytes: &[u8]) -> Value {
+    match str::from_utf8(bytes) {
+        Ok(string) => Value::from(string),
+        Err(_) => Value::from(bytes.to_vec()),
+    }
+}
+
+fn error(message: Value) -> Value {
+    let mut object = Map::new();
+    object.insert(String::from("error"), message);
+    Value::Object(object)
+}
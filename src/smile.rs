@@ -0,0 +1,525 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serialization and deserialization of a universal, arbitrary value as the [SMILE] binary JSON
+//! format.
+//!
+//! SMILE is the binary JSON format of the Jackson library for Java. Every value is written with a
+//! `:)\n` header and a flags byte, where all of the flags are cleared, so names and string values
+//! are never written as back-references and raw binary is never used. Integers are written in
+//! the smallest integer type, and all other numbers are written as 64-bit floats.
+//!
+//! Reading supports the shared name and string value back-references of the header, but some
+//! features cannot be represented by a universal, arbitrary value:
+//!
+//! - Binary data, both the 7-bit and raw encodings, is read as an array of byte values.
+//! - A `BigInteger` that does not fit in 64 bits and a `BigDecimal` are read as 64-bit floats,
+//!   so precision may be lost.
+//! - A non-finite float, i.e. `NaN` and infinity, is read as a null.
+//!
+//! [SMILE]: https://github.com/FasterXML/smile-format-specification
+
+use serde_json::{Map, Number, Value};
+use std::str;
+
+use super::{Error, Result};
+
+/// The first three bytes of a SMILE document.
+const HEADER: &[u8; 3] = b":)\n";
+
+/// The flag of the header byte to share names with back-references.
+const SHARED_NAMES: u8 = 0x01;
+
+/// The flag of the header byte to share short string values with back-references.
+const SHARED_VALUES: u8 = 0x02;
+
+/// The number of shared names or string values before the back-references are reset.
+const SHARED_LIMIT: usize = 1024;
+
+/// The maximum number of nested arrays and objects, which keeps a malicious document from
+/// overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// The end marker of a long string or name.
+const END_STRING: u8 = 0xFC;
+
+/// The optional end marker of a document.
+const END_DOCUMENT: u8 = 0xFF;
+
+const EMPTY_STRING: u8 = 0x20;
+const NULL: u8 = 0x21;
+const FALSE: u8 = 0x22;
+const TRUE: u8 = 0x23;
+const INT32: u8 = 0x24;
+const INT64: u8 = 0x25;
+const BIG_INTEGER: u8 = 0x26;
+const FLOAT32: u8 = 0x28;
+const FLOAT64: u8 = 0x29;
+const BIG_DECIMAL: u8 = 0x2A;
+const LONG_ASCII: u8 = 0xE0;
+const LONG_UNICODE: u8 = 0xE4;
+const BINARY_7BIT: u8 = 0xE8;
+const START_ARRAY: u8 = 0xF8;
+const END_ARRAY: u8 = 0xF9;
+const START_OBJECT: u8 = 0xFA;
+const END_OBJECT: u8 = 0xFB;
+const BINARY_RAW: u8 = 0xFD;
+const LONG_NAME: u8 = 0x34;
+
+/// Serializes a value to a SMILE document.
+///
+/// The header is written without any flags, so the document can be read by any SMILE parser.
+pub fn to_vec(value: &Value) -> Vec<u8> {
+    let mut smile = HEADER.to_vec();
+    smile.push(0x00);
+    write_value(&mut smile, value);
+    smile
+}
+
+fn write_value(smile: &mut Vec<u8>, value: &Value) {
+    match *value {
+        Value::Null => smile.push(NULL),
+        Value::Bool(false) => smile.push(FALSE),
+        Value::Bool(true) => smile.push(TRUE),
+        Value::Number(ref number) => write_number(smile, number),
+        Value::String(ref string) => write_string(smile, string),
+        Value::Array(ref array) => {
+            smile.push(START_ARRAY);
+            for value in array {
+                write_value(smile, value);
+            }
+            smile.push(END_ARRAY);
+        }
+        Value::Object(ref object) => {
+            smile.push(START_OBJECT);
+            for (name, value) in object {
+                write_name(smile, name);
+                write_value(smile, value);
+            }
+            smile.push(END_OBJECT);
+        }
+    }
+}
+
+fn write_number(smile: &mut Vec<u8>, number: &Number) {
+    if let Some(n) = number.as_i64() {
+        if (-16..=15).contains(&n) {
+            smile.push(0xC0 | zigzag(n) as u8);
+        } else if (i64::from(i32::min_value())..=i64::from(i32::max_value())).contains(&n) {
+            smile.push(INT32);
+            write_vint(smile, zigzag(n));
+        } else {
+            smile.push(INT64);
+            write_vint(smile, zigzag(n));
+        }
+    } else if let Some(n) = number.as_u64() {
+        // Larger than the maximum signed 64-bit integer, so a leading zero byte is needed for
+        // the two's complement bytes of a Java `BigInteger` to be positive.
+        let mut bytes = vec![0];
+        bytes.extend_from_slice(&n.to_be_bytes());
+        smile.push(BIG_INTEGER);
+        write_vint(smile, bytes.len() as u64);
+        write_7bit(smile, &bytes);
+    } else if let Some(n) = number.as_f64() {
+        let bits = n.to_bits();
+        smile.push(FLOAT64);
+        for i in (0..10).rev() {
+            smile.push(((bits >> (7 * i)) & 0x7F) as u8);
+        }
+    }
+}
+
+fn write_string(smile: &mut Vec<u8>, string: &str) {
+    let len = string.len();
+    let token = if string.is_ascii() {
+        match len {
+            0 => EMPTY_STRING,
+            1..=32 => 0x40 + (len - 1) as u8,
+            33..=64 => 0x60 + (len - 33) as u8,
+            _ => LONG_ASCII,
+        }
+    } else {
+        match len {
+            2..=33 => 0x80 + (len - 2) as u8,
+            34..=65 => 0xA0 + (len - 34) as u8,
+            _ => LONG_UNICODE,
+        }
+    };
+    smile.push(token);
+    smile.extend_from_slice(string.as_bytes());
+    if token == LONG_ASCII || token == LONG_UNICODE {
+        smile.push(END_STRING);
+    }
+}
+
+fn write_name(smile: &mut Vec<u8>, name: &str) {
+    let len = name.len();
+    let token = if name.is_ascii() {
+        match len {
+            0 => EMPTY_STRING,
+            1..=64 => 0x80 + (len - 1) as u8,
+            _ => LONG_NAME,
+        }
+    } else {
+        match len {
+            2..=57 => 0xC0 + (len - 2) as u8,
+            _ => LONG_NAME,
+        }
+    };
+    smile.push(token);
+    smile.extend_from_slice(name.as_bytes());
+    if token == LONG_NAME {
+        smile.push(END_STRING);
+    }
+}
+
+/// Writes a variable length integer.
+///
+/// The integer is big-endian with seven bits per byte, except the last byte, which has six bits
+/// and the high bit set.
+fn write_vint(smile: &mut Vec<u8>, value: u64) {
+    let mut bytes = vec![0x80 | (value & 0x3F) as u8];
+    let mut rest = value >> 6;
+    while rest > 0 {
+        bytes.push((rest & 0x7F) as u8);
+        rest >>= 7;
+    }
+    smile.extend(bytes.iter().rev());
+}
+
+/// Writes bytes with seven bits per byte, so the high bit of every byte is cleared.
+///
+/// The bits of the bytes are written from the most significant bit in groups of seven, and any
+/// remaining bits are right-aligned in the last byte.
+fn write_7bit(smile: &mut Vec<u8>, bytes: &[u8]) {
+    let mut bits = 0u32;
+    let mut count = 0;
+    for &byte in bytes {
+        bits = (bits << 8) | u32::from(byte);
+        count += 8;
+        while count >= 7 {
+            count -= 7;
+            smile.push(((bits >> count) & 0x7F) as u8);
+        }
+    }
+    if count > 0 {
+        smile.push((bits & ((1 << count) - 1)) as u8);
+    }
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+/// Deserializes a SMILE document to a value.
+///
+/// # Errors
+///
+/// A `Smile` error occurs if the header is missing, the document is truncated, there is an
+/// invalid token or back-reference, the arrays and objects are nested more than 128 levels deep,
+/// or there are bytes after the value.
+pub fn from_slice(input: &[u8]) -> Result<Value> {
+    if input.len() < 4 || !input.starts_with(HEADER) {
+        return Err(Error::Smile(String::from("Missing the SMILE header")));
+    }
+    let flags = input[3];
+    if flags >> 4 != 0 {
+        return Err(Error::Smile(format!(
+            "Unsupported SMILE version {}",
+            flags >> 4
+        )));
+    }
+    let mut decoder = Decoder {
+        input,
+        position: 4,
+        depth: 0,
+        names: if flags & SHARED_NAMES != 0 { Some(Vec::new()) } else { None },
+        values: if flags & SHARED_VALUES != 0 { Some(Vec::new()) } else { None },
+    };
+    let value = decoder.value()?;
+    match &input[decoder.position..] {
+        [] | [END_DOCUMENT] => Ok(value),
+        _ => Err(Error::Smile(String::from("Trailing bytes after the SMILE value"))),
+    }
+}
+
+/// A reader of the tokens of a SMILE document, which keeps the shared names and string values for
+/// the back-references.
+struct Decoder<'a> {
+    input: &'a [u8],
+    position: usize,
+    depth: usize,
+    names: Option<Vec<String>>,
+    values: Option<Vec<String>>,
+}
+
+impl<'a> Decoder<'a> {
+    fn next(&mut self) -> Result<u8> {
+        let byte = self.peek()?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn peek(&self) -> Result<u8> {
+        self.input
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| Error::Smile(String::from("Unexpected end of the SMILE input")))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let input = self.input;
+        let end = self.position.saturating_add(len);
+        if end > input.len() {
+            return Err(Error::Smile(String::from("Unexpected end of the SMILE input")));
+        }
+        let bytes = &input[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn take_until_end(&mut self) -> Result<&'a [u8]> {
+        let input = self.input;
+        let len = input[self.position..]
+            .iter()
+            .position(|&b| b == END_STRING)
+            .ok_or_else(|| Error::Smile(String::from("Missing the end of a long SMILE string")))?;
+        let bytes = &input[self.position..self.position + len];
+        self.position += len + 1;
+        Ok(bytes)
+    }
+
+    fn vint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for _ in 0..10 {
+            let byte = self.next()?;
+            if byte & 0x80 != 0 {
+                return Ok((value << 6) | u64::from(byte & 0x3F));
+            }
+            value = (value << 7) | u64::from(byte);
+        }
+        Err(Error::Smile(String::from("Invalid SMILE variable length integer")))
+    }
+
+    fn bits(&mut self, len: usize) -> Result<u64> {
+        Ok(self
+            .take(len)?
+            .iter()
+            .fold(0, |bits, &b| (bits << 7) | u64::from(b & 0x7F)))
+    }
+
+    fn binary_7bit(&mut self, len: usize) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(len);
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(7);
+            let mut bits = 0u64;
+            for _ in 0..chunk {
+                bits = (bits << 7) | u64::from(self.next()? & 0x7F);
+            }
+            if chunk == 7 {
+                bits = (bits << 7) | u64::from(self.next()? & 0x7F);
+            } else {
+                bits = (bits << chunk) | u64::from(self.next()? & ((1 << chunk) - 1));
+            }
+            for i in (0..chunk).rev() {
+                bytes.push((bits >> (8 * i)) as u8);
+            }
+            remaining -= chunk;
+        }
+        Ok(bytes)
+    }
+
+    fn binary_len(&mut self) -> Result<usize> {
+        let len = self.vint()? as usize;
+        if len > self.input.len() {
+            return Err(Error::Smile(String::from("Unexpected end of the SMILE input")));
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self, len: usize) -> Result<String> {
+        let bytes = self.take(len)?;
+        str::from_utf8(bytes)
+            .map(String::from)
+            .map_err(|e| Error::Smile(format!("Invalid UTF-8 in a SMILE string: {}", e)))
+    }
+
+    fn long_string(&mut self) -> Result<String> {
+        let bytes = self.take_until_end()?;
+        str::from_utf8(bytes)
+            .map(String::from)
+            .map_err(|e| Error::Smile(format!("Invalid UTF-8 in a SMILE string: {}", e)))
+    }
+
+    /// Reads the items of an array or object one level deeper.
+    fn nested<T, F>(&mut self, read: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::Smile(format!(
+                "The SMILE value is nested more than {} levels deep",
+                MAX_DEPTH
+            )));
+        }
+        self.depth += 1;
+        let result = read(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let token = self.next()?;
+        Ok(match token {
+            0x01..=0x1F => Value::String(shared(&self.values, usize::from(token) - 1)?),
+            EMPTY_STRING => Value::String(String::new()),
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            INT32 | INT64 => Value::from(unzigzag(self.vint()?)),
+            BIG_INTEGER => {
+                let len = self.binary_len()?;
+                big_integer(&self.binary_7bit(len)?)
+            }
+            FLOAT32 => float(f64::from(f32::from_bits(self.bits(5)? as u32))),
+            FLOAT64 => float(f64::from_bits(self.bits(10)?)),
+            BIG_DECIMAL => {
+                let scale = unzigzag(self.vint()?) as i32;
+                let len = self.binary_len()?;
+                let unscaled = big_float(&self.binary_7bit(len)?);
+                float(unscaled * 10f64.powi(-scale))
+            }
+            0x40..=0xBF => {
+                let len = match token {
+                    0x40..=0x5F => token - 0x40 + 1,
+                    0x60..=0x7F => token - 0x60 + 33,
+                    0x80..=0x9F => token - 0x80 + 2,
+                    _ => token - 0xA0 + 34,
+                };
+                let string = self.string(usize::from(len))?;
+                share(&mut self.values, &string);
+                Value::String(string)
+            }
+            0xC0..=0xDF => Value::from(unzigzag(u64::from(token & 0x1F))),
+            LONG_ASCII | LONG_UNICODE => Value::String(self.long_string()?),
+            BINARY_7BIT => {
+                let len = self.binary_len()?;
+                Value::from(self.binary_7bit(len)?)
+            }
+            0xEC..=0xEF => {
+                let index = (usize::from(token & 0x03) << 8) | usize::from(self.next()?);
+                Value::String(shared(&self.values, index)?)
+            }
+            START_ARRAY => Value::Array(self.nested(|decoder| {
+                let mut array = Vec::new();
+                while decoder.peek()? != END_ARRAY {
+                    array.push(decoder.value()?);
+                }
+                decoder.position += 1;
+                Ok(array)
+            })?),
+            START_OBJECT => Value::Object(self.nested(|decoder| {
+                let mut object = Map::new();
+                while let Some(name) = decoder.name()? {
+                    let value = decoder.value()?;
+                    object.insert(name, value);
+                }
+                Ok(object)
+            })?),
+            BINARY_RAW => {
+                let len = self.binary_len()?;
+                Value::from(self.take(len)?.to_vec())
+            }
+            _ => return Err(Error::Smile(format!("Invalid SMILE value token 0x{:02X}", token))),
+        })
+    }
+
+    /// Reads the name of the next key of an object, or `None` at the end of the object.
+    fn name(&mut self) -> Result<Option<String>> {
+        let token = self.next()?;
+        let name = match token {
+            EMPTY_STRING => String::new(),
+            0x30..=0x33 => {
+                let index = (usize::from(token & 0x03) << 8) | usize::from(self.next()?);
+                return shared(&self.names, index).map(Some);
+            }
+            LONG_NAME => self.long_string()?,
+            0x40..=0x7F => return shared(&self.names, usize::from(token & 0x3F)).map(Some),
+            0x80..=0xBF => self.string(usize::from(token & 0x3F) + 1)?,
+            0xC0..=0xF7 => self.string(usize::from(token - 0xC0) + 2)?,
+            END_OBJECT => return Ok(None),
+            _ => return Err(Error::Smile(format!("Invalid SMILE name token 0x{:02X}", token))),
+        };
+        if !name.is_empty() {
+            share(&mut self.names, &name);
+        }
+        Ok(Some(name))
+    }
+}
+
+/// Adds a name or string value to the shared back-references, if sharing is enabled.
+fn share(shared: &mut Option<Vec<String>>, string: &str) {
+    if let Some(ref mut shared) = *shared {
+        if shared.len() == SHARED_LIMIT {
+            shared.clear();
+        }
+        shared.push(String::from(string));
+    }
+}
+
+/// Gets a name or string value for a back-reference.
+fn shared(shared: &Option<Vec<String>>, index: usize) -> Result<String> {
+    shared
+        .as_ref()
+        .and_then(|s| s.get(index))
+        .cloned()
+        .ok_or_else(|| Error::Smile(format!("Invalid SMILE back-reference {}", index)))
+}
+
+fn float(n: f64) -> Value {
+    Number::from_f64(n).map_or(Value::Null, Value::Number)
+}
+
+/// Converts the big-endian, two's complement bytes of a Java `BigInteger` to a value.
+///
+/// A float is used if the integer does not fit in 64 bits.
+fn big_integer(bytes: &[u8]) -> Value {
+    let negative = bytes.first().map_or(false, |b| b & 0x80 != 0);
+    if bytes.len() <= 8 {
+        let initial = if negative { -1i64 } else { 0 };
+        Value::from(bytes.iter().fold(initial, |n, &b| (n << 8) | i64::from(b)))
+    } else if bytes.len() == 9 && bytes[0] == 0 {
+        Value::from(bytes[1..].iter().fold(0u64, |n, &b| (n << 8) | u64::from(b)))
+    } else {
+        float(big_float(bytes))
+    }
+}
+
+/// Converts the big-endian, two's complement bytes of a Java `BigInteger` to a float.
+fn big_float(bytes: &[u8]) -> f64 {
+    match bytes.split_first() {
+        Some((&first, rest)) => rest
+            .iter()
+            .fold(f64::from(first as i8), |n, &b| n * 256.0 + f64::from(b)),
+        None => 0.0,
+    }
+}
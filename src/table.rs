@@ -0,0 +1,56 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The rows and columns of an array of objects for the table output formats, i.e. Markdown and
+//! Tab-Separated Values (TSV).
+
+use serde_json::{Map, Value};
+
+use super::{Error, Result};
+
+/// Gets the objects of an array, where each object is a row of a table.
+///
+/// # Errors
+///
+/// A `Generic` error with the name of the format occurs if an element is not an object.
+pub fn objects<'a>(values: &'a [Value], format: &str) -> Result<Vec<&'a Map<String, Value>>> {
+    values
+        .iter()
+        .map(|v| {
+            v.as_object().ok_or_else(|| {
+                Error::Generic(format!(
+                    "{} output requires every element of an array to be an object",
+                    format
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Gets the columns of a table, which are the union of all of the keys of the objects in the
+/// order that the keys first appear.
+pub fn columns<'a>(objects: &[&'a Map<String, Value>]) -> Vec<&'a String> {
+    let mut columns: Vec<&String> = Vec::new();
+    for object in objects {
+        for key in object.keys() {
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+    columns
+}
@@ -0,0 +1,254 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transformations of the universal, arbitrary value between deserialization and serialization.
+//!
+//! All formats are deserialized to a `serde_json::Value`, so a transformation can be applied to
+//! the value regardless of the input and output formats.
+
+use serde_json::{Map, Number, Value};
+
+use super::NullPolicy;
+
+/// Converts strings that look like numbers to numbers.
+///
+/// Every string is recursively parsed as a signed 64-bit integer, then an unsigned 64-bit integer,
+/// and then as a 64-bit float. The first successful parse replaces the string with the number.
+/// A string that cannot be parsed, or is a non-finite float like `"NaN"`, remains a string. This
+/// is useful for formats where all values are strings, like URL-encoded data.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_json;
+/// extern crate panser;
+///
+/// use panser::transform;
+///
+/// fn main() {
+///     let value = json!({"integer": "42", "float": "3.14", "string": "hello"});
+///     assert_eq!(
+///         transform::coerce_numbers(value),
+///         json!({"integer": 42, "float": 3.14, "string": "hello"})
+///     );
+/// }
+/// ```
+pub fn coerce_numbers(value: Value) -> Value {
+    match value {
+        Value::String(s) => {
+            if let Ok(n) = s.parse::<i64>() {
+                Value::Number(n.into())
+            } else if let Ok(n) = s.parse::<u64>() {
+                Value::Number(n.into())
+            } else if let Some(n) = s.parse::<f64>().ok().and_then(Number::from_f64) {
+                Value::Number(n)
+            } else {
+                Value::String(s)
+            }
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(coerce_numbers).collect()),
+        Value::Object(values) => Value::Object(
+            values
+                .into_iter()
+                .map(|(k, v)| (k, coerce_numbers(v)))
+                .collect(),
+        ),
+        v => v,
+    }
+}
+
+/// Converts strings that look like booleans or numbers to booleans or numbers.
+///
+/// Every string that is exactly `true` or `false` is recursively replaced with a boolean, and all
+/// other strings are converted like the `coerce_numbers` function. A string that is not
+/// unambiguously a boolean or a number, such as `"True"` or `"yes"`, remains a string. This is
+/// useful for formats where all values are strings, like URL-encoded data and dotenv files.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_json;
+/// extern crate panser;
+///
+/// use panser::transform;
+///
+/// fn main() {
+///     let value = json!({"bool": "true", "integer": "42", "float": "1.5", "string": "yes"});
+///     assert_eq!(
+///         transform::coerce_scalars(value),
+///         json!({"bool": true, "integer": 42, "float": 1.5, "string": "yes"})
+///     );
+/// }
+/// ```
+pub fn coerce_scalars(value: Value) -> Value {
+    match value {
+        Value::String(s) => match s.as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => coerce_numbers(Value::String(s)),
+        },
+        Value::Array(values) => Value::Array(values.into_iter().map(coerce_scalars).collect()),
+        Value::Object(values) => Value::Object(
+            values
+                .into_iter()
+                .map(|(k, v)| (k, coerce_scalars(v)))
+                .collect(),
+        ),
+        v => v,
+    }
+}
+
+/// Replaces, or removes, null values based on a policy.
+///
+/// Every null is recursively replaced with the value for the `policy`. If the policy is
+/// `NullPolicy::Skip`, then null fields are removed from objects and null elements are removed
+/// from arrays, but a null that is not within an object or array remains a null.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_json;
+/// extern crate panser;
+///
+/// use panser::{transform, NullPolicy};
+///
+/// fn main() {
+///     let value = json!({"a": null, "b": 1});
+///     assert_eq!(transform::apply_null_policy(value, NullPolicy::Skip), json!({"b": 1}));
+/// }
+/// ```
+pub fn apply_null_policy(value: Value, policy: NullPolicy) -> Value {
+    match value {
+        Value::Null => match policy {
+            NullPolicy::EmptyArray => Value::Array(Vec::new()),
+            NullPolicy::EmptyObject => Value::Object(Map::new()),
+            NullPolicy::EmptyString => Value::String(String::new()),
+            NullPolicy::Skip => Value::Null,
+            NullPolicy::Zero => Value::Number(0.into()),
+        },
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .filter(|v| policy != NullPolicy::Skip || !v.is_null())
+                .map(|v| apply_null_policy(v, policy))
+                .collect(),
+        ),
+        Value::Object(values) => Value::Object(
+            values
+                .into_iter()
+                .filter(|(_, v)| policy != NullPolicy::Skip || !v.is_null())
+                .map(|(k, v)| (k, apply_null_policy(v, policy)))
+                .collect(),
+        ),
+        v => v,
+    }
+}
+
+/// Converts floats without a fractional part to integers.
+///
+/// Every float is recursively converted to a signed 64-bit integer, or an unsigned 64-bit integer
+/// if it is too large, when the fractional part is zero, so `1.0` becomes `1`. A float that is too
+/// large for either integer remains a float. This reduces changes of number types when
+/// transcoding between formats with different number models, like YAML and TOML.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_json;
+/// extern crate panser;
+///
+/// use panser::transform;
+///
+/// fn main() {
+///     let value = json!({"whole": 1.0, "fraction": 1.5, "integer": 2});
+///     assert_eq!(
+///         transform::normalize_numbers(value),
+///         json!({"whole": 1, "fraction": 1.5, "integer": 2})
+///     );
+/// }
+/// ```
+pub fn normalize_numbers(value: Value) -> Value {
+    match value {
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.is_f64() && f.fract() == 0.0 => {
+                if f >= std::i64::MIN as f64 && f < std::i64::MAX as f64 {
+                    Value::Number((f as i64).into())
+                } else if f >= 0.0 && f < std::u64::MAX as f64 {
+                    Value::Number((f as u64).into())
+                } else {
+                    Value::Number(n)
+                }
+            }
+            _ => Value::Number(n),
+        },
+        Value::Array(values) => Value::Array(values.into_iter().map(normalize_numbers).collect()),
+        Value::Object(values) => Value::Object(
+            values
+                .into_iter()
+                .map(|(k, v)| (k, normalize_numbers(v)))
+                .collect(),
+        ),
+        v => v,
+    }
+}
+
+/// Converts integers to floats.
+///
+/// Every integer is recursively converted to a 64-bit float, so `1` becomes `1.0`. This is the
+/// reverse of the `normalize_numbers` function. Note, an integer larger than 2^53 may lose
+/// precision.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_json;
+/// extern crate panser;
+///
+/// use panser::transform;
+///
+/// fn main() {
+///     let value = json!({"integer": 1, "float": 1.5});
+///     assert_eq!(transform::force_float(value), json!({"integer": 1.0, "float": 1.5}));
+/// }
+/// ```
+pub fn force_float(value: Value) -> Value {
+    match value {
+        Value::Number(n) => {
+            if n.is_f64() {
+                Value::Number(n)
+            } else {
+                match n.as_f64().and_then(Number::from_f64) {
+                    Some(f) => Value::Number(f),
+                    None => Value::Number(n),
+                }
+            }
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(force_float).collect()),
+        Value::Object(values) => Value::Object(
+            values
+                .into_iter()
+                .map(|(k, v)| (k, force_float(v)))
+                .collect(),
+        ),
+        v => v,
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serialization and deserialization of a universal, arbitrary value as Tab-Separated Values
+//! (TSV).
+//!
+//! The first line is a header of the column names and every other line is a row. Values are not
+//! quoted. Instead, a tab, newline, carriage return, and backslash within a value are escaped as
+//! `\t`, `\n`, `\r`, and `\\`, respectively.
+
+use serde_json::{Map, Value};
+use std::str;
+
+use super::table;
+use super::{Error, Result};
+
+/// Serializes a value to Tab-Separated Values (TSV).
+///
+/// An array of objects is a header row, where the columns are the union of all of the keys of the
+/// objects, followed by a row of the values of each object. A missing key or a null is an empty
+/// value. A single object is a header row followed by a single row.
+///
+/// # Errors
+///
+/// A `Generic` error occurs if the value is not an object or an array of objects, or a value is
+/// an array or object.
+pub fn to_vec(value: &Value) -> Result<Vec<u8>> {
+    let objects = match *value {
+        Value::Array(ref rows) => table::objects(rows, "TSV")?,
+        Value::Object(ref object) => vec![object],
+        _ => {
+            return Err(Error::Generic(String::from(
+                "TSV output requires an object or an array of objects",
+            )))
+        }
+    };
+    let columns = table::columns(&objects);
+    let mut tsv = columns
+        .iter()
+        .map(|c| escape(c))
+        .collect::<Vec<String>>()
+        .join("\t");
+    tsv.push('\n');
+    for object in objects {
+        let fields = columns
+            .iter()
+            .map(|c| object.get(*c).map_or(Ok(String::new()), field))
+            .collect::<Result<Vec<String>>>()?;
+        tsv.push_str(&fields.join("\t"));
+        tsv.push('\n');
+    }
+    Ok(tsv.into_bytes())
+}
+
+/// Deserializes Tab-Separated Values (TSV) to an array of objects.
+///
+/// Each row is an object, where the keys are the column names from the header row and the values
+/// are strings. Empty lines are ignored. See the `transform::coerce_numbers` function to convert
+/// values that look like numbers to numbers.
+///
+/// # Errors
+///
+/// A `Generic` error occurs if a row does not have the same number of values as the header row.
+pub fn from_slice(input: &[u8]) -> Result<Value> {
+    let text = str::from_utf8(input)?;
+    let mut lines = text
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.is_empty());
+    let columns = match lines.next() {
+        Some(header) => header.split('\t').map(unescape).collect::<Vec<String>>(),
+        None => return Ok(Value::Array(Vec::new())),
+    };
+    let mut rows = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let fields = line.split('\t').collect::<Vec<&str>>();
+        if fields.len() != columns.len() {
+            return Err(Error::Generic(format!(
+                "Row {} of the TSV input has {} values, but the header has {} columns",
+                i + 1,
+                fields.len(),
+                columns.len()
+            )));
+        }
+        let row = columns
+            .iter()
+            .cloned()
+            .zip(fields.into_iter().map(|f| Value::String(unescape(f))))
+            .collect::<Map<String, Value>>();
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
+/// Formats a value as a field of a row.
+fn field(value: &Value) -> Result<String> {
+    match *value {
+        Value::Array(_) | Value::Object(_) => Err(Error::Generic(String::from(
+            "Nested objects and arrays cannot be written as a TSV value",
+        ))),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Null => Ok(String::new()),
+        Value::Number(ref n) => Ok(n.to_string()),
+        Value::String(ref s) => Ok(escape(s)),
+    }
+}
+
+/// Escapes the tab, newline, carriage return, and backslash characters.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses the escaping of the tab, newline, carriage return, and backslash characters.
+///
+/// A backslash followed by any other character is kept as-is.
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some('\\') => unescaped.push('\\'),
+                Some(other) => {
+                    unescaped.push('\\');
+                    unescaped.push(other);
+                }
+                None => unescaped.push('\\'),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
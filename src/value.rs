@@ -0,0 +1,638 @@
+// Copyright (C) 2017 Christopher R. Field.
+//
+// This file is part of Panser.
+//
+// Panser is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Panser is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Panser.  If not, see <http://www.gnu.org/licenses/>.
+
+use base64;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, Serializer};
+use serde::Serialize;
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+use serde_json::{Map, Number, Value};
+use std::cell::Cell;
+use std::convert::TryFrom;
+use std::f64;
+use std::fmt;
+use std::io::{self, Write};
+use std::str;
+
+// A `serde_json::Value` cannot hold a non-finite float, i.e. NaN or infinity, so non-finite floats
+// are held as strings that start with this Unicode noncharacter until serialization, i.e.
+// `"\u{FDD0}NaN"`, `"\u{FDD0}Infinity"`, and `"\u{FDD0}-Infinity"`. Any actual string that starts
+// with the noncharacter is escaped with another noncharacter, so a held float never clashes with
+// actual data. The `NonFinite` and `Ext` types restore both when serializing.
+const MARKER: char = '\u{FDD0}';
+
+/// The key for the tagged representation of a MessagePack extension type, i.e.
+/// `{"$msgpack_ext": {"type": -1, "data": "<base64>"}}`.
+pub const MSGPACK_EXT: &str = "$msgpack_ext";
+
+/// The key for the type of the hexadecimal representation of a MessagePack extension type, i.e.
+/// `{"__ext_type__": -1, "__ext_data__": "<hex>"}`.
+pub const EXT_TYPE: &str = "__ext_type__";
+
+/// The key for the data of the hexadecimal representation of a MessagePack extension type.
+pub const EXT_DATA: &str = "__ext_data__";
+
+/// The prefix of a string that holds the base64 of a byte string, i.e. `"base64:AQID"`.
+pub const BASE64_PREFIX: &str = "base64:";
+
+// The name of the newtype struct that the rmp-serde crate uses for extension types.
+const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
+
+/// The representations of a MessagePack extension type in a `serde_json::Value`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtRepresentation {
+    /// The data is hexadecimal, i.e. `{"__ext_type__": -1, "__ext_data__": "<hex>"}`.
+    Hex,
+    /// The data is base64 within a tagged object, i.e.
+    /// `{"$msgpack_ext": {"type": -1, "data": "<base64>"}}`.
+    Tagged,
+}
+
+/// The options for deserializing to a `serde_json::Value`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ValueOptions {
+    /// Keep non-finite floats, i.e. NaN and infinity, instead of replacing them with null.
+    pub allow_nan: bool,
+    /// Convert byte strings to base64 strings with the `base64:` prefix instead of failing.
+    pub bytes_as_base64: bool,
+    /// Keep MessagePack extension types in a representation instead of an array.
+    pub ext: Option<ExtRepresentation>,
+    /// Convert boolean and number keys of maps to strings instead of failing.
+    pub stringify_keys: bool,
+}
+
+/// Deserializes to a `serde_json::Value` with the options.
+///
+/// This is the same as the `Deserialize` implementation for a `serde_json::Value`, except for
+/// the handling of values that JSON cannot represent.
+#[derive(Clone, Copy, Debug)]
+pub struct ValueSeed(pub ValueOptions);
+
+impl<'de> DeserializeSeed<'de> for ValueSeed {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor(self.0))
+    }
+}
+
+struct ValueVisitor(ValueOptions);
+
+impl ValueVisitor {
+    /// Escapes a string that starts with the noncharacter for held non-finite floats.
+    fn string(&self, value: String) -> Value {
+        if self.0.allow_nan && value.starts_with(MARKER) {
+            Value::String(format!("{}{}", MARKER, value))
+        } else {
+            Value::String(value)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(value).map_or_else(
+            || {
+                if !self.0.allow_nan {
+                    Value::Null
+                } else if value.is_nan() {
+                    Value::String(format!("{}NaN", MARKER))
+                } else if value.is_sign_positive() {
+                    Value::String(format!("{}Infinity", MARKER))
+                } else {
+                    Value::String(format!("{}-Infinity", MARKER))
+                }
+            },
+            Value::Number,
+        ))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(self.string(String::from(value)))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(self.string(value))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Value, E>
+    where
+        E: de::Error,
+    {
+        if self.0.bytes_as_base64 {
+            Ok(Value::String(format!("{}{}", BASE64_PREFIX, base64::encode(value))))
+        } else {
+            Err(de::Error::invalid_type(de::Unexpected::Bytes(value), &self))
+        }
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ValueSeed(self.0).deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if let Some(representation) = self.0.ext {
+            deserializer.deserialize_seq(ExtVisitor(representation))
+        } else {
+            ValueSeed(self.0).deserialize(deserializer)
+        }
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(ValueSeed(self.0))? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut values = Map::new();
+        loop {
+            let key = if self.0.stringify_keys {
+                map.next_key_seed(KeySeed)?
+            } else {
+                map.next_key::<String>()?
+            };
+            let key = match key {
+                Some(k) => k,
+                None => break,
+            };
+            let value = map.next_value_seed(ValueSeed(self.0))?;
+            values.insert(key, value);
+        }
+        Ok(Value::Object(values))
+    }
+}
+
+/// Visits a MessagePack extension type, which is a sequence of the type and the data, and creates
+/// the representation.
+struct ExtVisitor(ExtRepresentation);
+
+impl<'de> Visitor<'de> for ExtVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a MessagePack extension type")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let ext_type = seq
+            .next_element::<i8>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data = seq
+            .next_element_seed(BytesSeed)?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let mut ext = Map::new();
+        match self.0 {
+            ExtRepresentation::Hex => {
+                ext.insert(String::from(EXT_TYPE), Value::Number(ext_type.into()));
+                ext.insert(String::from(EXT_DATA), Value::String(to_hex(&data)));
+            }
+            ExtRepresentation::Tagged => {
+                let mut tagged = Map::new();
+                tagged.insert(String::from("type"), Value::Number(ext_type.into()));
+                tagged.insert(String::from("data"), Value::String(base64::encode(&data)));
+                ext.insert(String::from(MSGPACK_EXT), Value::Object(tagged));
+            }
+        }
+        Ok(Value::Object(ext))
+    }
+}
+
+/// Deserializes the key of a map as a string, where a boolean or number key is converted to
+/// a string, i.e. `1` is `"1"`.
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = String;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<String, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(KeyVisitor)
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string, number, or boolean key")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<String, E> {
+        Ok(value.to_string())
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<String, E> {
+        Ok(value.to_string())
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<String, E> {
+        Ok(value.to_string())
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<String, E> {
+        Ok(value.to_string())
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<String, E> {
+        Ok(String::from(value))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<String, E> {
+        Ok(value)
+    }
+}
+
+/// Deserializes the data of a MessagePack extension type as bytes.
+struct BytesSeed;
+
+impl<'de> DeserializeSeed<'de> for BytesSeed {
+    type Value = Vec<u8>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("bytes")
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(value)
+    }
+}
+
+/// Serializes a `serde_json::Value` with any representations of MessagePack extension types
+/// restored to extension types.
+///
+/// This should only be used with the rmp-serde serializer. If the flag is `true`, i.e. the value
+/// was deserialized with the `allow_nan` option, then any held non-finite floats are also restored
+/// to floats.
+pub struct Ext<'a>(pub &'a Value, pub bool);
+
+impl<'a> Serialize for Ext<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self.0 {
+            Value::Object(ref values) => {
+                if let Some((ext_type, data)) = as_ext(values) {
+                    return serializer
+                        .serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &(ext_type, Bytes(&data)));
+                }
+                let mut map = serializer.serialize_map(Some(values.len()))?;
+                for (key, value) in values {
+                    map.serialize_entry(key, &Ext(value, self.1))?;
+                }
+                map.end()
+            }
+            Value::Array(ref values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(&Ext(value, self.1))?;
+                }
+                seq.end()
+            }
+            ref value if self.1 => NonFinite(value).serialize(serializer),
+            ref value => value.serialize(serializer),
+        }
+    }
+}
+
+/// Serializes a `serde_json::Value` with any strings that hold base64 with the `base64:` prefix
+/// restored to byte strings.
+///
+/// A string with the prefix that is not valid base64 is serialized as a string.
+pub struct Base64Bytes<'a>(pub &'a Value);
+
+impl<'a> Serialize for Base64Bytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self.0 {
+            Value::String(ref s) if s.starts_with(BASE64_PREFIX) => {
+                match base64::decode(&s[BASE64_PREFIX.len()..]) {
+                    Ok(data) => serializer.serialize_bytes(&data),
+                    Err(_) => serializer.serialize_str(s),
+                }
+            }
+            Value::Array(ref values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(&Base64Bytes(value))?;
+                }
+                seq.end()
+            }
+            Value::Object(ref values) => {
+                let mut map = serializer.serialize_map(Some(values.len()))?;
+                for (key, value) in values {
+                    map.serialize_entry(key, &Base64Bytes(value))?;
+                }
+                map.end()
+            }
+            ref value => value.serialize(serializer),
+        }
+    }
+}
+
+/// Serializes a slice as bytes instead of a sequence.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Gets the type and data of a representation of a MessagePack extension type.
+///
+/// Returns `None` if the object is not a representation, the type is not a signed 8-bit integer,
+/// or the data is not base64 for the tagged representation or hexadecimal for the hexadecimal
+/// representation.
+fn as_ext(values: &Map<String, Value>) -> Option<(i8, Vec<u8>)> {
+    if values.len() == 1 {
+        let ext = values.get(MSGPACK_EXT)?.as_object()?;
+        let ext_type = i8::try_from(ext.get("type")?.as_i64()?).ok()?;
+        let data = base64::decode(ext.get("data")?.as_str()?).ok()?;
+        Some((ext_type, data))
+    } else if values.len() == 2 {
+        let ext_type = i8::try_from(values.get(EXT_TYPE)?.as_i64()?).ok()?;
+        let data = from_hex(values.get(EXT_DATA)?.as_str()?)?;
+        Some((ext_type, data))
+    } else {
+        None
+    }
+}
+
+/// Formats the data as a lowercase hexadecimal string without any separators.
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parses a hexadecimal string without any separators.
+///
+/// Returns `None` if the string has an odd length or a non-hexadecimal character.
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    s.as_bytes()
+        .chunks(2)
+        .map(|c| {
+            str::from_utf8(c)
+                .ok()
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        })
+        .collect()
+}
+
+/// Serializes a `serde_json::Value` with any held non-finite floats restored to floats.
+///
+/// This should only be used for a value that was deserialized with the `allow_nan` option.
+pub struct NonFinite<'a>(pub &'a Value);
+
+impl<'a> Serialize for NonFinite<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Restore {
+            value: self.0,
+            literal: None,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes a value that was deserialized with the `allow_nan` option to JSON with any held
+/// non-finite floats written as the non-standard `NaN`, `Infinity`, and `-Infinity` literals.
+///
+/// This matches the output of Python's `json.dumps` with `allow_nan=True`. If `indent` is `None`,
+/// then the JSON is compact. Otherwise, the JSON is pretty and each level of nesting is indented
+/// with the indent.
+pub fn to_json_literals(value: &Value, indent: Option<&[u8]>) -> serde_json::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let literal = Cell::new(None);
+    let restore = Restore {
+        value,
+        literal: Some(&literal),
+    };
+    match indent {
+        Some(indent) => {
+            let formatter = Literals {
+                formatter: PrettyFormatter::with_indent(indent),
+                literal: &literal,
+            };
+            restore.serialize(&mut serde_json::Serializer::with_formatter(&mut buf, formatter))?;
+        }
+        None => {
+            let formatter = Literals {
+                formatter: CompactFormatter,
+                literal: &literal,
+            };
+            restore.serialize(&mut serde_json::Serializer::with_formatter(&mut buf, formatter))?;
+        }
+    }
+    Ok(buf)
+}
+
+/// Serializes a value with the held non-finite floats restored to floats and the escaped strings
+/// restored to the actual strings.
+///
+/// The serde_json serializer writes a non-finite float as null, so the literal for the float is
+/// set before the float is serialized for the `Literals` formatter to write instead.
+struct Restore<'a> {
+    value: &'a Value,
+    literal: Option<&'a Cell<Option<&'static str>>>,
+}
+
+impl<'a> Restore<'a> {
+    fn nested(&self, value: &'a Value) -> Restore<'a> {
+        Restore {
+            value,
+            literal: self.literal,
+        }
+    }
+}
+
+impl<'a> Serialize for Restore<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self.value {
+            Value::String(ref s) if s.starts_with(MARKER) => {
+                let (literal, float) = match &s[MARKER.len_utf8()..] {
+                    "NaN" => ("NaN", f64::NAN),
+                    "Infinity" => ("Infinity", f64::INFINITY),
+                    "-Infinity" => ("-Infinity", f64::NEG_INFINITY),
+                    escaped => return serializer.serialize_str(escaped),
+                };
+                if let Some(l) = self.literal {
+                    l.set(Some(literal));
+                }
+                serializer.serialize_f64(float)
+            }
+            Value::Array(ref values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(&self.nested(value))?;
+                }
+                seq.end()
+            }
+            Value::Object(ref values) => {
+                let mut map = serializer.serialize_map(Some(values.len()))?;
+                for (key, value) in values {
+                    map.serialize_entry(key, &self.nested(value))?;
+                }
+                map.end()
+            }
+            ref value => value.serialize(serializer),
+        }
+    }
+}
+
+/// A JSON formatter that writes the literal of a non-finite float instead of null.
+struct Literals<'a, F> {
+    formatter: F,
+    literal: &'a Cell<Option<&'static str>>,
+}
+
+impl<'a, F: Formatter> Formatter for Literals<'a, F> {
+    fn write_null<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self.literal.take() {
+            Some(literal) => writer.write_all(literal.as_bytes()),
+            None => self.formatter.write_null(writer),
+        }
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.formatter.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.formatter.begin_object_key(writer, first)
+    }
+
+    fn end_object_key<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.end_object_key(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.formatter.end_object_value(writer)
+    }
+}
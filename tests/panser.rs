@@ -15,10 +15,24 @@
 // You should have received a copy of the GNU General Public License
 // along with Panser.  If not, see <http://www.gnu.org/licenses/>.
 
+extern crate flate2;
+extern crate lz4_flex;
+extern crate panser;
+extern crate serde_json;
+extern crate zstd;
+
+use panser::{
+    parse_hex_dump, parse_radix_bytes, pipeline, Digest, Error, ExitCode, FrameReader, FrameWriter,
+    Framing, FromFormat, Panser, Pipeline, Radix, RadixStyle, SerializableError, Step, ToFormat,
+};
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use std::env;
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
 
 fn exe_path() -> PathBuf {
     Path::new(&env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR environment variable"))
@@ -219,3 +233,4269 @@ fn sized_input_delimited_output_works() {
     assert_eq!(buf, vec![0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x0A]);
 }
 
+
+#[test]
+fn stxetx_output_works() {
+    let process = Command::new(exe_path())
+        .arg("--stxetx-output")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x02, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03]);
+}
+
+#[test]
+fn stxetx_input_works() {
+    let process = Command::new(exe_path())
+        .arg("--stxetx-input")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&vec![0x02, 0x7b, 0x22, 0x62, 0x6f, 0x6f, 0x6c, 0x22, 0x3a, 0x74, 0x72, 0x75, 0x65, 0x7d, 0x03]).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3]);
+}
+
+#[test]
+fn stxetx_msgpack_works() {
+    let process = Command::new(exe_path())
+        .arg("--stxetx")
+        .arg("-f")
+        .arg("msgpack")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&vec![0x02, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03]).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x02, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03]);
+}
+
+#[test]
+fn stxetx_lrc_checksum_works() {
+    let process = Command::new(exe_path())
+        .arg("--stxetx-output")
+        .arg("--stxetx-checksum")
+        .arg("lrc")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x02, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03, 0xeb]);
+}
+
+#[test]
+fn stxetx_crc16_checksum_works() {
+    let process = Command::new(exe_path())
+        .arg("--stxetx")
+        .arg("--stxetx-checksum")
+        .arg("crc16")
+        .arg("-f")
+        .arg("msgpack")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&vec![0x02, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03, 0xd5, 0x3d]).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x02, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03, 0xd5, 0x3d]);
+}
+
+#[test]
+fn stxetx_misframed_fails() {
+    let mut process = Command::new(exe_path())
+        .arg("--stxetx-input")
+        .arg("-f")
+        .arg("msgpack")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(&vec![0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03]).expect("Write to stdin");
+    let status = process.wait().expect("Wait for process");
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn stxetx_checksum_without_stxetx_fails() {
+    let output = run_with_env(&[], &["--stxetx-checksum", "lrc"], b"{\"bool\":true}");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn continue_on_error_works() {
+    let process = Command::new(exe_path())
+        .arg("--delimited")
+        .arg("0Ah")
+        .arg("--continue-on-error")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n{\"bool\":\n{\"bool\":true}\n".as_bytes()).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x0A, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x0A]);
+    let mut err = String::new();
+    process.stderr.expect("stderr").read_to_string(&mut err).expect("Read from stderr");
+    assert!(err.contains("Skipped 1 message(s)"));
+}
+
+#[test]
+fn fail_fast_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--delimited")
+        .arg("0Ah")
+        .arg("--continue-on-error")
+        .arg("--fail-fast")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all("{\"bool\":\n{\"bool\":true}\n".as_bytes()).expect("Write to stdin");
+    let status = process.wait().expect("Wait for process");
+    assert!(!status.success());
+}
+
+#[test]
+fn c_array_style_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("c-array")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3");
+}
+
+#[test]
+fn c_array_style_width_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("c-array")
+        .arg("--width")
+        .arg("4")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "0x81, 0xa4, 0x62, 0x6f,\n0x6f, 0x6c, 0xc3");
+}
+
+#[test]
+fn c_declaration_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("c-array")
+        .arg("--c-declaration")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "static const uint8_t data[] = {\n  0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3\n};\nstatic const size_t data_len = 7;\n");
+}
+
+#[test]
+fn c_array_style_sized_output_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("c")
+        .arg("--sized-output")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "0x00, 0x00, 0x00, 0x07, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3");
+}
+
+#[test]
+fn allow_nan_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg("--allow-nan")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&vec![0x93, 0xcb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xcb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xcb, 0xff, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "[NaN,Infinity,-Infinity]");
+}
+
+#[test]
+fn allow_nan_binary_output_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("msgpack")
+        .arg("--allow-nan")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&vec![0x81, 0xa1, 0x61, 0xcb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x81, 0xa1, 0x61, 0xcb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn allow_nan_keeps_strings_works() {
+    let input = vec![0x82, 0xa1, 0x61, 0xcb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa1, 0x62, 0xa6, 0xef, 0xb7, 0x90, 0x4e, 0x61, 0x4e];
+    for (to, indent, expected) in vec![
+        ("json", None, "{\"a\":NaN,\"b\":\"\u{FDD0}NaN\"}"),
+        ("json", Some("2"), "{\n  \"a\": NaN,\n  \"b\": \"\u{FDD0}NaN\"\n}"),
+        ("yaml", None, "a: .nan\nb: \u{FDD0}NaN\n"),
+    ] {
+        let mut command = Command::new(exe_path());
+        command.arg("-f").arg("msgpack").arg("-t").arg(to).arg("--allow-nan");
+        if let Some(i) = indent {
+            command.arg("--pretty-indent").arg(i);
+        }
+        let process = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Create process");
+        process.stdin.expect("stdin").write_all(&input).expect("Write to stdin");
+        let mut buf = String::new();
+        process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+        assert_eq!(buf, expected);
+    }
+}
+
+#[test]
+fn nan_as_null_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg("--nan-as-null")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&vec![0x92, 0xcb, 0x7f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xcb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "[null,null]");
+}
+
+#[test]
+fn rust_style_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("rust")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "vec![0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3]\n");
+}
+
+#[test]
+fn rust_slice_style_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("rust")
+        .arg("--rust-slice")
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n{\"bool\":false}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "&[0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3]\n&[0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc2]\n");
+}
+
+#[test]
+fn rust_style_width_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("rust")
+        .arg("--width")
+        .arg("4")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "vec![\n    0x81, 0xa4, 0x62, 0x6f,\n    0x6f, 0x6c, 0xc3,\n]\n");
+}
+
+#[test]
+fn coerce_numbers_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--coerce-numbers")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"integer\":\"42\",\"float\":\"3.14\",\"string\":\"hello\",\"array\":[\"-1\"]}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"array\":[-1],\"float\":3.14,\"integer\":42,\"string\":\"hello\"}");
+}
+
+#[test]
+fn coerce_numbers_url_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("url")
+        .arg("-t")
+        .arg("json")
+        .arg("--coerce-numbers")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("a=42&b=3.14&c=hello".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":42,\"b\":3.14,\"c\":\"hello\"}");
+}
+
+#[test]
+fn coerce_scalars_url_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("url")
+        .arg("-t")
+        .arg("json")
+        .arg("--coerce-scalars")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("a=true&b=42&c=1.5&d=True&e=hello".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":true,\"b\":42,\"c\":1.5,\"d\":\"True\",\"e\":\"hello\"}");
+}
+
+#[test]
+fn preview_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("hex")
+        .arg("--preview")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "81 A4 62 6F 6F 6C C3 \n..bool.");
+}
+
+#[test]
+fn preview_delimited_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("hex")
+        .arg("-d")
+        .arg("0Ah")
+        .arg("--preview")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n{\"a\":\"hi\"}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "81 A4 62 6F 6F 6C C3 \n..bool.\n81 A1 61 A2 68 69 \n..a.hi\n");
+}
+
+#[test]
+fn pad_decimal_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("dec")
+        .arg("--pad")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "129 164 098 111 111 108 195 ");
+}
+
+#[test]
+fn output_prefix_suffix_works() {
+    let process = Command::new(exe_path())
+        .arg("--output-prefix")
+        .arg("02h")
+        .arg("--output-suffix")
+        .arg("03h,0Dh")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x02, 0x81, 0xa4, 0x62, 0x6f, 0x6f, 0x6c, 0xc3, 0x03, 0x0D, 0x0A]);
+}
+
+#[test]
+fn output_prefix_radix_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("h")
+        .arg("--output-prefix")
+        .arg("3Eh 20h")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "> 81 A4 62 6F 6F 6C C3 ");
+}
+
+#[test]
+fn hexdump_style_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--style")
+        .arg("hexdump")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true,\"number\":1}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        &buf,
+        "00000000: 7b22 626f 6f6c 223a 7472 7565 2c22 6e75  {\"bool\":true,\"nu\n\
+         00000010: 6d62 6572 223a 317d                      mber\":1}\n"
+    );
+}
+
+#[test]
+fn hexdump_style_group_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("hexdump")
+        .arg("--group")
+        .arg("1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        &buf,
+        "00000000: 81 a4 62 6f 6f 6c c3                             ..bool.\n"
+    );
+}
+
+#[test]
+fn null_policy_skip_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--null-policy")
+        .arg("skip")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":null,\"b\":1}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"b\":1}");
+}
+
+#[test]
+fn null_policy_skip_toml_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("toml")
+        .arg("--null-policy")
+        .arg("skip")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":null,\"b\":1}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "b = 1\n");
+}
+
+#[test]
+fn null_to_default_toml_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("toml")
+        .arg("--null-to-default")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":null,\"b\":1}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "a = \"\"\nb = 1\n");
+}
+
+#[test]
+fn toml_array_of_tables_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("toml")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"servers\":[{\"ip\":\"1\"},{\"ip\":\"2\"}]}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "[[servers]]\nip = \"1\"\n\n[[servers]]\nip = \"2\"\n");
+}
+
+#[test]
+fn toml_round_trip_works() {
+    let json = "{\"a\":[{\"b\":[{\"c\":1}],\"d\":\"e\"}],\"f\":1,\"servers\":[{\"ip\":\"1\"},{\"ip\":\"2\"}]}";
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("json")
+        .arg("-t")
+        .arg("toml")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(json.as_bytes()).expect("Write to stdin");
+    let mut toml = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut toml).expect("Read from stdout");
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("toml")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&toml).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, json);
+}
+
+#[test]
+fn preserve_ext_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg("--preserve-ext")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&[0xd6, 0xff, 0x00, 0x00, 0x00, 0x01]).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"$msgpack_ext\":{\"data\":\"AAAAAQ==\",\"type\":-1}}");
+}
+
+#[test]
+fn preserve_ext_msgpack_works() {
+    let process = Command::new(exe_path())
+        .arg("--preserve-ext")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"time\":{\"$msgpack_ext\":{\"type\":-1,\"data\":\"AAAAAQ==\"}}}".as_bytes()).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x81, 0xa4, 0x74, 0x69, 0x6d, 0x65, 0xd6, 0xff, 0x00, 0x00, 0x00, 0x01]);
+}
+
+#[test]
+fn base64_style_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("base64")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "gaRib29sww==");
+}
+
+#[test]
+fn base64_style_url_no_padding_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("base64")
+        .arg("--base64-url")
+        .arg("--no-padding")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":\"~~~\"}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "eyJhIjoifn5-In0");
+}
+
+#[test]
+fn base64_style_delimited_output_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("base64")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "gaRib29sww==\n");
+}
+
+#[test]
+fn msgpack_ext_as_hex_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg("--msgpack-ext-as-hex")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&[0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5]).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"__ext_data__\":\"5a4af6a5\",\"__ext_type__\":-1}");
+}
+
+#[test]
+fn msgpack_restore_ext_works() {
+    let process = Command::new(exe_path())
+        .arg("--msgpack-restore-ext")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"__ext_data__\":\"5a4af6a5\",\"__ext_type__\":-1}".as_bytes()).expect("Write to stdin");
+    let mut buf: Vec<u8> = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0xd6, 0xff, 0x5a, 0x4a, 0xf6, 0xa5]);
+}
+
+#[test]
+fn check_works() {
+    let valid = env::temp_dir().join("panser_check_works.json");
+    std::fs::write(&valid, "{\"bool\":true}").expect("Write valid file");
+    let output = Command::new(exe_path())
+        .arg("--check")
+        .arg(&valid)
+        .output()
+        .expect("Run process");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("OK"));
+}
+
+#[test]
+fn check_fails() {
+    let valid = env::temp_dir().join("panser_check_fails_valid.json");
+    let invalid = env::temp_dir().join("panser_check_fails_invalid.json");
+    std::fs::write(&valid, "{\"bool\":true}").expect("Write valid file");
+    std::fs::write(&invalid, "{\"bool\":").expect("Write invalid file");
+    let output = Command::new(exe_path())
+        .arg("--check")
+        .arg(&invalid)
+        .arg(&valid)
+        .output()
+        .expect("Run process");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(&format!("{}: OK", valid.display())));
+    assert!(stderr.contains(&format!("{}: ", invalid.display())));
+}
+
+#[test]
+fn hex_radix_lower_prefix_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("h")
+        .arg("--lower")
+        .arg("--prefix")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "0x81 0xa4 0x62 0x6f 0x6f 0x6c 0xc3 ");
+}
+
+#[test]
+fn bin_radix_prefix_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("b")
+        .arg("--prefix")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("true".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "0b11000011 ");
+}
+
+#[test]
+fn hex_radix_group_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("h")
+        .arg("--group")
+        .arg("2")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "81A4 626F 6F6C C3 ");
+}
+
+#[test]
+fn hex_radix_group_width_delimited_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("h")
+        .arg("--group")
+        .arg("2")
+        .arg("--width")
+        .arg("4")
+        .arg("--delimited")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n1\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "81A4 626F \n6F6C C3 \n01 \n");
+}
+
+#[test]
+fn markdown_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("markdown")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("[{\"name\":\"a\",\"size\":1},{\"name\":\"b|c\",\"enabled\":true}]".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        &buf,
+        "| name | size | enabled |\n\
+         | --- | --- | --- |\n\
+         | a | 1 |  |\n\
+         | b\\|c |  | true |\n"
+    );
+}
+
+#[test]
+fn markdown_object_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("md")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true,\"list\":[1,2]}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        &buf,
+        "| Key | Value |\n\
+         | --- | --- |\n\
+         | bool | true |\n\
+         | list | 1, 2 |\n"
+    );
+}
+
+#[test]
+fn markdown_empty_array_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("markdown")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("[]".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "");
+}
+
+#[test]
+fn escaped_style_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("escaped")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "\\x81\\xa4bool\\xc3");
+}
+
+#[test]
+fn python_bytes_style_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--style")
+        .arg("python-bytes")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\\\\b\":\"\u{e9}\"}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "b\"{\\\"a\\\\\\\\b\\\":\\\"\\xc3\\xa9\\\"}\"");
+}
+
+#[test]
+fn tsv_output_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("tsv")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("[{\"name\":\"a\\tb\",\"size\":1},{\"name\":\"c\",\"size\":null}]".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "name\tsize\na\\tb\t1\nc\t\n");
+}
+
+#[test]
+fn tsv_input_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("tsv")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("name\tsize\na\\tb\t1\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "[{\"name\":\"a\\tb\",\"size\":\"1\"}]");
+}
+
+#[test]
+fn jobs_preserves_order_works() {
+    let process = Command::new(exe_path())
+        .arg("-d")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .arg("--jobs")
+        .arg("4")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let input = (0..200).map(|n| format!("{{\"n\":{}}}\n", n)).collect::<String>();
+    process.stdin.expect("stdin").write_all(input.as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, input);
+}
+
+#[test]
+fn radix_input_works() {
+    let process = Command::new(exe_path())
+        .arg("--radix-input")
+        .arg("h")
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("81 A4 62 6F 6F 6C C3 ".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"bool\":true}");
+}
+
+#[test]
+fn radix_input_delimited_works() {
+    let process = Command::new(exe_path())
+        .arg("--radix-input")
+        .arg("hex")
+        .arg("-d")
+        .arg("0Ah")
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("0x81,0xa4,0x62,0x6f,0x6f,0x6c,0xc3,\nC2\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"bool\":true}\nfalse\n");
+}
+
+#[test]
+fn append_works() {
+    let output = env::temp_dir().join("panser_append_works.json");
+    std::fs::write(&output, "").expect("Create output file");
+    for _ in 0..2 {
+        let mut process = Command::new(exe_path())
+            .arg("-t")
+            .arg("json")
+            .arg("--append")
+            .arg("-o")
+            .arg(&output)
+            .stdin(Stdio::piped())
+            .spawn()
+            .expect("Create process");
+        process.stdin.take().expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+        assert!(process.wait().expect("Wait for process").success());
+    }
+    let contents = std::fs::read_to_string(&output).expect("Read output file");
+    assert_eq!(&contents, "{\"bool\":true}{\"bool\":true}");
+}
+
+#[test]
+fn newline_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("-n")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"bool\":true}\n");
+}
+
+#[test]
+fn recursive_works() {
+    let input = env::temp_dir().join("panser_recursive_works");
+    let _ = std::fs::remove_dir_all(&input);
+    std::fs::create_dir_all(input.join("nested")).expect("Create input directory");
+    std::fs::write(input.join("a.json"), "{\"a\":1}").expect("Create input file");
+    std::fs::write(input.join("nested").join("b.yaml"), "b: 2").expect("Create input file");
+    std::fs::write(input.join("notes.txt"), "Not an input").expect("Create input file");
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("-R")
+        .arg(&input)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":1}{\"b\":2}");
+}
+
+#[test]
+fn recursive_output_dir_works() {
+    let input = env::temp_dir().join("panser_recursive_output_dir_works_input");
+    let output = env::temp_dir().join("panser_recursive_output_dir_works_output");
+    let _ = std::fs::remove_dir_all(&input);
+    let _ = std::fs::remove_dir_all(&output);
+    std::fs::create_dir_all(input.join("nested")).expect("Create input directory");
+    std::fs::write(input.join("a.yaml"), "a: 1").expect("Create input file");
+    std::fs::write(input.join("nested").join("b.toml"), "b = 2").expect("Create input file");
+    std::fs::write(input.join("notes.txt"), "Not an input").expect("Create input file");
+    let status = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--recursive")
+        .arg("--output-dir")
+        .arg(&output)
+        .arg(&input)
+        .status()
+        .expect("Run process");
+    assert!(status.success());
+    let a = std::fs::read_to_string(output.join("a.json")).expect("Read output file");
+    assert_eq!(&a, "{\"a\":1}");
+    let b = std::fs::read_to_string(output.join("nested").join("b.json")).expect("Read output file");
+    assert_eq!(&b, "{\"b\":2}");
+    assert!(!output.join("notes.json").exists());
+}
+
+#[test]
+fn color_always_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("h")
+        .arg("--color")
+        .arg("always")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        &buf,
+        "\u{1b}[38;5;14m81\u{1b}[0m \u{1b}[38;5;14mA4\u{1b}[0m 62 6F 6F 6C \u{1b}[38;5;14mC3\u{1b}[0m "
+    );
+}
+
+#[test]
+fn color_always_cbor_length_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("h")
+        .arg("-t")
+        .arg("cbor")
+        .arg("--color")
+        .arg("always")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("\"abcdefghijklmnopqrstuvwx\"".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert!(buf.starts_with("\u{1b}[38;5;14m78\u{1b}[0m \u{1b}[38;5;11m18\u{1b}[0m 61 62 63 "));
+    assert!(buf.ends_with(" 77 78 "));
+}
+
+#[test]
+fn color_auto_pipe_works() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("h")
+        .arg("--color")
+        .arg("auto")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "81 A4 62 6F 6F 6C C3 ");
+}
+
+#[test]
+fn from_stdin_format_header_works() {
+    let process = Command::new(exe_path())
+        .arg("--from-stdin-format-header")
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"\x04{\"a\":1}\n\x05\x81\xa1b\x02\n").expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":1}{\"b\":2}");
+}
+
+#[test]
+fn from_stdin_format_header_unknown_tag_fails() {
+    let mut process = Command::new(exe_path())
+        .arg("--from-stdin-format-header")
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"\x2a{\"a\":1}\n").expect("Write to stdin");
+    assert!(!process.wait().expect("Wait for process").success());
+}
+
+#[test]
+fn watch_works() {
+    let input = env::temp_dir().join("panser_watch_works.json");
+    let output = env::temp_dir().join("panser_watch_works.out.json");
+    std::fs::write(&input, "{\"a\":1}").expect("Create input file");
+    let _ = std::fs::remove_file(&output);
+    let mut process = Command::new(exe_path())
+        .arg("--watch")
+        .arg("-o")
+        .arg(&output)
+        .arg(&input)
+        .spawn()
+        .expect("Create process");
+    let wait_for = |expected: &str| {
+        for _ in 0..100 {
+            if std::fs::read_to_string(&output).map(|c| c == expected).unwrap_or(false) {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        false
+    };
+    let initial = wait_for("{\"a\":1}");
+    std::fs::write(&input, "{\"a\":2}").expect("Modify input file");
+    let updated = wait_for("{\"a\":2}");
+    process.kill().expect("Kill process");
+    process.wait().expect("Wait for process");
+    assert!(initial);
+    assert!(updated);
+}
+
+#[test]
+fn stats_works() {
+    let process = Command::new(exe_path())
+        .arg("--stats")
+        .arg("-d")
+        .arg("0Ah")
+        .arg("-o")
+        .arg("/dev/null")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1}\n{\"bb\":2}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stderr.expect("stderr").read_to_string(&mut buf).expect("Read from stderr");
+    assert!(buf.starts_with(
+        "Message 1: 4 bytes\n\
+         Message 2: 5 bytes\n\
+         Messages: 2\n\
+         Input: 15 bytes\n\
+         Output: 9 bytes\n\
+         Minimum: 4 bytes\n\
+         Mean: 4.50 bytes\n\
+         Maximum: 5 bytes\n\
+         Elapsed: "
+    ));
+}
+
+#[test]
+fn pretty_indent_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--pretty-indent")
+        .arg("4")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":[1]}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\n    \"a\": [\n        1\n    ]\n}");
+}
+
+#[test]
+fn pretty_tab_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--pretty-tab")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":[1]}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\n\t\"a\": [\n\t\t1\n\t]\n}");
+}
+
+#[test]
+fn pretty_indent_yaml_fails() {
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("yaml")
+        .arg("--pretty-indent")
+        .arg("4")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Run process");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("YAML"));
+}
+
+#[test]
+fn normalize_numbers_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--normalize-numbers")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1.0,\"b\":[2.5,3.0],\"c\":4}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":1,\"b\":[2.5,3],\"c\":4}");
+}
+
+#[test]
+fn force_float_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--normalize-numbers")
+        .arg("--force-float")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1,\"b\":2.5}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":1.0,\"b\":2.5}");
+}
+
+#[test]
+fn checksum_style_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("checksum")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n{\"bool\":true}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        &buf,
+        "1 33524cdb353dc763b14332a9b73a24d602e8fa8b75eaf1078cbfda644eeab429\n\
+         2 33524cdb353dc763b14332a9b73a24d602e8fa8b75eaf1078cbfda644eeab429\n"
+    );
+}
+
+#[test]
+fn checksum_style_crc32_works() {
+    let process = Command::new(exe_path())
+        .arg("--style")
+        .arg("checksum:crc32")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "1 39999504\n");
+}
+
+#[test]
+fn checksum_works() {
+    let mut child = Command::new(exe_path())
+        .arg("--checksum")
+        .arg("md5")
+        .arg("-r")
+        .arg("hex")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all("{\"bool\":true}".as_bytes())
+        .expect("Write to stdin");
+    let output = child.wait_with_output().expect("Wait for process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "81 A4 62 6F 6F 6C C3 ");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "1 a7225e10ad3e8c1f16f7e5b21c8ace00\n");
+}
+
+/// A writer that can be read after it is moved into a `Panser`.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().expect("Lock").write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn checksum_writer_works() {
+    let input = env::temp_dir().join("panser_checksum_writer_works.json");
+    let output = env::temp_dir().join("panser_checksum_writer_works.msgpack");
+    std::fs::write(&input, "{\"bool\":true}").expect("Write input");
+    let digests = Captured::default();
+    Panser::new()
+        .inputs(Some(vec![input.to_str().expect("Input path")]))
+        .output(Some(output.to_str().expect("Output path")))
+        .checksum(Some(Digest::Md5))
+        .checksum_writer(Some(Box::new(digests.clone())))
+        .run()
+        .expect("Run");
+    assert_eq!(std::fs::read(&output).expect("Read output"), vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+    assert_eq!(
+        String::from_utf8_lossy(&digests.0.lock().expect("Lock")),
+        "1 a7225e10ad3e8c1f16f7e5b21c8ace00\n"
+    );
+}
+
+#[test]
+fn trailing_newline_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("yaml")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert!(buf.ends_with('\n'));
+}
+
+#[test]
+fn no_trailing_newline_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("yaml")
+        .arg("--no-trailing-newline")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "bool: true");
+}
+
+#[test]
+fn list_formats_works() {
+    let output = Command::new(exe_path())
+        .arg("--list-formats")
+        .output()
+        .expect("Run process");
+    let buf = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = buf.lines().collect();
+    assert_eq!(lines[0], "Format      Read     Write");
+    assert!(lines.contains(&"Bincode     limited  yes"));
+    assert!(lines.contains(&"Envy        yes      no"));
+    assert!(lines.contains(&"JSON        yes      yes"));
+    assert!(lines.contains(&"Markdown    no       yes"));
+}
+
+#[test]
+fn format_byte_works() {
+    // (radix, pad, lower, prefix, expected) for the byte 10.
+    let cases = [
+        (Radix::Binary, false, false, false, "1010"),
+        (Radix::Binary, false, false, true, "0b1010"),
+        (Radix::Binary, false, true, false, "1010"),
+        (Radix::Binary, false, true, true, "0b1010"),
+        (Radix::Binary, true, false, false, "00001010"),
+        (Radix::Binary, true, false, true, "0b00001010"),
+        (Radix::Binary, true, true, false, "00001010"),
+        (Radix::Binary, true, true, true, "0b00001010"),
+        (Radix::Decimal, false, false, false, "10"),
+        (Radix::Decimal, false, false, true, "10"),
+        (Radix::Decimal, false, true, false, "10"),
+        (Radix::Decimal, false, true, true, "10"),
+        (Radix::Decimal, true, false, false, "010"),
+        (Radix::Decimal, true, false, true, "010"),
+        (Radix::Decimal, true, true, false, "010"),
+        (Radix::Decimal, true, true, true, "010"),
+        (Radix::Hexadecimal, false, false, false, "A"),
+        (Radix::Hexadecimal, false, false, true, "0xA"),
+        (Radix::Hexadecimal, false, true, false, "a"),
+        (Radix::Hexadecimal, false, true, true, "0xa"),
+        (Radix::Hexadecimal, true, false, false, "0A"),
+        (Radix::Hexadecimal, true, false, true, "0x0A"),
+        (Radix::Hexadecimal, true, true, false, "0a"),
+        (Radix::Hexadecimal, true, true, true, "0x0a"),
+        (Radix::Octal, false, false, false, "12"),
+        (Radix::Octal, false, false, true, "0o12"),
+        (Radix::Octal, false, true, false, "12"),
+        (Radix::Octal, false, true, true, "0o12"),
+        (Radix::Octal, true, false, false, "012"),
+        (Radix::Octal, true, false, true, "0o012"),
+        (Radix::Octal, true, true, false, "012"),
+        (Radix::Octal, true, true, true, "0o012"),
+    ];
+    for &(radix, pad, lower, prefix, expected) in cases.iter() {
+        let style = RadixStyle {
+            lower,
+            pad,
+            prefix,
+            radix,
+        };
+        assert_eq!(panser::format_byte(10, &style), expected, "{:?}", style);
+    }
+}
+
+#[test]
+fn line_numbers_works() {
+    let process = Command::new(exe_path())
+        .arg("--line-numbers")
+        .arg("-t")
+        .arg("json")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1}\n{\"a\":2}\n{\"a\":3}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "1: {\"a\":1}\n2: {\"a\":2}\n3: {\"a\":3}\n");
+}
+
+#[test]
+fn line_numbers_every_works() {
+    let process = Command::new(exe_path())
+        .arg("--line-numbers")
+        .arg("--every")
+        .arg("2")
+        .arg("-t")
+        .arg("json")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1}\n{\"a\":2}\n{\"a\":3}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "1: {\"a\":1}\n3: {\"a\":3}\n");
+}
+
+#[test]
+fn line_numbers_binary_warns() {
+    let mut child = Command::new(exe_path())
+        .arg("--line-numbers")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all("{\"bool\":true}".as_bytes())
+        .expect("Write to stdin");
+    let output = child.wait_with_output().expect("Wait for process");
+    assert!(output.stdout.starts_with(b"1: "));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Warning: "));
+}
+
+#[test]
+fn frame_reader_sized_works() {
+    let input: &[u8] = &[
+        0x00, 0x00, 0x00, 0x07, 0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3,
+        0x00, 0x00, 0x00, 0x07, 0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC2,
+        0x00, 0x00,
+    ];
+    let values = FrameReader::new(input, FromFormat::Msgpack, Some(Framing::Sized))
+        .collect::<panser::Result<Vec<_>>>()
+        .expect("Read frames");
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0]["bool"], true);
+    assert_eq!(values[1]["bool"], false);
+}
+
+#[test]
+fn annotate_works() {
+    let process = Command::new(exe_path())
+        .arg("--annotate")
+        .arg("-r")
+        .arg("hex")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n{\"bool\":false}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        &buf,
+        "# frame 1, 13 bytes, offset 0x0\n\
+         81 A4 62 6F 6F 6C C3 \n\
+         # frame 2, 14 bytes, offset 0xE\n\
+         81 A4 62 6F 6F 6C C2 \n"
+    );
+}
+
+#[test]
+fn stop_after_works() {
+    let mut child = Command::new(exe_path())
+        .arg("--stop-after")
+        .arg("3")
+        .arg("-t")
+        .arg("json")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    // The stdin pipe is kept open, so the process must not wait for the End-of-File (EOF).
+    let mut stdin = child.stdin.take().expect("stdin");
+    stdin.write_all("{\"a\":1}\n{\"a\":2}\n{\"a\":3}\n{\"a\":4}\n".as_bytes()).expect("Write to stdin");
+    stdin.flush().expect("Flush stdin");
+    let mut status = None;
+    for _ in 0..50 {
+        status = child.try_wait().expect("Wait for process");
+        if status.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    if status.is_none() {
+        child.kill().expect("Kill process");
+    }
+    let mut buf = String::new();
+    child.stdout.take().expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    drop(stdin);
+    assert!(status.map_or(false, |s| s.success()));
+    assert_eq!(&buf, "{\"a\":1}\n{\"a\":2}\n{\"a\":3}\n");
+}
+
+#[test]
+fn stop_after_skipped_works() {
+    let process = Command::new(exe_path())
+        .arg("--stop-after")
+        .arg("2")
+        .arg("--continue-on-error")
+        .arg("-t")
+        .arg("json")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1}\nbad\n{\"a\":2}\n{\"a\":3}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stderr.expect("stderr").read_to_string(&mut buf).expect("Read from stderr");
+    assert!(buf.contains("Skipped 1 message(s) that failed to deserialize"));
+}
+
+#[test]
+fn frame_writer_radix_matches_cli() {
+    let process = Command::new(exe_path())
+        .arg("-r")
+        .arg("hex")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n{\"bool\":false}\n".as_bytes()).expect("Write to stdin");
+    let mut expected = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut expected).expect("Read from stdout");
+    let mut writer = FrameWriter::new(Vec::new(), Some(Framing::Delimited(b'\n')))
+        .radix(Some(RadixStyle::new(Radix::Hexadecimal)));
+    for input in &["{\"bool\":true}", "{\"bool\":false}"] {
+        let value = panser::deserialize(input.as_bytes(), FromFormat::Json).expect("Deserialize");
+        writer.write_value(&value, ToFormat::Msgpack).expect("Write value");
+    }
+    assert_eq!(writer.into_inner(), expected);
+}
+
+#[test]
+fn diff_against_identical_works() {
+    let reference = env::temp_dir().join("panser_diff_against_identical.msgpack");
+    std::fs::write(&reference, [0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]).expect("Create reference file");
+    let mut child = Command::new(exe_path())
+        .arg("--diff-against")
+        .arg(&reference)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all("{\"bool\":true}".as_bytes())
+        .expect("Write to stdin");
+    let output = child.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "identical (7 bytes)\n");
+}
+
+#[test]
+fn diff_against_differs_works() {
+    let reference = env::temp_dir().join("panser_diff_against_differs.msgpack");
+    std::fs::write(&reference, [0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC2, 0x00]).expect("Create reference file");
+    let mut child = Command::new(exe_path())
+        .arg("--diff-against")
+        .arg(&reference)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all("{\"bool\":true}".as_bytes())
+        .expect("Write to stdin");
+    let output = child.wait_with_output().expect("Wait for process");
+    assert_eq!(output.status.code(), Some(6));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Offset    Expected  Actual\n\
+         00000006  c2        c3\n\
+         00000007  00        --\n\
+         2 byte(s) differ, expected 8 bytes, actual 7 bytes\n"
+    );
+}
+
+#[test]
+fn every_works() {
+    let process = Command::new(exe_path())
+        .arg("--every")
+        .arg("3")
+        .arg("-t")
+        .arg("json")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let input = (0..9).map(|i| format!("{{\"a\":{}}}\n", i)).collect::<String>();
+    process.stdin.expect("stdin").write_all(input.as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":0}\n{\"a\":3}\n{\"a\":6}\n");
+}
+
+#[test]
+fn every_jobs_works() {
+    let process = Command::new(exe_path())
+        .arg("--every")
+        .arg("3")
+        .arg("-j")
+        .arg("3")
+        .arg("-t")
+        .arg("json")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let input = (0..9).map(|i| format!("{{\"a\":{}}}\n", i)).collect::<String>();
+    process.stdin.expect("stdin").write_all(input.as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":0}\n{\"a\":3}\n{\"a\":6}\n");
+}
+
+#[test]
+fn dotenv_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("dotenv")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("# A comment\nHOST=localhost\nPORT=8080\nNAME=\"panser app\"\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"HOST\":\"localhost\",\"NAME\":\"panser app\",\"PORT\":\"8080\"}");
+}
+
+#[test]
+fn dotenv_file_name_works() {
+    let dir = env::temp_dir().join("panser_dotenv_file_name_works");
+    std::fs::create_dir_all(&dir).expect("Create directory");
+    let input = dir.join(".env");
+    std::fs::write(&input, "KEY=value\n").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"KEY\":\"value\"}");
+}
+
+#[test]
+fn tee_radix_works() {
+    let mut child = Command::new(exe_path())
+        .arg("--tee-radix")
+        .arg("hex")
+        .arg("--sized-output")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all("{\"bool\":true}".as_bytes())
+        .expect("Write to stdin");
+    let output = child.wait_with_output().expect("Wait for process");
+    assert_eq!(
+        output.stdout,
+        vec![0x00, 0x00, 0x00, 0x07, 0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "# frame 1, 13 bytes, offset 0x0\n0 0 0 7 81 A4 62 6F 6F 6C C3 \n"
+    );
+}
+
+#[test]
+fn pipeline_yaml_to_sorted_json_is_deterministic() {
+    let pipeline = Pipeline::new()
+        .step(Step::Transcode {
+            from: FromFormat::Yaml,
+            to: ToFormat::Json,
+        })
+        .step(Step::Transcode {
+            from: FromFormat::Json,
+            to: ToFormat::Json,
+        })
+        .step(Step::SortKeys);
+    let first = pipeline::run(b"zeta: 1\nalpha:\n  c: 3\n  b: 2\n", &pipeline).expect("Run");
+    let second = pipeline::run(b"alpha:\n  b: 2\n  c: 3\nzeta: 1\n", &pipeline).expect("Run");
+    assert_eq!(first, b"{\"alpha\":{\"b\":2,\"c\":3},\"zeta\":1}".to_vec());
+    assert_eq!(first, second);
+}
+
+#[test]
+fn pipeline_filter_map_and_wrap_works() {
+    let pipeline = Pipeline::new()
+        .step(Step::Transcode {
+            from: FromFormat::Json,
+            to: ToFormat::Json,
+        })
+        .step(Step::Extract(String::from("/data")))
+        .step(Step::Filter(Box::new(|v| v["keep"] == true)))
+        .step(Step::UnwrapArray)
+        .step(Step::Map(Box::new(|v| v["id"].clone())))
+        .step(Step::WrapArray);
+    let input = b"{\"data\":[{\"id\":1,\"keep\":false},{\"id\":2,\"keep\":true}]}";
+    let output = pipeline::run(input, &pipeline).expect("Run");
+    assert_eq!(output, b"[2]".to_vec());
+}
+
+#[test]
+fn pipeline_without_transcode_step_fails() {
+    let pipeline = Pipeline::new().step(Step::SortKeys);
+    assert!(pipeline::run(b"{}", &pipeline).is_err());
+}
+
+#[test]
+fn parse_radix_bytes_round_trip_works() {
+    let binary = Command::new(exe_path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    binary.stdin.expect("stdin").write_all("{\"bool\":true,\"a\":[1,2]}".as_bytes()).expect("Write to stdin");
+    let mut expected = Vec::new();
+    binary.stdout.expect("stdout").read_to_end(&mut expected).expect("Read from stdout");
+    for &(radix, name) in &[
+        (Radix::Binary, "bin"),
+        (Radix::Decimal, "dec"),
+        (Radix::Hexadecimal, "hex"),
+        (Radix::Octal, "oct"),
+    ] {
+        let process = Command::new(exe_path())
+            .arg("-r")
+            .arg(name)
+            .arg("--prefix")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Create process");
+        process.stdin.expect("stdin").write_all("{\"bool\":true,\"a\":[1,2]}".as_bytes()).expect("Write to stdin");
+        let mut buf = String::new();
+        process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+        assert_eq!(parse_radix_bytes(&buf, radix).expect("Parse"), expected);
+    }
+}
+
+#[test]
+fn parse_radix_bytes_error_includes_token() {
+    let err = parse_radix_bytes("81 A4 ZZ", Radix::Hexadecimal).unwrap_err();
+    assert_eq!(err.code(), ExitCode::ParseInt);
+    assert_eq!(i32::from(err.code()), 4);
+    assert!(err.to_string().contains("'ZZ'"));
+}
+
+#[test]
+fn radix_from_str_bytes_works() {
+    let bytes = Radix::from_str_bytes("0o201,0o244 0o142", Radix::Octal).expect("Parse");
+    assert_eq!(bytes, vec![0x81, 0xA4, 0x62]);
+}
+
+#[test]
+fn output_bom_yaml_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("yaml")
+        .arg("--output-bom")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let mut buf = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, b"\xEF\xBB\xBFbool: true\n".to_vec());
+}
+
+#[test]
+fn output_charset_utf16le_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--output-charset")
+        .arg("utf16le")
+        .arg("--output-bom")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":\"é\"}".as_bytes()).expect("Write to stdin");
+    let mut buf = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        buf,
+        vec![
+            0xFF, 0xFE, b'{', 0, b'"', 0, b'a', 0, b'"', 0, b':', 0, b'"', 0, 0xE9, 0, b'"', 0,
+            b'}', 0
+        ]
+    );
+}
+
+#[test]
+fn output_charset_binary_format_fails() {
+    let mut process = Command::new(exe_path())
+        .arg("--output-charset")
+        .arg("utf16be")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let status = process.wait().expect("Wait for process");
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn input_bom_is_stripped() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"\xEF\xBB\xBF{\"bool\":true}").expect("Write to stdin");
+    let mut buf = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(buf, vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+}
+
+#[test]
+fn input_bom_is_stripped_from_delimited_file() {
+    let input = env::temp_dir().join("panser_input_bom_is_stripped_from_delimited_file.json");
+    std::fs::write(&input, b"\xEF\xBB\xBF{\"a\":1}\n{\"b\":2}\n").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("-d")
+        .arg("0Ah")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}\n{\"b\":2}\n");
+}
+
+#[test]
+fn detect_from_extension_works() {
+    assert_eq!(
+        FromFormat::detect_from_extension("yml").map(|f| f.to_string()),
+        Some(String::from("YAML"))
+    );
+    assert_eq!(
+        FromFormat::detect_from_extension("MPK").map(|f| f.to_string()),
+        Some(String::from("Msgpack"))
+    );
+    assert_eq!(
+        FromFormat::detect_from_extension("flexbuf").map(|f| f.to_string()),
+        Some(String::from("FlexBuffers"))
+    );
+    assert_eq!(
+        FromFormat::detect_from_extension("json").map(|f| f.to_string()),
+        Some(String::from("JSON"))
+    );
+    assert!(FromFormat::detect_from_extension("txt").is_none());
+}
+
+#[test]
+fn yml_extension_input_works() {
+    let input = env::temp_dir().join("panser_yml_extension_input_works.yml");
+    std::fs::write(&input, "bool: true\n").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}");
+}
+
+#[test]
+fn gzip_input_works() {
+    let input = env::temp_dir().join("panser_gzip_input_works.yml.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"bool: true\n").expect("Compress");
+    std::fs::write(&input, encoder.finish().expect("Compress")).expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}");
+}
+
+#[test]
+fn gzip_input_with_from_works() {
+    let input = env::temp_dir().join("panser_gzip_input_with_from_works.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"bool = true\n").expect("Compress");
+    std::fs::write(&input, encoder.finish().expect("Compress")).expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-f")
+        .arg("toml")
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}");
+}
+
+#[test]
+fn connect_works() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Bind listener");
+    let address = listener.local_addr().expect("Local address");
+    let mut child = Command::new(exe_path())
+        .arg("--connect")
+        .arg(address.to_string())
+        .arg("--sized-output")
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all("{\"bool\":true}".as_bytes())
+        .expect("Write to stdin");
+    let (mut stream, _) = listener.accept().expect("Accept connection");
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).expect("Read from stream");
+    assert!(child.wait().expect("Wait for process").success());
+    assert_eq!(buf, vec![0x00, 0x00, 0x00, 0x07, 0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+}
+
+#[test]
+fn connect_color_auto_works() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Bind listener");
+    let address = listener.local_addr().expect("Local address");
+    // The stdout of the process is inherited, so it may be a terminal, but the output is sent to
+    // the server and must never be colored.
+    let mut child = Command::new(exe_path())
+        .arg("--connect")
+        .arg(address.to_string())
+        .arg("-r")
+        .arg("h")
+        .arg("--color")
+        .arg("auto")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .spawn()
+        .expect("Create process");
+    child
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all("{\"bool\":true}".as_bytes())
+        .expect("Write to stdin");
+    let (mut stream, _) = listener.accept().expect("Accept connection");
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).expect("Read from stream");
+    assert!(child.wait().expect("Wait for process").success());
+    assert_eq!(&buf, "81 A4 62 6F 6F 6C C3 ");
+}
+
+#[test]
+fn connect_refused_fails() {
+    let address = {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Bind listener");
+        listener.local_addr().expect("Local address")
+    };
+    let output = Command::new(exe_path())
+        .arg("--connect")
+        .arg(address.to_string())
+        .stdin(Stdio::null())
+        .output()
+        .expect("Run process");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(&address.to_string()));
+}
+
+#[test]
+fn output_newline_works() {
+    let process = Command::new(exe_path())
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .arg("--output-newline")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n{\"n\":10}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    assert_eq!(
+        buf,
+        vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3, 0x0A, 0x81, 0xA1, 0x6E, 0x0A, 0x0A]
+    );
+}
+
+#[test]
+fn output_newline_is_not_a_delimiter() {
+    let output = Command::new(exe_path())
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .arg("--output-newline")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            child.stdin.take().expect("stdin").write_all("{\"n\":10}\n".as_bytes())?;
+            child.wait_with_output()
+        })
+        .expect("Run process");
+    // The newline byte within the message, the integer 10, is read as a delimiter.
+    let mut child = Command::new(exe_path())
+        .arg("-f")
+        .arg("msgpack")
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Create process");
+    child.stdin.take().expect("stdin").write_all(&output.stdout).expect("Write to stdin");
+    assert!(!child.wait().expect("Wait for process").success());
+}
+
+#[test]
+fn stats_json_works() {
+    let process = Command::new(exe_path())
+        .arg("--stats-json")
+        .arg("-d")
+        .arg("0Ah")
+        .arg("-o")
+        .arg("/dev/null")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1}\n{\"bb\":2}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stderr.expect("stderr").read_to_string(&mut buf).expect("Read from stderr");
+    assert_eq!(buf.lines().count(), 1);
+    let stats: serde_json::Value = serde_json::from_str(&buf).expect("Parse JSON");
+    assert_eq!(stats["messages"], 2);
+    assert_eq!(stats["input_bytes"], 15);
+    assert_eq!(stats["output_bytes"], 9);
+    assert_eq!(stats["min_bytes"], 4);
+    assert_eq!(stats["mean_bytes"], 4.5);
+    assert_eq!(stats["max_bytes"], 5);
+    assert_eq!(stats["input_formats"]["JSON"], 2);
+    assert_eq!(stats["output_format"], "Msgpack");
+    assert!(stats["elapsed_seconds"].is_f64());
+}
+
+#[test]
+fn stats_json_stop_after_works() {
+    let process = Command::new(exe_path())
+        .arg("--stats-json")
+        .arg("--stop-after")
+        .arg("2")
+        .arg("--continue-on-error")
+        .arg("-d")
+        .arg("0Ah")
+        .arg("-o")
+        .arg("/dev/null")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1}\nbad\n{\"bb\":2}\n".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stderr.expect("stderr").read_to_string(&mut buf).expect("Read from stderr");
+    let line = buf.lines().last().expect("Stats line");
+    let stats: serde_json::Value = serde_json::from_str(line).expect("Parse JSON");
+    assert_eq!(stats["messages"], 2);
+    assert_eq!(stats["skipped"], 1);
+    assert_eq!(stats["input_formats"]["JSON"], 2);
+}
+
+#[test]
+fn listen_works() {
+    let address = {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Bind listener");
+        listener.local_addr().expect("Local address")
+    };
+    let child = Command::new(exe_path())
+        .arg("--listen")
+        .arg(address.to_string())
+        .arg("--sized-input")
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    // The process may not be listening yet, so the connection is retried for a few seconds.
+    let mut stream = (0..100)
+        .find_map(|_| {
+            TcpStream::connect(address).ok().or_else(|| {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                None
+            })
+        })
+        .expect("Connect to process");
+    stream
+        .write_all(&[0x00, 0x00, 0x00, 0x07, 0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3])
+        .expect("Write to stream");
+    drop(stream);
+    let output = child.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}\n");
+}
+
+#[test]
+fn listen_address_in_use_fails() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Bind listener");
+    let address = listener.local_addr().expect("Local address");
+    let output = Command::new(exe_path())
+        .arg("--listen")
+        .arg(address.to_string())
+        .stdin(Stdio::null())
+        .output()
+        .expect("Run process");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(&address.to_string()));
+}
+
+#[test]
+fn smile_works() {
+    let input = "{\"array\":[null,false,true,-16,15,-1000,100000,5000000000,18446744073709551615,1.5],\"long\":\"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\",\"string\":\"\",\"unicode\":\"h\u{e9}llo\"}";
+    let value: serde_json::Value = serde_json::from_str(input).expect("Parse JSON");
+    let smile = panser::serialize_value(&value, ToFormat::Smile).expect("Serialize");
+    assert_eq!(&smile[..4], b":)\n\x00");
+    let output = panser::deserialize(&smile, FromFormat::Smile).expect("Deserialize");
+    assert_eq!(output, value);
+}
+
+#[test]
+fn smile_nested_too_deep_fails() {
+    let mut input = b":)\n\x00".to_vec();
+    input.extend(std::iter::repeat(0xF8).take(200_000));
+    match panser::deserialize(&input, FromFormat::Smile) {
+        Err(Error::Smile(message)) => assert!(message.contains("nested")),
+        result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+    }
+    let mut input = b":)\n\x00".to_vec();
+    input.extend(std::iter::repeat(0xF8).take(128));
+    input.push(0x21);
+    input.extend(std::iter::repeat(0xF9).take(128));
+    assert!(panser::deserialize(&input, FromFormat::Smile).is_ok());
+}
+
+#[test]
+fn smile_shared_names_works() {
+    // Written by Jackson with shared names, so the second name is a back-reference.
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("smile")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&[0x3A, 0x29, 0x0A, 0x01, 0xF8, 0xFA, 0x80, 0x61, 0xC2, 0xFB, 0xFA, 0x40, 0xC4, 0xFB, 0xF9]).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "[{\"a\":1},{\"a\":2}]");
+}
+
+#[test]
+fn smile_radix_works() {
+    let process = Command::new(exe_path())
+        .arg("-t")
+        .arg("smile")
+        .arg("-r")
+        .arg("h")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"a\":1}".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "3A 29 A 0 FA 80 61 C2 FB ");
+}
+
+#[test]
+fn input_null_terminated_works() {
+    let process = Command::new(exe_path())
+        .arg("-0")
+        .arg("--from")
+        .arg("json")
+        .arg("--to")
+        .arg("yaml")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"\"./a.json\"\x00\"./b.json\"\x00").expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "./a.json\n./b.json\n");
+}
+
+#[test]
+fn output_null_terminated_works() {
+    let process = Command::new(exe_path())
+        .arg("--input-null-terminated")
+        .arg("--output-null-terminated")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"{\"a\":1}\x00[2]\x00").expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\":1}\x00[2]\x00");
+}
+
+#[test]
+fn input_null_terminated_sized_fails() {
+    let output = Command::new(exe_path())
+        .arg("-0")
+        .arg("--sized-input")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Run process");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn udp_listen_works() {
+    let address = UdpSocket::bind("127.0.0.1:0")
+        .and_then(|s| s.local_addr())
+        .expect("Free address");
+    let mut child = Command::new(exe_path())
+        .arg("--udp-listen")
+        .arg(address.to_string())
+        .arg("--stop-after")
+        .arg("2")
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    // A datagram sent before the process is listening is lost, so datagrams are sent until the
+    // process stops.
+    let socket = UdpSocket::bind("127.0.0.1:0").expect("Bind socket");
+    for _ in 0..100 {
+        socket.send_to(&[0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3], address).expect("Send datagram");
+        if child.try_wait().expect("Wait for process").is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let output = child.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}\n{\"bool\":true}\n");
+}
+
+#[test]
+fn udp_max_size_works() {
+    let address = UdpSocket::bind("127.0.0.1:0")
+        .and_then(|s| s.local_addr())
+        .expect("Free address");
+    let mut child = Command::new(exe_path())
+        .arg("--udp-listen")
+        .arg(address.to_string())
+        .arg("--udp-max-size")
+        .arg("4")
+        .arg("--stop-after")
+        .arg("1")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let socket = UdpSocket::bind("127.0.0.1:0").expect("Bind socket");
+    for _ in 0..100 {
+        socket.send_to(b"[1,2,3]", address).expect("Send datagram");
+        socket.send_to(b"[1]", address).expect("Send datagram");
+        if child.try_wait().expect("Wait for process").is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    let output = child.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[1]");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("larger than the maximum datagram size"));
+}
+
+#[test]
+fn udp_send_works() {
+    let socket = UdpSocket::bind("127.0.0.1:0").expect("Bind socket");
+    socket.set_read_timeout(Some(std::time::Duration::from_secs(5))).expect("Set timeout");
+    let address = socket.local_addr().expect("Local address");
+    let process = Command::new(exe_path())
+        .arg("--udp-send")
+        .arg(address.to_string())
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}\n[1]\n".as_bytes()).expect("Write to stdin");
+    let mut buf = [0; 64];
+    let (len, _) = socket.recv_from(&mut buf).expect("Receive datagram");
+    assert_eq!(&buf[..len], &[0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+    let (len, _) = socket.recv_from(&mut buf).expect("Receive datagram");
+    assert_eq!(&buf[..len], &[0x91, 0x01]);
+}
+
+#[test]
+fn with_input_transform_works() {
+    let input = env::temp_dir().join("panser_with_input_transform_works.msgpack");
+    let output = env::temp_dir().join("panser_with_input_transform_works.json");
+    // Each sized frame is a 2-byte sequence number followed by the Msgpack data.
+    std::fs::write(
+        &input,
+        [
+            0x00, 0x00, 0x00, 0x09, 0x00, 0x01, 0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3,
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x02, 0x91, 0x01,
+        ],
+    )
+    .expect("Write input");
+    Panser::new()
+        .inputs(Some(vec![input.to_str().expect("Input path")]))
+        .output(Some(output.to_str().expect("Output path")))
+        .from(Some(FromFormat::Msgpack))
+        .sized_input(true)
+        .delimited_output(Some("0Ah"))
+        .with_input_transform(|bytes| Ok(bytes[2..].to_vec()))
+        .run()
+        .expect("Run");
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), "{\"bool\":true}\n[1]\n");
+}
+
+#[test]
+fn with_output_transform_works() {
+    let input = env::temp_dir().join("panser_with_output_transform_works.json");
+    let output = env::temp_dir().join("panser_with_output_transform_works.msgpack");
+    std::fs::write(&input, "[1]\n[2]\n").expect("Write input");
+    let sequence = Arc::new(Mutex::new(0u8));
+    Panser::new()
+        .inputs(Some(vec![input.to_str().expect("Input path")]))
+        .output(Some(output.to_str().expect("Output path")))
+        .delimited_input(Some("0Ah"))
+        .sized_output(true)
+        .with_output_transform(move |bytes| {
+            let mut sequence = sequence.lock().expect("Lock");
+            *sequence += 1;
+            let mut transformed = vec![0x00, *sequence];
+            transformed.extend(bytes);
+            Ok(transformed)
+        })
+        .run()
+        .expect("Run");
+    assert_eq!(
+        std::fs::read(&output).expect("Read output"),
+        vec![0x00, 0x00, 0x00, 0x04, 0x00, 0x01, 0x91, 0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x02, 0x91, 0x02]
+    );
+}
+
+#[test]
+fn with_output_transform_jobs_works() {
+    let input = env::temp_dir().join("panser_with_output_transform_jobs_works.json");
+    let output = env::temp_dir().join("panser_with_output_transform_jobs_works.json.out");
+    let lines: Vec<String> = (1..=200).map(|n| format!("[{}]\n", n)).collect();
+    std::fs::write(&input, lines.concat()).expect("Write input");
+    let sequence = Arc::new(Mutex::new(0));
+    Panser::new()
+        .inputs(Some(vec![input.to_str().expect("Input path")]))
+        .output(Some(output.to_str().expect("Output path")))
+        .delimited_input(Some("0Ah"))
+        .delimited_output(Some("0Ah"))
+        .to(Some(ToFormat::Json))
+        .jobs(Some(4))
+        .with_output_transform(move |bytes| {
+            let mut sequence = sequence.lock().expect("Lock");
+            *sequence += 1;
+            let mut transformed = format!("{}:", sequence).into_bytes();
+            transformed.extend(bytes);
+            Ok(transformed)
+        })
+        .run()
+        .expect("Run");
+    let expected: Vec<String> = (1..=200).map(|n| format!("{}:[{}]\n", n, n)).collect();
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), expected.concat());
+}
+
+#[test]
+fn delimited_character_works() {
+    let process = Command::new(exe_path())
+        .arg("--delimited-input")
+        .arg(";")
+        .arg("--delimited-output")
+        .arg("\\n")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("[1];{\"a\":2};".as_bytes()).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "[1]\n{\"a\":2}\n");
+}
+
+#[test]
+fn delimited_multibyte_character_fails() {
+    let output = Command::new(exe_path())
+        .arg("-d")
+        .arg("é")
+        .stdin(Stdio::null())
+        .output()
+        .expect("Run process");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a single byte in UTF-8"));
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_listen_works() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream;
+
+    let path = env::temp_dir().join("panser_unix_listen_works.sock");
+    let _ = std::fs::remove_file(&path);
+    let child = Command::new(exe_path())
+        .arg("--unix-listen")
+        .arg(&path)
+        .arg("--unix-mode")
+        .arg("600")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let mut stream = (0..100)
+        .find_map(|_| {
+            UnixStream::connect(&path).ok().or_else(|| {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                None
+            })
+        })
+        .expect("Connect to process");
+    let mode = std::fs::metadata(&path).expect("Socket metadata").permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    stream.write_all("{\"bool\":true}".as_bytes()).expect("Write to stream");
+    drop(stream);
+    let output = child.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}");
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_listen_stale_socket_works() {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = env::temp_dir().join("panser_unix_listen_stale_socket_works.sock");
+    let _ = std::fs::remove_file(&path);
+    // The listener is dropped without removing the socket file, like a server that crashed.
+    drop(UnixListener::bind(&path).expect("Bind listener"));
+    assert!(path.exists());
+    let child = Command::new(exe_path())
+        .arg("--unix-listen")
+        .arg(&path)
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let mut stream = (0..100)
+        .find_map(|_| {
+            UnixStream::connect(&path).ok().or_else(|| {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                None
+            })
+        })
+        .expect("Connect to process");
+    stream.write_all("[1]".as_bytes()).expect("Write to stream");
+    drop(stream);
+    let output = child.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[1]");
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_listen_not_socket_fails() {
+    let path = env::temp_dir().join("panser_unix_listen_not_socket_fails.sock");
+    std::fs::write(&path, "not a socket").expect("Write file");
+    let output = Command::new(exe_path())
+        .arg("--unix-listen")
+        .arg(&path)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Run process");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a socket file"));
+    assert_eq!(std::fs::read_to_string(&path).expect("Read file"), "not a socket");
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_connect_works() {
+    use std::os::unix::net::UnixListener;
+
+    let path = env::temp_dir().join("panser_unix_connect_works.sock");
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).expect("Bind listener");
+    let process = Command::new(exe_path())
+        .arg("--unix-connect")
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all("{\"bool\":true}".as_bytes()).expect("Write to stdin");
+    let (mut stream, _) = listener.accept().expect("Accept connection");
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).expect("Read from stream");
+    assert_eq!(buf, vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3]);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn stringify_keys_works() {
+    let process = Command::new(exe_path())
+        .arg("--stringify-keys")
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    // {1: "a", -2: {true: 3}}
+    process.stdin.expect("stdin").write_all(&[0x82, 0x01, 0xA1, 0x61, 0xFE, 0x81, 0xC3, 0x03]).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"-2\":{\"true\":3},\"1\":\"a\"}");
+}
+
+#[test]
+fn integer_keys_fails() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.as_ref().expect("stdin").write_all(&[0x81, 0x01, 0xA1, 0x61]).expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn resp_works() {
+    let process = Command::new(exe_path())
+        .arg("-f")
+        .arg("resp")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n").expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "[\"GET\",\"key\"]");
+}
+
+#[test]
+fn resp3_works() {
+    let input = b"%3\r\n+a\r\n~2\r\n:1\r\n(18446744073709551615\r\n$1\r\nb\r\n-ERR unknown\r\n:2\r\n|1\r\n+ttl\r\n:3\r\n#t\r\n";
+    let output = panser::deserialize(input, FromFormat::Resp).expect("Deserialize");
+    assert_eq!(output.to_string(), "{\"2\":true,\"a\":[1,18446744073709551615],\"b\":{\"error\":\"ERR unknown\"}}");
+}
+
+#[test]
+fn resp_round_trip_works() {
+    let input = "{\"array\":[null,false,true,-1,18446744073709551615,1.5],\"string\":\"a\\r\\nb\"}";
+    let value: serde_json::Value = serde_json::from_str(input).expect("Parse JSON");
+    let resp = panser::serialize_value(&value, ToFormat::Resp).expect("Serialize");
+    let output = panser::deserialize(&resp, FromFormat::Resp).expect("Deserialize");
+    assert_eq!(output, value);
+}
+
+#[test]
+fn resp_nested_too_deep_fails() {
+    for line in &["*1\r\n", "%1\r\n", "|0\r\n"] {
+        let input = line.repeat(200_000);
+        match panser::deserialize(input.as_bytes(), FromFormat::Resp) {
+            Err(Error::Resp(message)) => assert!(message.contains("nested")),
+            result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+        }
+    }
+    let input = format!("{}_\r\n", "*1\r\n".repeat(128));
+    assert!(panser::deserialize(input.as_bytes(), FromFormat::Resp).is_ok());
+}
+
+#[test]
+fn resp_truncated_fails() {
+    let result = panser::deserialize(b"*2\r\n$3\r\nGET\r\n", FromFormat::Resp);
+    assert_eq!(result.map_err(|e| e.code()).err(), Some(ExitCode::Transcode));
+}
+
+#[test]
+fn gzip_input_magic_works() {
+    let compressed = env::temp_dir().join("panser_gzip_input_magic_works_1.json");
+    let plain = env::temp_dir().join("panser_gzip_input_magic_works_2.json");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"{\"a\":1}").expect("Compress");
+    std::fs::write(&compressed, encoder.finish().expect("Compress")).expect("Create input file");
+    std::fs::write(&plain, "{\"b\":2}").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&compressed)
+        .arg(&plain)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"a\":1}{\"b\":2}");
+}
+
+#[test]
+fn gzip_input_stdin_works() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"{\"bool\":true}").expect("Compress");
+    let process = Command::new(exe_path())
+        .arg("--gzip-input")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&encoder.finish().expect("Compress")).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"bool\":true}");
+}
+
+#[test]
+fn gzip_output_works() {
+    let process = Command::new(exe_path())
+        .arg("--gzip-output=9")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"{\"bool\":true}").expect("Write to stdin");
+    let mut buf = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&buf[..]).read_to_string(&mut decoded).expect("Decompress");
+    assert_eq!(&decoded, "{\"bool\":true}");
+}
+
+#[test]
+fn gzip_output_extension_works() {
+    let input = env::temp_dir().join("panser_gzip_output_extension_works.yml");
+    let output = env::temp_dir().join("panser_gzip_output_extension_works.json.gz");
+    std::fs::write(&input, "bool: true\n").expect("Create input file");
+    let status = Command::new(exe_path())
+        .arg("-o")
+        .arg(&output)
+        .arg(&input)
+        .status()
+        .expect("Run process");
+    assert!(status.success());
+    let mut decoded = String::new();
+    let file = std::fs::File::open(&output).expect("Open output file");
+    flate2::read::GzDecoder::new(file).read_to_string(&mut decoded).expect("Decompress");
+    assert_eq!(&decoded, "{\"bool\":true}");
+}
+
+#[test]
+fn diff_works() {
+    let yaml = env::temp_dir().join("panser_diff_works.yml");
+    let toml = env::temp_dir().join("panser_diff_works.toml");
+    std::fs::write(&yaml, "name: panser\nserver:\n  port: 80\ntags: [a]\n").expect("Create input file");
+    std::fs::write(&toml, "tags = [\"a\", \"b\"]\n[server]\nhost = \"localhost\"\nport = 8080\n").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("--diff")
+        .arg(&yaml)
+        .arg(&toml)
+        .output()
+        .expect("Run process");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "- /name: \"panser\"\n+ /server/host: \"localhost\"\n~ /server/port: 80 -> 8080\n+ /tags/1: \"b\"\n"
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn diff_identical_works() {
+    let yaml = env::temp_dir().join("panser_diff_identical_works.yml");
+    let json = env::temp_dir().join("panser_diff_identical_works.json");
+    std::fs::write(&yaml, "bool: true\nlist: [1, 2]\n").expect("Create input file");
+    std::fs::write(&json, "{\"list\":[1,2],\"bool\":true}").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("--diff")
+        .arg(&yaml)
+        .arg(&json)
+        .output()
+        .expect("Run process");
+    assert!(output.stdout.is_empty());
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn cbor_diag_works() {
+    let process = Command::new(exe_path())
+        .arg("--cbor-diag")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"{\"a\":[1,-2,1.5,\"x\\n\",null,true,100000],\"b\":{}}").expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\": [1, -2, 1.5, \"x\\n\", null, true, 100000], \"b\": {}}\n");
+}
+
+#[test]
+fn cbor_diag_delimited_works() {
+    let process = Command::new(exe_path())
+        .arg("--cbor-diag")
+        .arg("-d")
+        .arg("0Ah")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"{\"a\":1}\n[\"b\"]\n").expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"a\": 1}\n[\"b\"]\n");
+}
+
+#[test]
+fn zstd_input_works() {
+    let input = env::temp_dir().join("panser_zstd_input_works.yml.zst");
+    std::fs::write(&input, zstd::encode_all(&b"bool: true\n"[..], 3).expect("Compress")).expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}");
+}
+
+#[test]
+fn zstd_input_magic_sized_works() {
+    // The frames are inside the compressed stream, and the file has no `.zst` extension.
+    let input = env::temp_dir().join("panser_zstd_input_magic_sized_works.msgpack");
+    let frames = [0x00, 0x00, 0x00, 0x01, 0xC3, 0x00, 0x00, 0x00, 0x01, 0xC2];
+    std::fs::write(&input, zstd::encode_all(&frames[..], 19).expect("Compress")).expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("--sized-input")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "true\nfalse\n");
+}
+
+#[test]
+fn zstd_input_stdin_works() {
+    let process = Command::new(exe_path())
+        .arg("--zstd-input")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(&zstd::encode_all(&b"{\"bool\":true}"[..], 3).expect("Compress")).expect("Write to stdin");
+    let mut buf = String::new();
+    process.stdout.expect("stdout").read_to_string(&mut buf).expect("Read from stdout");
+    assert_eq!(&buf, "{\"bool\":true}");
+}
+
+#[test]
+fn zstd_output_works() {
+    let process = Command::new(exe_path())
+        .arg("--zstd-output=19")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.expect("stdin").write_all(b"{\"bool\":true}").expect("Write to stdin");
+    let mut buf = Vec::new();
+    process.stdout.expect("stdout").read_to_end(&mut buf).expect("Read from stdout");
+    let decoded = zstd::decode_all(&buf[..]).expect("Decompress");
+    assert_eq!(String::from_utf8_lossy(&decoded), "{\"bool\":true}");
+}
+
+#[test]
+fn zstd_output_level_fails() {
+    let output = Command::new(exe_path())
+        .arg("--zstd-output=23")
+        .output()
+        .expect("Run process");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn verbosity_works() {
+    let input = env::temp_dir().join("panser_verbosity_works.json");
+    let output = env::temp_dir().join("panser_verbosity_works.msgpack");
+    std::fs::write(&input, "{\"bool\":true}").expect("Write input");
+    let log = Captured::default();
+    Panser::new()
+        .inputs(Some(vec![input.to_str().expect("Input path")]))
+        .output(Some(output.to_str().expect("Output path")))
+        .verbosity(1)
+        .verbose_writer(Some(Box::new(log.clone())))
+        .run()
+        .expect("Run");
+    assert_eq!(
+        String::from_utf8_lossy(&log.0.lock().expect("Lock")),
+        "[1] input: JSON, 13 bytes: 7B 22 62 6F 6F 6C 22 3A 74 72 75 65 7D\n\
+         [1] value: {\"bool\":true}\n\
+         [1] output: Msgpack, 7 bytes\n"
+    );
+}
+
+#[test]
+fn verbosity_all_bytes_works() {
+    let input = env::temp_dir().join("panser_verbosity_all_bytes_works.json");
+    let output = env::temp_dir().join("panser_verbosity_all_bytes_works.msgpack");
+    let long = format!("\"{}\"", "a".repeat(70));
+    std::fs::write(&input, &long).expect("Write input");
+    let log = Captured::default();
+    Panser::new()
+        .inputs(Some(vec![input.to_str().expect("Input path")]))
+        .output(Some(output.to_str().expect("Output path")))
+        .verbosity(1)
+        .verbose_writer(Some(Box::new(log.clone())))
+        .run()
+        .expect("Run");
+    let text = String::from_utf8_lossy(&log.0.lock().expect("Lock")).into_owned();
+    assert!(text.starts_with("[1] input: JSON, 72 bytes: 22 61"));
+    assert!(text.contains(" 61 ...\n"));
+    let log = Captured::default();
+    Panser::new()
+        .inputs(Some(vec![input.to_str().expect("Input path")]))
+        .output(Some(output.to_str().expect("Output path")))
+        .verbosity(2)
+        .verbose_writer(Some(Box::new(log.clone())))
+        .run()
+        .expect("Run");
+    let text = String::from_utf8_lossy(&log.0.lock().expect("Lock")).into_owned();
+    assert!(!text.contains("..."));
+    assert!(text.contains("[1] output: Msgpack, 72 bytes: D9 46 61"));
+}
+
+#[test]
+fn verbose_piped_stderr_works() {
+    let process = Command::new(exe_path())
+        .arg("-vv")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.as_ref().expect("stdin").write_all(b"{\"bool\":true}").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn follow_works() {
+    let input = env::temp_dir().join("panser_follow_works.json");
+    let frame = [0x00, 0x00, 0x00, 0x08, 0x7b, 0x22, 0x61, 0x22, 0x3a, 0x31, 0x7d, 0x20];
+    let mut data = frame.to_vec();
+    data.extend_from_slice(&frame[..6]);
+    std::fs::write(&input, &data).expect("Create input file");
+    let mut process = Command::new(exe_path())
+        .arg("--follow")
+        .arg("--sized-input")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let mut stdout = process.stdout.take().expect("stdout");
+    let mut first = [0; 8];
+    stdout.read_exact(&mut first).expect("Read first message");
+    let mut file = std::fs::OpenOptions::new().append(true).open(&input).expect("Open input file");
+    file.write_all(&frame[6..]).expect("Append to input file");
+    let mut second = [0; 8];
+    stdout.read_exact(&mut second).expect("Read second message");
+    process.kill().expect("Kill process");
+    process.wait().expect("Wait for process");
+    assert_eq!(&first, b"{\"a\":1}\n");
+    assert_eq!(&second, b"{\"a\":1}\n");
+}
+
+#[test]
+fn follow_without_framing_fails() {
+    let input = env::temp_dir().join("panser_follow_without_framing_fails.json");
+    std::fs::write(&input, "{\"a\":1}").expect("Create input file");
+    let status = Command::new(exe_path())
+        .arg("--follow")
+        .arg(&input)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .expect("Run process");
+    assert!(!status.success());
+}
+
+#[test]
+fn partial_output_on_error_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":1}\n{\"a\":\n").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(!output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}\n");
+}
+
+#[test]
+fn partial_output_on_encode_error_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--delimited-input")
+        .arg("0Ah")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("toml")
+        .arg("--jobs")
+        .arg("2")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":1}\n1\n").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(!output.status.success());
+    assert_eq!(output.stdout, b"a = 1\n\n");
+}
+
+#[test]
+fn stdin_dash_input_works() {
+    let header = env::temp_dir().join("panser_stdin_dash_input_works_header.json");
+    let footer = env::temp_dir().join("panser_stdin_dash_input_works_footer.json");
+    std::fs::write(&header, "{\"a\":1}").expect("Create header file");
+    std::fs::write(&footer, "{\"c\":3}").expect("Create footer file");
+    let mut process = Command::new(exe_path())
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .arg(&header)
+        .arg("-")
+        .arg(&footer)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"b\":2}").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}\n{\"b\":2}\n{\"c\":3}\n");
+}
+
+#[test]
+fn stdin_dash_input_twice_fails() {
+    let result = Panser::new().inputs(Some(vec!["-", "-"])).run();
+    assert!(result.is_err());
+}
+
+#[test]
+fn ascii_only_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--ascii-only")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all("[\"café\",\"😀\"]".as_bytes()).expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"[\"caf\\u00e9\",\"\\ud83d\\ude00\"]");
+}
+
+#[test]
+fn ascii_only_binary_format_ignored_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--ascii-only")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all("\"é\"".as_bytes()).expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, vec![0xa2, 0xc3, 0xa9]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning"));
+}
+
+#[test]
+fn pointer_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--pointer")
+        .arg("/servers/1/port")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"servers\":[{\"port\":80},{\"port\":8080}]}").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"8080");
+}
+
+#[test]
+fn pointer_missing_fails() {
+    let mut process = Command::new(exe_path())
+        .arg("--pointer")
+        .arg("/servers/2")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"servers\":[]}").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("/servers/2"));
+}
+
+#[test]
+fn output_dir_collision_works() {
+    let input = env::temp_dir().join("panser_output_dir_collision_works_input");
+    let output = env::temp_dir().join("panser_output_dir_collision_works_output");
+    let _ = std::fs::remove_dir_all(&input);
+    let _ = std::fs::remove_dir_all(&output);
+    std::fs::create_dir_all(input.join("a")).expect("Create input directory");
+    std::fs::create_dir_all(input.join("b")).expect("Create input directory");
+    std::fs::write(input.join("a").join("data.json"), "{\"a\":1}").expect("Create input file");
+    std::fs::write(input.join("b").join("data.yaml"), "b: 2").expect("Create input file");
+    let status = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--output-dir")
+        .arg(&output)
+        .arg(input.join("a").join("data.json"))
+        .arg(input.join("b").join("data.yaml"))
+        .status()
+        .expect("Run process");
+    assert!(status.success());
+    let a = std::fs::read_to_string(output.join("data.json")).expect("Read output file");
+    assert_eq!(&a, "{\"a\":1}");
+    let b = std::fs::read_to_string(output.join("data-1.json")).expect("Read output file");
+    assert_eq!(&b, "{\"b\":2}");
+}
+
+#[test]
+fn multiple_to_works() {
+    let stem = env::temp_dir().join("panser_multiple_to_works");
+    let json = env::temp_dir().join("panser_multiple_to_works.json");
+    let yaml = env::temp_dir().join("panser_multiple_to_works.yaml");
+    let _ = std::fs::remove_file(&json);
+    let _ = std::fs::remove_file(&yaml);
+    let mut process = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("-t")
+        .arg("yaml")
+        .arg("-o")
+        .arg(&stem)
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":1}").expect("Write to stdin");
+    assert!(process.wait().expect("Wait for process").success());
+    assert_eq!(std::fs::read_to_string(&json).expect("Read JSON output"), "{\"a\":1}");
+    assert_eq!(std::fs::read_to_string(&yaml).expect("Read YAML output"), "a: 1\n");
+}
+
+#[test]
+fn multiple_to_stdout_fails() {
+    let result = Panser::new()
+        .to(Some(ToFormat::Json))
+        .also_to(vec![ToFormat::Yaml])
+        .run();
+    assert!(result.is_err());
+}
+
+#[test]
+fn multiple_to_output_dir_works() {
+    let input = env::temp_dir().join("panser_multiple_to_output_dir_works.json");
+    let output = env::temp_dir().join("panser_multiple_to_output_dir_works_output");
+    let _ = std::fs::remove_dir_all(&output);
+    std::fs::write(&input, "{\"a\":1}").expect("Create input file");
+    let status = Command::new(exe_path())
+        .arg("-t")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg("--output-dir")
+        .arg(&output)
+        .arg(&input)
+        .status()
+        .expect("Run process");
+    assert!(status.success());
+    let msgpack = std::fs::read(output.join("panser_multiple_to_output_dir_works.msgpack"))
+        .expect("Read Msgpack output");
+    assert_eq!(msgpack, vec![0x81, 0xa1, 0x61, 0x01]);
+    let json = std::fs::read_to_string(output.join("panser_multiple_to_output_dir_works.json"))
+        .expect("Read JSON output");
+    assert_eq!(&json, "{\"a\":1}");
+}
+
+#[test]
+fn bytes_as_base64_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--bytes-as-base64")
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(&[0x81, 0xa1, 0x61, 0xc4, 0x03, 0x01, 0x02, 0x03]).expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":\"base64:AQID\"}");
+}
+
+#[test]
+fn base64_to_bytes_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--base64-to-bytes")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":\"base64:AQID\"}").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, vec![0x81, 0xa1, 0x61, 0xc4, 0x03, 0x01, 0x02, 0x03]);
+}
+
+#[test]
+fn base64_to_bytes_pretty_works() {
+    let args = ["--base64-to-bytes", "--pretty-indent", "2", "-t", "json"];
+    let output = run_with_input(&args, b"{\"a\":\"base64:AQID\"}");
+    assert_eq!(output, b"{\n  \"a\": [\n    1,\n    2,\n    3\n  ]\n}".to_vec());
+}
+
+#[test]
+fn base64_to_bytes_allow_nan_fails() {
+    let output = env::temp_dir().join("panser_base64_to_bytes_allow_nan_fails.msgpack");
+    let result = Panser::new()
+        .base64_to_bytes(true)
+        .allow_nan(true)
+        .output(Some(output.to_str().expect("Output path")))
+        .run();
+    match result {
+        Err(Error::Generic(message)) => assert!(message.contains("--base64-to-bytes")),
+        result => panic!("Unexpected result: {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn indent_tabs_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--indent-tabs")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":[1]}").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\n\t\"a\": [\n\t\t1\n\t]\n}");
+}
+
+#[test]
+fn indent_tabs_with_indent_fails() {
+    let status = Command::new(exe_path())
+        .arg("--indent-tabs")
+        .arg("--indent")
+        .arg("2")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .expect("Run process");
+    assert!(!status.success());
+}
+
+#[test]
+fn in_place_works() {
+    let dir = env::temp_dir().join("panser_in_place_works");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Create directory");
+    std::fs::write(dir.join("good.json"), "{\"a\":1}").expect("Create input file");
+    std::fs::write(dir.join("bad.json"), "{\"a\":").expect("Create input file");
+    std::fs::write(dir.join("same.json"), "{\"b\":2}").expect("Create input file");
+    let status = Command::new(exe_path())
+        .arg("-t")
+        .arg("toml")
+        .arg("--in-place")
+        .arg("--rename-ext")
+        .arg(dir.join("good.json"))
+        .arg(dir.join("bad.json"))
+        .stderr(Stdio::null())
+        .status()
+        .expect("Run process");
+    assert!(!status.success());
+    assert!(!dir.join("good.json").exists());
+    assert_eq!(std::fs::read_to_string(dir.join("good.toml")).expect("Read output"), "a = 1\n");
+    assert_eq!(std::fs::read_to_string(dir.join("bad.json")).expect("Read input"), "{\"a\":");
+    assert!(!dir.join("bad.toml").exists());
+    assert!(!dir.join(".bad.json.panser-tmp").exists());
+    let status = Command::new(exe_path())
+        .arg("-t")
+        .arg("yaml")
+        .arg("--in-place")
+        .arg(dir.join("same.json"))
+        .status()
+        .expect("Run process");
+    assert!(status.success());
+    assert_eq!(std::fs::read_to_string(dir.join("same.json")).expect("Read output"), "b: 2\n");
+}
+
+#[test]
+fn in_place_compressed_works() {
+    let input = env::temp_dir().join("panser_in_place_compressed_works.json.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"{\"a\":1}").expect("Compress input");
+    std::fs::write(&input, encoder.finish().expect("Compress input")).expect("Create input file");
+    let status = Command::new(exe_path())
+        .arg("-t")
+        .arg("yaml")
+        .arg("--in-place")
+        .arg(&input)
+        .status()
+        .expect("Run process");
+    assert!(status.success());
+    let compressed = std::fs::read(&input).expect("Read output");
+    assert!(compressed.starts_with(&[0x1F, 0x8B]));
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(&compressed[..]).read_to_string(&mut decoded).expect("Decompress");
+    assert_eq!(decoded, "a: 1\n");
+}
+
+#[test]
+fn in_place_rename_ext_existing_fails() {
+    let dir = env::temp_dir().join("panser_in_place_rename_ext_existing_fails");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("Create directory");
+    std::fs::write(dir.join("d.json"), "{\"a\":1}").expect("Create input file");
+    std::fs::write(dir.join("d.toml"), "b = 2\n").expect("Create existing file");
+    let status = Command::new(exe_path())
+        .arg("-t")
+        .arg("toml")
+        .arg("--in-place")
+        .arg("--rename-ext")
+        .arg(dir.join("d.json"))
+        .stderr(Stdio::null())
+        .status()
+        .expect("Run process");
+    assert!(!status.success());
+    assert_eq!(std::fs::read_to_string(dir.join("d.json")).expect("Read input"), "{\"a\":1}");
+    assert_eq!(std::fs::read_to_string(dir.join("d.toml")).expect("Read file"), "b = 2\n");
+    assert!(!dir.join(".d.json.panser-tmp").exists());
+}
+
+#[test]
+fn escape_html_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--escape-html")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"html\":\"<script>alert(1)</script>&\"}").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        &b"{\"html\":\"\\u003Cscript\\u003Ealert(1)\\u003C/script\\u003E\\u0026\"}"[..]
+    );
+}
+
+#[test]
+fn atomic_works() {
+    let output = env::temp_dir().join("panser_atomic_works.json");
+    let temp = env::temp_dir().join("panser_atomic_works.json.tmp");
+    std::fs::write(&output, "{\"old\":true}").expect("Create output file");
+    let mut process = Command::new(exe_path())
+        .arg("--atomic")
+        .arg("-d")
+        .arg("0Ah")
+        .arg("-o")
+        .arg(&output)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":1}\n{\"a\":\n").expect("Write to stdin");
+    assert!(!process.wait().expect("Wait for process").success());
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), "{\"old\":true}");
+    assert!(!temp.exists());
+    let mut process = Command::new(exe_path())
+        .arg("--atomic")
+        .arg("-o")
+        .arg(&output)
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":1}").expect("Write to stdin");
+    assert!(process.wait().expect("Wait for process").success());
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), "{\"a\":1}");
+    assert!(!temp.exists());
+}
+
+#[test]
+fn append_without_framing_warns() {
+    let output = env::temp_dir().join("panser_append_without_framing_warns.json");
+    let _ = std::fs::remove_file(&output);
+    let mut process = Command::new(exe_path())
+        .arg("--append")
+        .arg("-o")
+        .arg(&output)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"a\":1}").expect("Write to stdin");
+    let result = process.wait_with_output().expect("Wait for process");
+    assert!(result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("Warning"));
+}
+
+#[test]
+fn non_ascii_literal_works() {
+    // The characters are written as literal UTF-8 unless the '--ascii-only' flag is used.
+    let mut process = Command::new(exe_path())
+        .arg("-f")
+        .arg("json")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(b"{\"key\":\"\\u65e5\\u672c\\u8a9e\"}")
+        .expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).expect("UTF-8"), "{\"key\":\"日本語\"}");
+}
+
+#[test]
+fn parse_hex_dump_works() {
+    let expected = vec![0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3];
+    let dumps = [
+        "81a4626f6f6cc3",
+        "0x81, 0xA4, 0x62, 0x6F, 0x6F, 0x6C, 0xC3,",
+        "static const uint8_t data[] = {\n    0x81, 0xa4, 0x62, 0x6f,\n    0x6f, 0x6c, 0xc3\n};\n",
+        "00000000: 81a4 626f 6f6c c3                        ..bool.\n",
+        "00000000  81 a4 62 6f 6f 6c c3                             |..bool.|\n00000007\n",
+    ];
+    for dump in dumps.iter() {
+        assert_eq!(parse_hex_dump(dump).expect("Parse"), expected, "{}", dump);
+    }
+    assert!(parse_hex_dump("81 a").is_err());
+}
+
+#[test]
+fn from_base16_file_works() {
+    let input = env::temp_dir().join("panser_from_base16_file_works.txt");
+    std::fs::write(&input, "00000000: 81a4 626f 6f6c c3                        ..bool.\n")
+        .expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("--from-base16-file")
+        .arg("-f")
+        .arg("msgpack")
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"bool\":true}");
+}
+
+#[test]
+fn glob_input_works() {
+    let dir = env::temp_dir().join("panser_glob_input_works");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("b")).expect("Create directory");
+    std::fs::write(dir.join("b").join("2.json"), "{\"b\":2}").expect("Create input file");
+    std::fs::write(dir.join("1.json"), "{\"a\":1}").expect("Create input file");
+    std::fs::write(dir.join("3.yaml"), "c: 3").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .arg(dir.join("**").join("*.json"))
+        .output()
+        .expect("Run process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}\n{\"b\":2}\n");
+}
+
+#[test]
+fn glob_input_without_matches_fails() {
+    let pattern = env::temp_dir().join("panser_glob_input_without_matches_fails").join("*.json");
+    let output = Command::new(exe_path()).arg(&pattern).output().expect("Run process");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains(&*pattern.to_string_lossy()));
+}
+
+#[test]
+fn serializable_error_works() {
+    let error = parse_radix_bytes("zz", Radix::Hexadecimal).expect_err("Invalid hex");
+    let value = serde_json::to_value(&SerializableError(&error)).expect("Serialize");
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "code": 4,
+            "kind": "Parse integer",
+            "message": "Invalid number 'zz': invalid digit found in string",
+            "source_message": "invalid digit found in string",
+        })
+    );
+    let error = Error::Generic(String::from("Something failed"));
+    let value = serde_json::to_value(&SerializableError(&error)).expect("Serialize");
+    assert_eq!(value["source_message"], serde_json::Value::Null);
+}
+
+#[test]
+fn collect_errors_works() {
+    let input = env::temp_dir().join("panser_collect_errors_works.json");
+    let output = env::temp_dir().join("panser_collect_errors_works.out.json");
+    std::fs::write(&input, "{\"a\":1}\n{\"b\":\n[1,\n{\"c\":3}\n").expect("Create input file");
+    let mut panser = Panser::new()
+        .inputs(Some(vec![&*input.to_string_lossy()]))
+        .output(Some(&*output.to_string_lossy()))
+        .to(Some(ToFormat::Json))
+        .delimited_input(Some("0Ah"))
+        .delimited_output(Some("0Ah"))
+        .collect_errors(true);
+    panser.clone().run().expect("Run");
+    let errors = panser.take_errors();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|e| e.code() == ExitCode::Transcode));
+    assert!(panser.take_errors().is_empty());
+    assert_eq!(
+        std::fs::read_to_string(&output).expect("Read output"),
+        "{\"a\":1}\n{\"c\":3}\n"
+    );
+}
+
+#[test]
+fn collect_errors_encode_works() {
+    let mut panser = Panser::new()
+        .to(Some(ToFormat::Toml))
+        .jobs(Some(2))
+        .collect_errors(true);
+    let input = env::temp_dir().join("panser_collect_errors_encode_works.json");
+    let output = env::temp_dir().join("panser_collect_errors_encode_works.toml");
+    std::fs::write(&input, "{\"a\":1}\n[1,2]\n{\"b\":2}\n").expect("Create input file");
+    panser
+        .clone()
+        .inputs(Some(vec![&*input.to_string_lossy()]))
+        .output(Some(&*output.to_string_lossy()))
+        .delimited_input(Some("0Ah"))
+        .run()
+        .expect("Run");
+    assert_eq!(panser.take_errors().len(), 1);
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), "a = 1\nb = 2\n");
+}
+
+#[test]
+fn input_path_output_path_works() {
+    let input = env::temp_dir().join("panser_input_path_output_path_works.yaml");
+    let output = env::temp_dir().join("panser_input_path_output_path_works.toml");
+    std::fs::write(&input, "a: 1\nb: text\n").expect("Create input file");
+    Panser::new()
+        .input_path(&input.to_string_lossy())
+        .output_path(&output.to_string_lossy())
+        .run()
+        .expect("Run");
+    assert_eq!(
+        std::fs::read_to_string(&output).expect("Read output"),
+        "a = 1\nb = \"text\"\n"
+    );
+}
+
+#[test]
+fn output_path_unknown_extension_keeps_to() {
+    let input = env::temp_dir().join("panser_output_path_unknown_extension_keeps_to.json");
+    let output = env::temp_dir().join("panser_output_path_unknown_extension_keeps_to.out");
+    std::fs::write(&input, "{\"a\":1}").expect("Create input file");
+    Panser::new()
+        .to(Some(ToFormat::Yaml))
+        .input_path(&input.to_string_lossy())
+        .output_path(&output.to_string_lossy())
+        .run()
+        .expect("Run");
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), "a: 1\n");
+}
+
+#[test]
+fn path_explicit_format_takes_precedence_works() {
+    let input = env::temp_dir().join("panser_path_explicit_format_takes_precedence_works.json");
+    let output = env::temp_dir().join("panser_path_explicit_format_takes_precedence_works.toml");
+    std::fs::write(&input, "a: 1\n").expect("Create input file");
+    Panser::new()
+        .from(Some(FromFormat::Yaml))
+        .to(Some(ToFormat::Json))
+        .input_path(&input.to_string_lossy())
+        .output_path(&output.to_string_lossy())
+        .run()
+        .expect("Run");
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), "{\"a\":1}");
+}
+
+/// Serves a single HTTP response on a local port and returns the URL of the server.
+fn serve_http(response: &'static str) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Bind listener");
+    let address = listener.local_addr().expect("Local address");
+    std::thread::spawn(move || {
+        if let Ok((mut stream, _)) = listener.accept() {
+            let mut buf = [0; 1024];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://{}", address)
+}
+
+#[test]
+fn http_input_works() {
+    let url = serve_http(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/yaml\r\nContent-Length: 5\r\n\
+         Connection: close\r\n\r\na: 1\n",
+    );
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(format!("{}/data?format=any", url))
+        .output()
+        .expect("Run process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}");
+}
+
+#[test]
+fn http_input_not_found_fails() {
+    let url = serve_http(
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    );
+    let output = Command::new(exe_path())
+        .arg(format!("{}/data.json", url))
+        .output()
+        .expect("Run process");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("404"));
+}
+
+#[test]
+fn progress_without_terminal_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--progress")
+        .arg("-d")
+        .arg("0Ah")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(b"{\"a\":1}\n{\"b\":2}\n")
+        .expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}\n{\"b\":2}\n");
+    // The progress line is only written if stderr is a terminal.
+    assert!(output.stderr.is_empty());
+}
+
+/// Transcodes a value from JSON to a format and back to JSON.
+fn round_trip(
+    value: &serde_json::Value,
+    to: ToFormat,
+    from: FromFormat,
+) -> panser::Result<serde_json::Value> {
+    let input = serde_json::to_vec(value).expect("Serialize");
+    let output = panser::transcode(&input, FromFormat::Json, to)?;
+    let output = panser::transcode(&output, from, ToFormat::Json)?;
+    Ok(serde_json::from_slice(&output).expect("Deserialize"))
+}
+
+#[test]
+fn round_trip_lossless_formats_works() {
+    // The matrix is the contract in the "Round Trips" section of the crate documentation, so it
+    // must be updated with it.
+    let corpus = vec![
+        serde_json::json!({
+            "null": null,
+            "bool": false,
+            "int": -42,
+            "max_u64": 18_446_744_073_709_551_615u64,
+            "min_i64": -9_223_372_036_854_775_808i64,
+            "float": 0.1,
+            "large_float": 1.5e300,
+            "string": "héllo \u{1F600}",
+            "array": [1, "two", [3.5], {"four": 4}],
+            "empty_object": {},
+            "empty_array": [],
+        }),
+        serde_json::json!([1, 2, 3]),
+        serde_json::json!("string"),
+        serde_json::json!(12),
+        serde_json::json!(true),
+        serde_json::json!(null),
+    ];
+    let formats = [
+        (ToFormat::Cbor, FromFormat::Cbor),
+        (ToFormat::FlexBuffers, FromFormat::FlexBuffers),
+        (ToFormat::Hjson, FromFormat::Hjson),
+        (ToFormat::Json, FromFormat::Json),
+        (ToFormat::Msgpack, FromFormat::Msgpack),
+        (ToFormat::Resp, FromFormat::Resp),
+        (ToFormat::Smile, FromFormat::Smile),
+        (ToFormat::Yaml, FromFormat::Yaml),
+    ];
+    for &(to, from) in formats.iter() {
+        for value in &corpus {
+            let result = round_trip(value, to, from).expect("Round trip");
+            assert_eq!(&result, value, "{}", to);
+        }
+    }
+}
+
+#[test]
+fn round_trip_subset_formats_works() {
+    let table = serde_json::json!({
+        "int": -42,
+        "float": 0.5,
+        "string": "text",
+        "array": [1, 2],
+        "nested": {"bool": true},
+    });
+    assert_eq!(round_trip(&table, ToFormat::Toml, FromFormat::Toml).expect("TOML"), table);
+    assert!(round_trip(&serde_json::json!({"a": null}), ToFormat::Toml, FromFormat::Toml).is_err());
+    let strings = serde_json::json!({"a": "1", "b": "text"});
+    assert_eq!(round_trip(&strings, ToFormat::Url, FromFormat::Url).expect("URL"), strings);
+    let rows = serde_json::json!([{"a": "1", "b": "x"}, {"a": "2", "b": "y"}]);
+    assert_eq!(round_trip(&rows, ToFormat::Tsv, FromFormat::Tsv).expect("TSV"), rows);
+    assert!(round_trip(&serde_json::json!([1, 2]), ToFormat::Tsv, FromFormat::Tsv).is_err());
+    let value = serde_json::json!({
+        "null": null,
+        "bool": false,
+        "max_i64": 9_223_372_036_854_775_807i64,
+        "min_i64": -9_223_372_036_854_775_808i64,
+        "float": 0.1,
+        "string": "héllo \u{1F600}",
+        "array": [1, "two", [3.5], {"four": 4}],
+        "empty_object": {},
+    });
+    assert_eq!(round_trip(&value, ToFormat::Pickle, FromFormat::Pickle).expect("Pickle"), value);
+    let max_u64 = serde_json::json!(18_446_744_073_709_551_615u64);
+    assert!(round_trip(&max_u64, ToFormat::Pickle, FromFormat::Pickle).is_err());
+}
+
+#[test]
+fn round_trip_bincode_fails() {
+    let value = serde_json::json!({"a": 1});
+    assert!(round_trip(&value, ToFormat::Bincode, FromFormat::Bincode).is_err());
+}
+
+#[test]
+fn input_encoding_latin1_works() {
+    let path = env::temp_dir().join("panser_input_encoding_latin1.json");
+    // The 'é' and 'ü' characters are the single bytes E9h and FCh in Latin-1.
+    std::fs::write(&path, b"{\"name\":\"Ren\xE9 M\xFCller\"}").expect("Write input");
+    let output = Command::new(exe_path())
+        .arg("--input-encoding")
+        .arg("latin1")
+        .arg("-t")
+        .arg("json")
+        .arg(&path)
+        .output()
+        .expect("Run process");
+    std::fs::remove_file(&path).ok();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("UTF-8"),
+        "{\"name\":\"René Müller\"}"
+    );
+}
+
+#[test]
+fn input_encoding_utf16le_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--input-encoding")
+        .arg("UTF-16-LE")
+        .arg("-f")
+        .arg("yaml")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    let input = "key: 日本\n"
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes().to_vec())
+        .collect::<Vec<u8>>();
+    process.stdin.take().expect("stdin").write_all(&input).expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).expect("UTF-8"), "{\"key\":\"日本\"}");
+}
+
+#[test]
+fn input_encoding_unpaired_surrogate_fails() {
+    let mut process = Command::new(exe_path())
+        .arg("--input-encoding")
+        .arg("utf-16-be")
+        .arg("-f")
+        .arg("json")
+        .arg("-t")
+        .arg("json")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    // The D800h code unit is a high surrogate without the low surrogate that must follow it.
+    process
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(b"\x00\"\xD8\x00\x00\"")
+        .expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert_eq!(output.status.code(), Some(i32::from(ExitCode::Utf8)));
+}
+
+#[test]
+fn quiet_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--quiet")
+        .arg("--delimited")
+        .arg("0Ah")
+        .arg("--continue-on-error")
+        .arg("--line-numbers")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process
+        .stdin
+        .take()
+        .expect("stdin")
+        .write_all(b"{\"bool\":true}\n{\"bool\":\n")
+        .expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(b"1: "));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn quiet_still_writes_error() {
+    let mut process = Command::new(exe_path())
+        .arg("--quiet")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"bool\":").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Error["));
+}
+
+#[test]
+fn silent_works() {
+    let mut process = Command::new(exe_path())
+        .arg("--silent")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(b"{\"bool\":").expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert_eq!(output.status.code(), Some(i32::from(ExitCode::Transcode)));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn eval_works() {
+    let output = Command::new(exe_path())
+        .arg("-e")
+        .arg("{\"a\":1}")
+        .arg("-t")
+        .arg("yaml")
+        .output()
+        .expect("Run process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).expect("UTF-8"), "a: 1\n");
+}
+
+#[test]
+fn eval_multiple_works() {
+    let output = Command::new(exe_path())
+        .arg("-e")
+        .arg("{\"a\":1}")
+        .arg("--eval")
+        .arg("{\"a\":2}")
+        .arg("-t")
+        .arg("json")
+        .arg("--delimited-output")
+        .arg("0Ah")
+        .output()
+        .expect("Run process");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).expect("UTF-8"), "{\"a\":1}\n{\"a\":2}\n");
+}
+
+#[test]
+fn eval_with_inputs_fails() {
+    let result = Panser::new()
+        .eval(Some(vec!["{\"a\":1}"]))
+        .inputs(Some(vec!["data.json"]))
+        .run();
+    assert!(result.is_err());
+}
+
+/// Runs the panser executable with the arguments and input, and returns the output.
+fn run_with_input(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let mut process = Command::new(exe_path())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(input).expect("Write to stdin");
+    let output = process.wait_with_output().expect("Wait for process");
+    assert!(output.status.success());
+    output.stdout
+}
+
+#[test]
+fn lz4_output_works() {
+    let input = b"{\"bool\":true}\n{\"bool\":false}\n";
+    let args = ["--delimited-input", "0Ah", "--sized-output", "-t", "msgpack"];
+    let original = run_with_input(&args, input);
+    let compressed = run_with_input(&[&args[..], &["--lz4-output"]].concat(), input);
+    assert!(compressed.starts_with(&[0x04, 0x22, 0x4D, 0x18]));
+    // The compression wraps the framed output, so the frames are recovered exactly.
+    let mut decoded = Vec::new();
+    lz4_flex::frame::FrameDecoder::new(&compressed[..])
+        .read_to_end(&mut decoded)
+        .expect("Decompress");
+    assert_eq!(decoded, original);
+}
+
+#[test]
+fn lz4_round_trip_works() {
+    let input = b"{\"bool\":true}\n{\"bool\":false}\n";
+    let args = ["--delimited-input", "0Ah", "--sized-output", "-t", "msgpack"];
+    let original = run_with_input(&args, input);
+    let compressed = run_with_input(&[&args[..], &["--lz4-output"]].concat(), input);
+    let args = ["--lz4-input", "--sized", "-f", "msgpack", "-t", "msgpack"];
+    assert_eq!(run_with_input(&args, &compressed), original);
+}
+
+#[test]
+fn lz4_input_extension_works() {
+    let input = env::temp_dir().join("panser_lz4_input_extension_works.yml.lz4");
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    encoder.write_all(b"bool: true\n").expect("Compress");
+    std::fs::write(&input, encoder.finish().expect("Finish")).expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    std::fs::remove_file(&input).ok();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"bool\":true}");
+}
+
+#[test]
+fn input_utf8_bom_file_works() {
+    let input = env::temp_dir().join("panser_input_utf8_bom_file_works.json");
+    std::fs::write(&input, b"\xEF\xBB\xBF{\"key\":\"\xC3\xA9\"}").expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    std::fs::remove_file(&input).ok();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).expect("UTF-8"), "{\"key\":\"é\"}");
+}
+
+#[test]
+fn input_utf16_bom_works() {
+    let text = "{\"key\":\"日本\"}";
+    let le = text.encode_utf16().flat_map(|unit| unit.to_le_bytes().to_vec());
+    let be = text.encode_utf16().flat_map(|unit| unit.to_be_bytes().to_vec());
+    let inputs = [
+        [0xFF, 0xFE].iter().cloned().chain(le).collect::<Vec<u8>>(),
+        [0xFE, 0xFF].iter().cloned().chain(be).collect::<Vec<u8>>(),
+    ];
+    for input in &inputs {
+        let output = run_with_input(&["-f", "json", "-t", "json"], input);
+        assert_eq!(String::from_utf8(output).expect("UTF-8"), text);
+    }
+}
+
+#[test]
+fn input_utf16_bom_binary_is_unchanged() {
+    // The FFh byte is a Msgpack negative fixint, so the BOM is not detected for a binary format.
+    let output = run_with_input(&["-f", "msgpack", "-t", "json", "-d", "0Ah"], b"\xFF\n\xFE\n");
+    assert_eq!(String::from_utf8(output).expect("UTF-8"), "-1\n-2\n");
+}
+
+#[test]
+fn stdin_works() {
+    let output = run_with_input(&["--stdin", "-t", "json"], b"{\"bool\":true}");
+    assert_eq!(output, b"{\"bool\":true}");
+}
+
+#[test]
+fn stdin_with_files_fails() {
+    let output = Command::new(exe_path())
+        .arg("--stdin")
+        .arg("data.json")
+        .output()
+        .expect("Run process");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn flexbuffers_round_trip_works() {
+    let input = env::temp_dir().join("panser_flexbuffers_round_trip_works.flexbuf");
+    let encoded = run_with_input(&["-t", "flexbuffers"], b"{\"a\":[1,2.5,\"x\",null]}");
+    std::fs::write(&input, encoded).expect("Create input file");
+    let output = Command::new(exe_path())
+        .arg("-t")
+        .arg("json")
+        .arg(&input)
+        .output()
+        .expect("Run process");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{\"a\":[1,2.5,\"x\",null]}");
+}
+
+fn run_with_env(vars: &[(&str, &str)], args: &[&str], input: &[u8]) -> Output {
+    let mut process = Command::new(exe_path())
+        .envs(vars.iter().cloned())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Create process");
+    process.stdin.take().expect("stdin").write_all(input).expect("Write to stdin");
+    process.wait_with_output().expect("Wait for process")
+}
+
+#[test]
+fn env_options_work() {
+    let vars = [
+        ("PANSER_DELIMITER", "0Ah"),
+        ("PANSER_FROM", "yaml"),
+        ("PANSER_RADIX", "dec"),
+        ("PANSER_TO", "json"),
+    ];
+    let output = run_with_env(&vars, &[], b"a: 1\n");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"123 34 97 34 58 49 125 \n");
+}
+
+#[test]
+fn env_options_cli_takes_precedence_works() {
+    let vars = [("PANSER_DELIMITER", ","), ("PANSER_FROM", "yaml"), ("PANSER_TO", "yaml")];
+    let output = run_with_env(&vars, &["--sized-output", "-f", "json", "-t", "json"], b"[1]");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"\x00\x00\x00\x03[1]");
+}
+
+#[test]
+fn env_options_extension_takes_precedence_works() {
+    let input = env::temp_dir().join("panser_env_options_extension_takes_precedence_works.yaml");
+    let output = env::temp_dir().join("panser_env_options_extension_takes_precedence_works.toml");
+    std::fs::write(&input, "a: 1\n").expect("Create input file");
+    let vars = [("PANSER_FROM", "json"), ("PANSER_TO", "json")];
+    let input = input.to_str().expect("Input path");
+    let result = run_with_env(&vars, &[input, "-o", output.to_str().expect("Output path")], b"");
+    assert!(result.status.success());
+    assert_eq!(std::fs::read_to_string(&output).expect("Read output"), "a = 1\n");
+}
+
+#[test]
+fn env_options_empty_is_unset_works() {
+    let output = run_with_env(&[("PANSER_TO", "")], &[], b"{}");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"\x80");
+}
+
+#[test]
+fn env_options_invalid_value_fails() {
+    let output = run_with_env(&[("PANSER_FROM", "bogus")], &["-t", "json"], b"{}");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("PANSER_FROM"));
+}